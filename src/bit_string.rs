@@ -0,0 +1,279 @@
+//! A fixed length string of bits packed into 64 bit blocks, for classic problems such as
+//! OneMax, knapsack and feature selection.
+
+use std::fmt::{self, Display, Formatter};
+
+use rand::{Rng, StdRng};
+
+use ::diversity::HammingDistance;
+use ::error::GeneticError;
+use ::interfaces::distance_metric::DistanceMetric;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitString {
+    blocks: Vec<u64>,
+    length: usize
+}
+
+impl GeneticCollection for BitString {
+    type Child = BitString;
+
+    /// Breeds a child by one point crossover followed by mutation.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>The child bit string if successful.</li>
+    ///     <li>LengthMismatchError if the parents have different lengths.</li>
+    /// </ul>
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.one_point_crossover(mate, rng)?;
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.length
+    }
+}
+
+impl Mutable for BitString {
+    /// Flips each bit with a probability of one over the length of the string.
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        if self.length == 0 {
+            return Ok(());
+        }
+        let mutation_rate = 1f64 / self.length as f64;
+        self.mutate_with_rate(mutation_rate, rng)
+    }
+}
+
+impl Display for BitString {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let bits: String = (0..self.length).map(|index| if self.get(index) { '1' } else { '0' }).collect();
+        write!(f, "{}", bits)
+    }
+}
+
+impl BitString {
+    /// Creates a bit string with every bit cleared.
+    pub fn new(length: usize) -> BitString {
+        BitString { blocks: vec![0; block_count(length)], length }
+    }
+
+    /// Creates a bit string whose bits are each set with a probability of one half.
+    pub fn new_random(length: usize, rng: &mut StdRng) -> BitString {
+        let mut bit_string = BitString {
+            blocks: (0..block_count(length)).map(|_| rng.gen::<u64>()).collect(),
+            length
+        };
+        bit_string.clear_unused_bits();
+        bit_string
+    }
+
+    pub fn from_bools(bits: &[bool]) -> BitString {
+        let mut bit_string = BitString::new(bits.len());
+        for (index, &bit) in bits.iter().enumerate() {
+            bit_string.set(index, bit);
+        }
+        bit_string
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.length, "bit index out of range");
+        self.blocks[index / BLOCK_SIZE] >> (index % BLOCK_SIZE) & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.length, "bit index out of range");
+        let mask = 1u64 << (index % BLOCK_SIZE);
+        if bit {
+            self.blocks[index / BLOCK_SIZE] |= mask;
+        } else {
+            self.blocks[index / BLOCK_SIZE] &= !mask;
+        }
+    }
+
+    pub fn flip(&mut self, index: usize) {
+        assert!(index < self.length, "bit index out of range");
+        self.blocks[index / BLOCK_SIZE] ^= 1u64 << (index % BLOCK_SIZE);
+    }
+
+    pub fn to_bools(&self) -> Vec<bool> {
+        (0..self.length).map(|index| self.get(index)).collect()
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.blocks.iter().map(|block| block.count_ones() as usize).sum()
+    }
+
+    pub fn count_zeros(&self) -> usize {
+        self.length - self.count_ones()
+    }
+
+    /// The number of positions at which two equally long bit strings differ.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>The distance if successful.</li>
+    ///     <li>LengthMismatchError if the bit strings have different lengths.</li>
+    /// </ul>
+    pub fn hamming_distance(&self, other: &BitString) -> GeneticResult<usize> {
+        self.check_length(other)?;
+        Ok(self.blocks.iter().zip(&other.blocks).map(|(a, b)| (a ^ b).count_ones() as usize).sum())
+    }
+
+    /// Flips each bit independently with the given probability.
+    pub fn mutate_with_rate(&mut self, mutation_rate: f64, rng: &mut StdRng) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&mutation_rate) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        if mutation_rate == 0f64 {
+            return Ok(());
+        }
+        // Jump straight from one flipped bit to the next by drawing the geometrically
+        // distributed gaps between them, instead of drawing a number for every bit.
+        let log_keep = (1f64 - mutation_rate).ln();
+        let mut index = 0usize;
+        loop {
+            let gap = if log_keep == f64::NEG_INFINITY {
+                0f64
+            } else {
+                (rng.gen::<f64>().ln() / log_keep).floor()
+            };
+            if gap >= (self.length - index) as f64 {
+                return Ok(());
+            }
+            index += gap as usize;
+            self.flip(index);
+            index += 1;
+        }
+    }
+
+    /// Takes the bits before a random point from self and the rest from the mate.
+    pub fn one_point_crossover(&self, mate: &BitString, rng: &mut StdRng) -> GeneticResult<BitString> {
+        self.check_length(mate)?;
+        let point = rng.gen_range(0, self.length + 1);
+        Ok(self.splice(mate, |index| index < point))
+    }
+
+    /// Takes the bits between two random points from the mate and the rest from self.
+    pub fn two_point_crossover(&self, mate: &BitString, rng: &mut StdRng) -> GeneticResult<BitString> {
+        self.check_length(mate)?;
+        let first = rng.gen_range(0, self.length + 1);
+        let second = rng.gen_range(0, self.length + 1);
+        let (start, end) = (first.min(second), first.max(second));
+        Ok(self.splice(mate, |index| index < start || index >= end))
+    }
+
+    /// Takes each bit from self or the mate with equal probability.
+    pub fn uniform_crossover(&self, mate: &BitString, rng: &mut StdRng) -> GeneticResult<BitString> {
+        self.check_length(mate)?;
+        let mut child = self.clone();
+        for (block, (own, other)) in child.blocks.iter_mut().zip(self.blocks.iter().zip(&mate.blocks)) {
+            let from_self = rng.gen::<u64>();
+            *block = (own & from_self) | (other & !from_self);
+        }
+        Ok(child)
+    }
+
+    fn splice<P: Fn(usize) -> bool>(&self, mate: &BitString, from_self: P) -> BitString {
+        let mut child = BitString::new(self.length);
+        for index in 0..self.length {
+            let bit = if from_self(index) { self.get(index) } else { mate.get(index) };
+            child.set(index, bit);
+        }
+        child
+    }
+
+    fn check_length(&self, other: &BitString) -> GeneticResult<()> {
+        if self.length == other.length {
+            Ok(())
+        } else {
+            Err(GeneticError::LengthMismatchError)
+        }
+    }
+
+    fn clear_unused_bits(&mut self) {
+        let used_bits = self.length % BLOCK_SIZE;
+        if used_bits != 0 {
+            if let Some(last) = self.blocks.last_mut() {
+                *last &= (1u64 << used_bits) - 1;
+            }
+        }
+    }
+}
+
+impl DistanceMetric<BitString> for HammingDistance {
+    fn distance(&self, a: &BitString, b: &BitString) -> GeneticResult<f64> {
+        Ok(a.hamming_distance(b)? as f64)
+    }
+}
+
+fn block_count(length: usize) -> usize {
+    length.div_ceil(BLOCK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::interfaces::fitness_function::{FitnessFunction, TargetFitness};
+
+    #[test]
+    fn popcount_helpers_count_set_and_cleared_bits() {
+        let mut bit_string = BitString::new(130);
+        bit_string.set(0, true);
+        bit_string.set(64, true);
+        bit_string.set(129, true);
+        bit_string.flip(64);
+        assert_eq!(2, bit_string.count_ones());
+        assert_eq!(128, bit_string.count_zeros());
+        assert_eq!(2, bit_string.hamming_distance(&BitString::new(130)).unwrap());
+        assert!(bit_string.hamming_distance(&BitString::new(3)).is_err());
+        assert!(matches!(TargetFitness::new(2f64).evaluate(&bit_string), Err(GeneticError::NoTargetFitnessError)));
+    }
+
+    #[test]
+    fn random_bit_string_has_no_bits_past_its_length() {
+        let mut rng = StdRng::from_seed(&[4usize][..]);
+        let bit_string = BitString::new_random(70, &mut rng);
+        let round_trip = BitString::from_bools(&bit_string.to_bools());
+        assert_eq!(round_trip, bit_string);
+        assert_eq!(70, bit_string.to_string().len());
+    }
+
+    #[test]
+    fn every_crossover_takes_each_bit_from_one_of_the_parents() {
+        let mut rng = StdRng::from_seed(&[5usize][..]);
+        let ones = BitString::from_bools(&[true; 100]);
+        let zeros = BitString::new(100);
+        let children = vec![
+            ones.one_point_crossover(&zeros, &mut rng).unwrap(),
+            ones.two_point_crossover(&zeros, &mut rng).unwrap(),
+            ones.uniform_crossover(&zeros, &mut rng).unwrap()
+        ];
+        for child in children {
+            assert_eq!(100, child.get_size());
+            assert_eq!(100, child.hamming_distance(&ones).unwrap() + child.hamming_distance(&zeros).unwrap());
+        }
+        let two_point = ones.two_point_crossover(&zeros, &mut rng).unwrap();
+        let bits = two_point.to_bools();
+        let switches = bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert!(switches <= 2);
+    }
+
+    #[test]
+    fn mutation_rate_of_one_flips_every_bit() {
+        let mut rng = StdRng::from_seed(&[6usize][..]);
+        let mut bit_string = BitString::new(75);
+        bit_string.mutate_with_rate(1f64, &mut rng).unwrap();
+        assert_eq!(75, bit_string.count_ones());
+        bit_string.mutate_with_rate(0f64, &mut rng).unwrap();
+        assert_eq!(75, bit_string.count_ones());
+    }
+}
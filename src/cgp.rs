@@ -0,0 +1,409 @@
+//! Cartesian genetic programming.
+//!
+//! The genome is a fixed grid of nodes, each with a function gene and two connection genes,
+//! followed by one gene per output naming the node it reads. A node may only connect to the
+//! inputs or to nodes in the few columns before it, so the program is a directed acyclic
+//! graph and, unlike a `GeneticString` expression, one node can feed many others. Nodes no
+//! output depends on are inactive and ignored when evaluating.
+//!
+//! CGP is usually evolved by mutation alone with the (1+λ) strategy of
+//! `replacement::OnePlusLambda`.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// The number of genes describing one node.
+const NODE_GENES: usize = 3;
+const DEFAULT_MUTATION_RATE: f64 = 0.03;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CgpFunction {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    And,
+    Or,
+    Xor,
+    Nand
+}
+
+impl CgpFunction {
+    /// Applies the function with the same checked arithmetic as `GeneticString`; the logic
+    /// functions work bitwise.
+    fn apply(&self, a: i32, b: i32) -> GeneticResult<i32> {
+        let result = match *self {
+            CgpFunction::Add => a.checked_add(b),
+            CgpFunction::Subtract => a.checked_sub(b),
+            CgpFunction::Multiply => a.checked_mul(b),
+            CgpFunction::Divide => a.checked_div(b),
+            CgpFunction::And => Some(a & b),
+            CgpFunction::Or => Some(a | b),
+            CgpFunction::Xor => Some(a ^ b),
+            CgpFunction::Nand => Some(!(a & b))
+        };
+        result.ok_or(GeneticError::ComputationError)
+    }
+
+    fn get_symbol(&self) -> &'static str {
+        match *self {
+            CgpFunction::Add => "+",
+            CgpFunction::Subtract => "-",
+            CgpFunction::Multiply => "*",
+            CgpFunction::Divide => "/",
+            CgpFunction::And => "and",
+            CgpFunction::Or => "or",
+            CgpFunction::Xor => "xor",
+            CgpFunction::Nand => "nand"
+        }
+    }
+}
+
+/// The shape of the grid shared by a population of genomes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgpParameters {
+    inputs: usize,
+    outputs: usize,
+    rows: usize,
+    columns: usize,
+    levels_back: usize,
+    functions: Vec<CgpFunction>
+}
+
+impl CgpParameters {
+    /// # Parameters
+    /// <ul>
+    /// <li>inputs - the number of program inputs</li>
+    /// <li>outputs - the number of program outputs</li>
+    /// <li>rows, columns - the size of the grid</li>
+    /// <li>levels_back - how many columns back a node may connect to</li>
+    /// <li>functions - the functions nodes may compute</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The parameters if successful.</li>
+    /// <li>InvalidParameterError if there are no outputs, no nodes, no functions or no
+    /// inputs, or levels back is zero.</li>
+    /// </ul>
+    pub fn new(inputs: usize, outputs: usize, rows: usize, columns: usize, levels_back: usize,
+               functions: Vec<CgpFunction>) -> GeneticResult<CgpParameters> {
+        if inputs == 0 || outputs == 0 || rows == 0 || columns == 0 || levels_back == 0 || functions.is_empty() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(CgpParameters { inputs, outputs, rows, columns, levels_back, functions })
+    }
+
+    pub fn get_inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn get_outputs(&self) -> usize {
+        self.outputs
+    }
+
+    pub fn get_node_count(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    pub fn get_functions(&self) -> &[CgpFunction] {
+        &self.functions
+    }
+
+    fn gene_count(&self) -> usize {
+        self.get_node_count() * NODE_GENES + self.outputs
+    }
+
+    /// The addresses a connection gene may hold, as the first address and how many follow
+    /// it. Inputs always come first, so the range is split in two.
+    fn connection_range(&self, node: usize) -> (usize, usize) {
+        let column = node / self.rows;
+        let first_column = column.saturating_sub(self.levels_back);
+        (self.inputs + first_column * self.rows, (column - first_column) * self.rows)
+    }
+
+    /// The number of values the gene at the given position can take.
+    fn gene_choices(&self, gene: usize) -> usize {
+        let node = gene / NODE_GENES;
+        if node >= self.get_node_count() {
+            self.inputs + self.get_node_count()
+        } else if gene.is_multiple_of(NODE_GENES) {
+            self.functions.len()
+        } else {
+            self.inputs + self.connection_range(node).1
+        }
+    }
+
+    /// Turns a choice in `0..gene_choices(gene)` into the value of the gene.
+    fn gene_value(&self, gene: usize, choice: usize) -> usize {
+        let node = gene / NODE_GENES;
+        if node >= self.get_node_count() || gene.is_multiple_of(NODE_GENES) || choice < self.inputs {
+            choice
+        } else {
+            self.connection_range(node).0 + choice - self.inputs
+        }
+    }
+
+    fn is_valid_gene(&self, gene: usize, value: usize) -> bool {
+        let node = gene / NODE_GENES;
+        if node >= self.get_node_count() || gene.is_multiple_of(NODE_GENES) {
+            value < self.gene_choices(gene)
+        } else {
+            let (first, count) = self.connection_range(node);
+            value < self.inputs || (value >= first && value < first + count)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CgpGenome {
+    genes: Vec<usize>,
+    parameters: Arc<CgpParameters>,
+    mutation_rate: f64
+}
+
+impl GeneticCollection for CgpGenome {
+    type Child = CgpGenome;
+
+    /// Breeds a child by point mutation of self alone. CGP doesn't use crossover, so the mate
+    /// is ignored.
+    fn breed(&self, _mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.clone();
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.genes.len()
+    }
+}
+
+impl Mutable for CgpGenome {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        self.point_mutation(rng);
+        Ok(())
+    }
+}
+
+impl PartialEq for CgpGenome {
+    fn eq(&self, other: &CgpGenome) -> bool {
+        self.genes == other.genes
+    }
+}
+
+impl Eq for CgpGenome {}
+
+impl Hash for CgpGenome {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.genes.hash(state);
+    }
+}
+
+impl Display for CgpGenome {
+    /// Writes the active nodes and the outputs, one per line, e.g. `n2 = x0 xor x1`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let address = |address: usize| if address < self.parameters.inputs {
+            format!("x{}", address)
+        } else {
+            format!("n{}", address - self.parameters.inputs)
+        };
+        for node in self.active_nodes() {
+            let (function, first, second) = self.node(node);
+            writeln!(f, "n{} = {} {} {}", node, address(first), function.get_symbol(), address(second))?;
+        }
+        for (output, &source) in self.output_genes().iter().enumerate() {
+            if output > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "o{} = {}", output, address(source))?;
+        }
+        Ok(())
+    }
+}
+
+impl CgpGenome {
+    /// Creates a genome with random genes. Each gene mutates with a probability of 0.03 until
+    /// configured otherwise.
+    pub fn new(parameters: Arc<CgpParameters>, rng: &mut StdRng) -> CgpGenome {
+        let genes = (0..parameters.gene_count())
+            .map(|gene| parameters.gene_value(gene, rng.gen_range(0, parameters.gene_choices(gene))))
+            .collect();
+        CgpGenome { genes, parameters, mutation_rate: DEFAULT_MUTATION_RATE }
+    }
+
+    /// Creates a genome from its genes: for every node its function index and two connection
+    /// addresses, then the address of every output. Addresses below the number of inputs are
+    /// inputs; the rest are nodes, counted down each column in turn.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The genome if successful.</li>
+    /// <li>LengthMismatchError if the number of genes doesn't fit the parameters.</li>
+    /// <li>InvalidParameterError if a gene names an unknown function or a connection reaches
+    /// forwards or further back than levels back allows.</li>
+    /// </ul>
+    pub fn from_genes(genes: Vec<usize>, parameters: Arc<CgpParameters>) -> GeneticResult<CgpGenome> {
+        if genes.len() != parameters.gene_count() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        if !genes.iter().enumerate().all(|(gene, &value)| parameters.is_valid_gene(gene, value)) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(CgpGenome { genes, parameters, mutation_rate: DEFAULT_MUTATION_RATE })
+    }
+
+    pub fn get_genes(&self) -> &[usize] {
+        &self.genes
+    }
+
+    pub fn get_parameters(&self) -> &Arc<CgpParameters> {
+        &self.parameters
+    }
+
+    pub fn set_mutation_rate(&mut self, mutation_rate: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&mutation_rate) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.mutation_rate = mutation_rate;
+        Ok(())
+    }
+
+    /// The nodes the outputs depend on, in the order they are evaluated.
+    pub fn active_nodes(&self) -> Vec<usize> {
+        let inputs = self.parameters.inputs;
+        let mut active = vec![false; self.parameters.get_node_count()];
+        let mut pending: Vec<usize> = self.output_genes().to_vec();
+        while let Some(address) = pending.pop() {
+            if address < inputs || active[address - inputs] {
+                continue;
+            }
+            active[address - inputs] = true;
+            let (_, first, second) = self.node(address - inputs);
+            pending.push(first);
+            pending.push(second);
+        }
+        (0..active.len()).filter(|&node| active[node]).collect()
+    }
+
+    /// Evaluates the active nodes.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The value of every output if successful.</li>
+    /// <li>InvalidParameterError if the number of inputs doesn't match the parameters.</li>
+    /// <li>ComputationError if the arithmetic overflows or divides by zero.</li>
+    /// </ul>
+    pub fn evaluate(&self, inputs: &[i32]) -> GeneticResult<Vec<i32>> {
+        if inputs.len() != self.parameters.inputs {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mut values = inputs.to_vec();
+        values.resize(inputs.len() + self.parameters.get_node_count(), 0);
+        for node in self.active_nodes() {
+            let (function, first, second) = self.node(node);
+            values[inputs.len() + node] = function.apply(values[first], values[second])?;
+        }
+        Ok(self.output_genes().iter().map(|&address| values[address]).collect())
+    }
+
+    /// Changes each gene to a different valid value with the mutation rate as probability.
+    pub fn point_mutation(&mut self, rng: &mut StdRng) {
+        for gene in 0..self.genes.len() {
+            let choices = self.parameters.gene_choices(gene);
+            if choices < 2 || rng.gen::<f64>() >= self.mutation_rate {
+                continue;
+            }
+            let current = self.genes[gene];
+            while self.genes[gene] == current {
+                self.genes[gene] = self.parameters.gene_value(gene, rng.gen_range(0, choices));
+            }
+        }
+    }
+
+    fn node(&self, node: usize) -> (CgpFunction, usize, usize) {
+        let genes = &self.genes[node * NODE_GENES..(node + 1) * NODE_GENES];
+        (self.parameters.functions[genes[0]], genes[1], genes[2])
+    }
+
+    fn output_genes(&self) -> &[usize] {
+        &self.genes[self.parameters.get_node_count() * NODE_GENES..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::genetic_algorithm::GeneticAlgorithm;
+    use ::interfaces::optimizer::Optimizer;
+    use ::replacement::OnePlusLambda;
+
+    fn logic_parameters() -> Arc<CgpParameters> {
+        let functions = vec![CgpFunction::And, CgpFunction::Or, CgpFunction::Nand, CgpFunction::Xor];
+        Arc::new(CgpParameters::new(2, 1, 1, 6, 3, functions).unwrap())
+    }
+
+    #[test]
+    fn evaluation_reuses_nodes_and_skips_inactive_ones() {
+        let parameters = Arc::new(CgpParameters::new(2, 2, 1, 3, 2,
+            vec![CgpFunction::Add, CgpFunction::Multiply, CgpFunction::Divide]).unwrap());
+        // n0 = x0 + x1, n1 = x0 / x1 (inactive), n2 = n0 * n0, outputs n2 and n0.
+        let genes = vec![0, 0, 1, 2, 0, 1, 1, 2, 2, 4, 2];
+        let genome = CgpGenome::from_genes(genes, parameters.clone()).unwrap();
+        assert_eq!(vec![0, 2], genome.active_nodes());
+        assert_eq!(vec![25, 5], genome.evaluate(&[2, 3]).unwrap());
+        // The inactive division by zero is never computed.
+        assert_eq!(vec![16, 4], genome.evaluate(&[4, 0]).unwrap());
+        assert_eq!("n0 = x0 + x1\nn2 = n0 * n0\no0 = n2\no1 = n0", genome.to_string());
+        let forwards = vec![0, 0, 3, 2, 0, 1, 1, 2, 2, 4, 2];
+        assert!(CgpGenome::from_genes(forwards, parameters).is_err());
+    }
+
+    #[test]
+    fn random_and_mutated_genomes_respect_levels_back() {
+        let mut rng = StdRng::from_seed(&[23usize][..]);
+        let parameters = Arc::new(CgpParameters::new(3, 2, 2, 10, 2, vec![CgpFunction::Add, CgpFunction::Xor]).unwrap());
+        let mut genome = CgpGenome::new(parameters.clone(), &mut rng);
+        genome.set_mutation_rate(0.5f64).unwrap();
+        for _ in 0..50 {
+            let mutated = genome.breed(&genome, &mut rng).unwrap();
+            assert_ne!(genome, mutated);
+            assert!(CgpGenome::from_genes(mutated.get_genes().to_vec(), parameters.clone()).is_ok());
+            genome = mutated;
+        }
+    }
+
+    #[test]
+    fn one_plus_lambda_evolves_a_circuit_without_losing_fitness() {
+        let mut rng = StdRng::from_seed(&[24usize][..]);
+        let xor_cases = |genome: &CgpGenome| -> GeneticResult<f64> {
+            let mut correct = 0f64;
+            for &(a, b) in &[(0, 0), (0, 1), (1, 0), (1, 1)] {
+                if genome.evaluate(&[a, b])?[0] & 1 == a ^ b {
+                    correct += 1f64;
+                }
+            }
+            Ok(correct)
+        };
+        let mut parent = CgpGenome::new(logic_parameters(), &mut rng);
+        parent.set_mutation_rate(0.1f64).unwrap();
+        let mut algorithm = GeneticAlgorithm::new(vec![parent], xor_cases).unwrap();
+        algorithm.set_replacement(Box::new(OnePlusLambda::new(4)));
+        let mut previous = algorithm.get_statistics(&mut rng).unwrap().get_best_fitness();
+        for _ in 0..100 {
+            let stats = algorithm.next_generation(&mut rng).unwrap();
+            assert_eq!(1, stats.get_population_size());
+            assert!(stats.get_best_fitness() >= previous);
+            previous = stats.get_best_fitness();
+        }
+        assert_eq!(4f64, previous);
+    }
+}
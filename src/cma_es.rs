@@ -0,0 +1,625 @@
+//! The covariance matrix adaptation evolution strategy (CMA-ES), which samples candidates from
+//! a multivariate normal distribution and adapts its mean, step size and covariance matrix to
+//! the ranking of the candidates. Restarts with a growing population (IPOP) or alternating
+//! large and small populations (BIPOP) help on multimodal problems.
+//!
+//! The eigendecomposition of the covariance matrix is computed with the Jacobi method, so no
+//! linear algebra crate is needed. As everywhere in the crate, higher fitness is better.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use rand::distributions::normal::StandardNormal;
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+/// The run restarts, or is considered converged, once the step size times the largest
+/// standard deviation falls below this fraction of the initial step size.
+const TOLERANCE_X: f64 = 1e-11;
+/// The run restarts, or is considered converged, once the best fitness of recent generations
+/// spans less than this.
+const TOLERANCE_FUN: f64 = 1e-12;
+/// The largest ratio between the largest and smallest eigenvalue of the covariance matrix.
+const MAXIMUM_CONDITION: f64 = 1e14;
+const DEFAULT_BOUND_PENALTY: f64 = 1e4;
+const JACOBI_SWEEPS: usize = 100;
+
+/// How the strategy starts again once it has converged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RestartStrategy {
+    /// The strategy keeps running after it has converged.
+    None,
+    /// Every restart multiplies the population size by the given factor.
+    Ipop { increase_factor: f64 },
+    /// Restarts alternate between doubling a large population and trying a small population
+    /// with a smaller step size, picking whichever regime has used fewer evaluations.
+    Bipop
+}
+
+/// A covariance matrix adaptation evolution strategy maximising a fitness function over real
+/// vectors. Vectors whose evaluation fails get a fitness of negative infinity, so they rank
+/// below every vector that can be evaluated.
+pub struct CmaEs<F> {
+    fitness_function: F,
+    dimension: usize,
+    initial_mean: Vec<f64>,
+    initial_step_size: f64,
+    bounds: Option<Vec<(f64, f64)>>,
+    bound_penalty: f64,
+    restart_strategy: RestartStrategy,
+    default_population_size: usize,
+    large_population_size: usize,
+    large_regime: bool,
+    large_evaluations: usize,
+    small_evaluations: usize,
+    run_evaluations: usize,
+    restarts: usize,
+    state: State,
+    population: Vec<Vec<f64>>,
+    fitness: Vec<f64>,
+    converged: bool,
+    generation: usize,
+    evaluations: usize,
+    progress: Progress<Vec<f64>>
+}
+
+/// The distribution and the strategy parameters of a single run, which are rebuilt whenever
+/// the population size changes.
+struct State {
+    population_size: usize,
+    weights: Vec<f64>,
+    mu_effective: f64,
+    cumulation: f64,
+    step_size_cumulation: f64,
+    rank_one_rate: f64,
+    rank_mu_rate: f64,
+    damping: f64,
+    expected_norm: f64,
+    mean: Vec<f64>,
+    step_size: f64,
+    covariance: Vec<Vec<f64>>,
+    eigenvectors: Vec<Vec<f64>>,
+    eigenvalue_roots: Vec<f64>,
+    evolution_path: Vec<f64>,
+    step_size_path: Vec<f64>,
+    generations: usize,
+    eigen_generation: usize,
+    best_history: VecDeque<f64>
+}
+
+impl State {
+    fn new(mean: Vec<f64>, step_size: f64, population_size: usize) -> State {
+        let dimension = mean.len() as f64;
+        let parents = population_size / 2;
+        let raw: Vec<f64> = (0..parents)
+            .map(|rank| (parents as f64 + 0.5).ln() - (rank as f64 + 1f64).ln())
+            .collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|weight| weight / total).collect();
+        let mu_effective = 1f64 / weights.iter().map(|weight| weight * weight).sum::<f64>();
+        let cumulation = (4f64 + mu_effective / dimension) / (dimension + 4f64 + 2f64 * mu_effective / dimension);
+        let step_size_cumulation = (mu_effective + 2f64) / (dimension + mu_effective + 5f64);
+        let rank_one_rate = 2f64 / ((dimension + 1.3).powi(2) + mu_effective);
+        let rank_mu_rate = (1f64 - rank_one_rate)
+            .min(2f64 * (mu_effective - 2f64 + 1f64 / mu_effective) / ((dimension + 2f64).powi(2) + mu_effective));
+        let damping = 1f64 + 2f64 * (((mu_effective - 1f64) / (dimension + 1f64)).sqrt() - 1f64).max(0f64)
+            + step_size_cumulation;
+        let expected_norm = dimension.sqrt() * (1f64 - 1f64 / (4f64 * dimension) + 1f64 / (21f64 * dimension * dimension));
+        let size = mean.len();
+        State {
+            population_size,
+            weights,
+            mu_effective,
+            cumulation,
+            step_size_cumulation,
+            rank_one_rate,
+            rank_mu_rate,
+            damping,
+            expected_norm,
+            mean,
+            step_size,
+            covariance: identity(size),
+            eigenvectors: identity(size),
+            eigenvalue_roots: vec![1f64; size],
+            evolution_path: vec![0f64; size],
+            step_size_path: vec![0f64; size],
+            generations: 0,
+            eigen_generation: 0,
+            best_history: VecDeque::new()
+        }
+    }
+
+    /// Draws a candidate, mean + step size * B * D * z.
+    fn sample(&self, rng: &mut StdRng) -> Vec<f64> {
+        let size = self.mean.len();
+        let scaled: Vec<f64> = (0..size).map(|index| {
+            let StandardNormal(noise) = rng.gen();
+            self.eigenvalue_roots[index] * noise
+        }).collect();
+        (0..size).map(|row| {
+            let rotated: f64 = (0..size).map(|column| self.eigenvectors[row][column] * scaled[column]).sum();
+            self.mean[row] + self.step_size * rotated
+        }).collect()
+    }
+
+    /// Moves the distribution towards the candidates, which are sorted from fittest to least
+    /// fit.
+    fn update(&mut self, ranked: &[&Vec<f64>]) {
+        let size = self.mean.len();
+        let old_mean = self.mean.clone();
+        let steps: Vec<Vec<f64>> = ranked.iter().take(self.weights.len())
+            .map(|candidate| (0..size).map(|index| (candidate[index] - old_mean[index]) / self.step_size).collect())
+            .collect();
+        let mean_step: Vec<f64> = (0..size)
+            .map(|index| self.weights.iter().zip(&steps).map(|(weight, step)| weight * step[index]).sum())
+            .collect();
+        for (index, step) in mean_step.iter().enumerate() {
+            self.mean[index] = old_mean[index] + self.step_size * step;
+        }
+
+        let whitened = self.inverse_square_root_times(&mean_step);
+        let step_size_factor = (self.step_size_cumulation * (2f64 - self.step_size_cumulation) * self.mu_effective).sqrt();
+        for (path, value) in self.step_size_path.iter_mut().zip(&whitened) {
+            *path = (1f64 - self.step_size_cumulation) * *path + step_size_factor * value;
+        }
+        self.generations += 1;
+        let path_norm = norm(&self.step_size_path);
+        let decay = 1f64 - (1f64 - self.step_size_cumulation).powi(2 * self.generations as i32);
+        let short_path = path_norm / decay.sqrt() / self.expected_norm < 1.4 + 2f64 / (size as f64 + 1f64);
+        let cumulation_factor = (self.cumulation * (2f64 - self.cumulation) * self.mu_effective).sqrt();
+        for (path, step) in self.evolution_path.iter_mut().zip(&mean_step) {
+            *path = (1f64 - self.cumulation) * *path + if short_path { cumulation_factor * step } else { 0f64 };
+        }
+
+        let correction = if short_path { 0f64 } else { self.rank_one_rate * self.cumulation * (2f64 - self.cumulation) };
+        let keep = 1f64 - self.rank_one_rate - self.rank_mu_rate + correction;
+        for row in 0..size {
+            for column in 0..=row {
+                let rank_one = self.evolution_path[row] * self.evolution_path[column];
+                let rank_mu: f64 = self.weights.iter().zip(&steps)
+                    .map(|(weight, step)| weight * step[row] * step[column])
+                    .sum();
+                let value = keep * self.covariance[row][column] + self.rank_one_rate * rank_one
+                    + self.rank_mu_rate * rank_mu;
+                self.covariance[row][column] = value;
+                self.covariance[column][row] = value;
+            }
+        }
+
+        self.step_size *= ((self.step_size_cumulation / self.damping) * (path_norm / self.expected_norm - 1f64)).exp();
+
+        let interval = self.population_size as f64 / (self.rank_one_rate + self.rank_mu_rate) / size as f64 / 10f64;
+        if (self.generations - self.eigen_generation) as f64 >= interval {
+            self.eigen_generation = self.generations;
+            let (eigenvalues, eigenvectors) = symmetric_eigen(&self.covariance);
+            self.eigenvalue_roots = eigenvalues.iter().map(|value| value.max(f64::MIN_POSITIVE).sqrt()).collect();
+            self.eigenvectors = eigenvectors;
+        }
+    }
+
+    /// Computes C^(-1/2) * vector = B * D^-1 * B^T * vector.
+    fn inverse_square_root_times(&self, vector: &[f64]) -> Vec<f64> {
+        let size = vector.len();
+        let projected: Vec<f64> = (0..size).map(|column| {
+            (0..size).map(|row| self.eigenvectors[row][column] * vector[row]).sum::<f64>()
+                / self.eigenvalue_roots[column]
+        }).collect();
+        (0..size).map(|row| (0..size).map(|column| self.eigenvectors[row][column] * projected[column]).sum()).collect()
+    }
+
+    /// Whether the distribution has collapsed, become ill-conditioned or stopped finding
+    /// different fitness values.
+    fn has_converged(&self, initial_step_size: f64, generation_fitness: &[f64]) -> bool {
+        let largest_root = self.eigenvalue_roots.iter().cloned().fold(0f64, f64::max);
+        let smallest_root = self.eigenvalue_roots.iter().cloned().fold(f64::INFINITY, f64::min);
+        let largest_deviation = (0..self.mean.len())
+            .map(|index| self.covariance[index][index].sqrt())
+            .fold(0f64, f64::max);
+        if self.step_size * largest_deviation < TOLERANCE_X * initial_step_size
+            || (largest_root / smallest_root).powi(2) > MAXIMUM_CONDITION
+            || !self.step_size.is_finite() {
+            return true;
+        }
+        let history_length = 10 + (30f64 * self.mean.len() as f64 / self.population_size as f64).ceil() as usize;
+        if self.best_history.len() < history_length {
+            return false;
+        }
+        let range = |values: &mut dyn Iterator<Item = f64>| {
+            let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)));
+            high - low
+        };
+        range(&mut self.best_history.iter().cloned()) < TOLERANCE_FUN
+            && range(&mut generation_fitness.iter().cloned()) < TOLERANCE_FUN
+    }
+}
+
+impl<F> CmaEs<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Creates the strategy with the default population size of 4 + 3 ln(n) candidates, no
+    /// bounds and no restarts.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>initial_mean - the centre of the first distribution</li>
+    /// <li>initial_step_size - the spread of the first distribution in every dimension</li>
+    /// <li>fitness_function - scores a candidate vector, higher is fitter</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The strategy if successful.</li>
+    /// <li>CollectionEmptyError if the mean has no dimensions.</li>
+    /// <li>InvalidParameterError if the step size isn't positive or the mean isn't finite.</li>
+    /// </ul>
+    pub fn new(initial_mean: Vec<f64>, initial_step_size: f64, fitness_function: F) -> GeneticResult<CmaEs<F>> {
+        if initial_mean.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        if !(initial_step_size > 0f64 && initial_step_size.is_finite() && initial_mean.iter().all(|value| value.is_finite())) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let dimension = initial_mean.len();
+        let population_size = 4 + (3f64 * (dimension as f64).ln()).floor() as usize;
+        Ok(CmaEs {
+            fitness_function,
+            dimension,
+            initial_mean: initial_mean.clone(),
+            initial_step_size,
+            bounds: None,
+            bound_penalty: DEFAULT_BOUND_PENALTY,
+            restart_strategy: RestartStrategy::None,
+            default_population_size: population_size,
+            large_population_size: population_size,
+            large_regime: true,
+            large_evaluations: 0,
+            small_evaluations: 0,
+            run_evaluations: 0,
+            restarts: 0,
+            state: State::new(initial_mean, initial_step_size, population_size),
+            population: vec![],
+            fitness: vec![],
+            converged: false,
+            generation: 0,
+            evaluations: 0,
+            progress: Progress::new(f64::NEG_INFINITY)
+        })
+    }
+
+    /// Sets the number of candidates sampled each generation, restarting the adaptation from
+    /// the current mean and step size. With restarts this is the starting population size.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the population has fewer than 2 candidates.</li>
+    /// </ul>
+    pub fn set_population_size(&mut self, population_size: usize) -> GeneticResult<()> {
+        if population_size < 2 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.default_population_size = population_size;
+        self.large_population_size = population_size;
+        let mean = self.state.mean.clone();
+        self.state = State::new(mean, self.state.step_size, population_size);
+        Ok(())
+    }
+
+    /// Limits the search to a box. Candidates outside of it are evaluated at the nearest
+    /// point inside and have their fitness lowered by the penalty weight times the squared
+    /// distance to that point, while the distribution adapts to the original candidates.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>LengthMismatchError if there isn't one bound per dimension.</li>
+    /// <li>InvalidParameterError if a bound is empty or not finite.</li>
+    /// </ul>
+    pub fn set_bounds(&mut self, bounds: Vec<(f64, f64)>) -> GeneticResult<()> {
+        if bounds.len() != self.dimension {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        if !bounds.iter().all(|&(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.bounds = Some(bounds);
+        Ok(())
+    }
+
+    /// Sets how strongly candidates outside the bounds are penalised. Defaults to 10000.
+    pub fn set_bound_penalty(&mut self, bound_penalty: f64) {
+        self.bound_penalty = bound_penalty.max(0f64);
+    }
+
+    pub fn set_restart_strategy(&mut self, restart_strategy: RestartStrategy) {
+        self.restart_strategy = restart_strategy;
+    }
+
+    pub fn get_mean(&self) -> &[f64] {
+        &self.state.mean
+    }
+
+    pub fn get_step_size(&self) -> f64 {
+        self.state.step_size
+    }
+
+    pub fn get_covariance(&self) -> &[Vec<f64>] {
+        &self.state.covariance
+    }
+
+    /// The number of candidates sampled each generation of the current run.
+    pub fn get_population_size(&self) -> usize {
+        self.state.population_size
+    }
+
+    /// The candidates of the last generation as they were evaluated, i.e. within the bounds.
+    pub fn get_population(&self) -> &[Vec<f64>] {
+        &self.population
+    }
+
+    pub fn get_restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// Whether the last generation met a convergence criterion without restarting.
+    pub fn has_converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn restart(&mut self, rng: &mut StdRng) {
+        let run_evaluations = self.evaluations - self.run_evaluations;
+        self.run_evaluations = self.evaluations;
+        if self.large_regime {
+            self.large_evaluations += run_evaluations;
+        } else {
+            self.small_evaluations += run_evaluations;
+        }
+        let (population_size, step_size) = match self.restart_strategy {
+            RestartStrategy::Ipop { increase_factor } => {
+                self.large_population_size = (self.large_population_size as f64 * increase_factor.max(1f64)).ceil() as usize;
+                (self.large_population_size, self.initial_step_size)
+            }
+            RestartStrategy::Bipop if self.restarts > 0 && self.small_evaluations < self.large_evaluations => {
+                self.large_regime = false;
+                let uniform: f64 = rng.gen();
+                let ratio = self.large_population_size as f64 / 2f64 / self.default_population_size as f64;
+                let population_size = (self.default_population_size as f64 * ratio.powf(uniform * uniform)).floor() as usize;
+                let step_size = self.initial_step_size * 10f64.powf(-2f64 * rng.gen::<f64>());
+                (population_size.max(self.default_population_size), step_size)
+            }
+            _ => {
+                self.large_regime = true;
+                self.large_population_size *= 2;
+                (self.large_population_size, self.initial_step_size)
+            }
+        };
+        let mean = match self.bounds {
+            Some(ref bounds) => bounds.iter().map(|&(lower, upper)| lower + rng.gen::<f64>() * (upper - lower)).collect(),
+            None => self.initial_mean.iter()
+                .map(|value| value + self.initial_step_size * (2f64 * rng.gen::<f64>() - 1f64) * 2f64)
+                .collect()
+        };
+        self.restarts += 1;
+        self.converged = false;
+        self.state = State::new(mean, step_size, population_size);
+    }
+
+    /// Evaluates a candidate, moved within the bounds if there are any.
+    fn evaluate(&mut self, candidate: &[f64]) -> (Vec<f64>, f64) {
+        self.evaluations += 1;
+        let (feasible, penalty) = match self.bounds {
+            Some(ref bounds) => {
+                let feasible: Vec<f64> = candidate.iter().zip(bounds)
+                    .map(|(&value, &(lower, upper))| value.max(lower).min(upper))
+                    .collect();
+                let distance: f64 = candidate.iter().zip(&feasible).map(|(a, b)| (a - b) * (a - b)).sum();
+                (feasible, self.bound_penalty * distance)
+            }
+            None => (candidate.to_vec(), 0f64)
+        };
+        let fitness = (self.fitness_function)(&feasible).unwrap_or(self.progress.get_invalid_fitness()) - penalty;
+        (feasible, fitness)
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        GenerationStats::from_real_vectors(self.generation, &self.population, &self.fitness, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        self.progress.update_best(self.population.iter().zip(self.fitness.iter().cloned()), stats);
+    }
+
+}
+
+impl<F> Optimizer<Vec<f64>> for CmaEs<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Summarises the last generation. Before the first generation the initial mean is
+    /// evaluated and summarised as a population of one.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if self.population.is_empty() {
+            let mean = self.state.mean.clone();
+            let fitness = self.evaluate(&mean);
+            self.population = vec![fitness.0];
+            self.fitness = vec![fitness.1];
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Samples and evaluates a generation, adapts the distribution to it and restarts if the
+    /// run has converged and a restart strategy is set.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        let candidates: Vec<Vec<f64>> = (0..self.state.population_size).map(|_| self.state.sample(rng)).collect();
+        let evaluated: Vec<(Vec<f64>, f64)> = candidates.iter().map(|candidate| self.evaluate(candidate)).collect();
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| evaluated[b].1.partial_cmp(&evaluated[a].1).unwrap_or(Ordering::Equal));
+        let ranked: Vec<&Vec<f64>> = order.iter().map(|&index| &candidates[index]).collect();
+        self.state.update(&ranked);
+        let (population, fitness): (Vec<Vec<f64>>, Vec<f64>) = evaluated.into_iter().unzip();
+        self.population = population;
+        self.fitness = fitness;
+        self.state.best_history.push_back(self.fitness[order[0]]);
+        let history_length = 10 + (30f64 * self.dimension as f64 / self.state.population_size as f64).ceil() as usize;
+        while self.state.best_history.len() > history_length {
+            self.state.best_history.pop_front();
+        }
+        self.converged = self.state.has_converged(self.initial_step_size, &self.fitness);
+        if self.converged && self.restart_strategy != RestartStrategy::None {
+            self.restart(rng);
+        }
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<Vec<f64>> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<Vec<f64>> {
+        &mut self.progress
+    }
+}
+
+/// Computes the eigenvalues and eigenvectors of a symmetric matrix with the cyclic Jacobi
+/// method. The eigenvectors are the columns of the returned matrix.
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = matrix.len();
+    let mut matrix = matrix.to_vec();
+    let mut eigenvectors = identity(size);
+    for _ in 0..JACOBI_SWEEPS {
+        let off_diagonal: f64 = (0..size)
+            .flat_map(|row| (row + 1..size).map(move |column| (row, column)))
+            .map(|(row, column)| matrix[row][column] * matrix[row][column])
+            .sum();
+        let diagonal: f64 = (0..size).map(|index| matrix[index][index] * matrix[index][index]).sum();
+        if off_diagonal <= 1e-30 * diagonal || off_diagonal == 0f64 {
+            break;
+        }
+        for p in 0..size {
+            for q in p + 1..size {
+                if matrix[p][q] == 0f64 {
+                    continue;
+                }
+                let theta = (matrix[q][q] - matrix[p][p]) / (2f64 * matrix[p][q]);
+                let tangent = theta.signum() / (theta.abs() + (theta * theta + 1f64).sqrt());
+                let cosine = 1f64 / (tangent * tangent + 1f64).sqrt();
+                let sine = tangent * cosine;
+                for row in matrix.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = cosine * kp - sine * kq;
+                    row[q] = sine * kp + cosine * kq;
+                }
+                let (head, tail) = matrix.split_at_mut(q);
+                for (pk, qk) in head[p].iter_mut().zip(tail[0].iter_mut()) {
+                    let (first, second) = (*pk, *qk);
+                    *pk = cosine * first - sine * second;
+                    *qk = sine * first + cosine * second;
+                }
+                for row in eigenvectors.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = cosine * kp - sine * kq;
+                    row[q] = sine * kp + cosine * kq;
+                }
+            }
+        }
+    }
+    ((0..size).map(|index| matrix[index][index]).collect(), eigenvectors)
+}
+
+fn identity(size: usize) -> Vec<Vec<f64>> {
+    (0..size).map(|row| (0..size).map(|column| if row == column { 1f64 } else { 0f64 }).collect()).collect()
+}
+
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|value| value * value).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use rand::SeedableRng;
+    use ::termination::{any, FitnessThreshold, MaxEvaluations, MaxGenerations};
+
+    fn rosenbrock(vector: &[f64]) -> GeneticResult<f64> {
+        Ok(-vector.windows(2)
+            .map(|pair| 100f64 * (pair[1] - pair[0] * pair[0]).powi(2) + (1f64 - pair[0]).powi(2))
+            .sum::<f64>())
+    }
+
+    fn rastrigin(vector: &[f64]) -> GeneticResult<f64> {
+        Ok(-vector.iter()
+            .map(|value| value * value - 10f64 * (2f64 * PI * value).cos() + 10f64)
+            .sum::<f64>())
+    }
+
+    #[test]
+    fn jacobi_eigendecomposition_reconstructs_the_matrix() {
+        let matrix = vec![vec![4f64, 1f64, 2f64], vec![1f64, 3f64, 0.5], vec![2f64, 0.5, 5f64]];
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&matrix);
+        for row in 0..3 {
+            for column in 0..3 {
+                let rebuilt: f64 = (0..3)
+                    .map(|index| eigenvectors[row][index] * eigenvalues[index] * eigenvectors[column][index])
+                    .sum();
+                assert!((rebuilt - matrix[row][column]).abs() < 1e-10);
+                let dot: f64 = (0..3).map(|index| eigenvectors[index][row] * eigenvectors[index][column]).sum();
+                assert!((dot - if row == column { 1f64 } else { 0f64 }).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn cma_es_finds_the_valley_floor_of_rosenbrock() {
+        let mut rng = StdRng::from_seed(&[70usize][..]);
+        let mut strategy = CmaEs::new(vec![0f64; 4], 0.5, rosenbrock).unwrap();
+        let mut termination = any(vec![Box::new(MaxGenerations::new(2000)), Box::new(FitnessThreshold::new(-1e-10))]);
+        let stats = strategy.run(&mut termination, &mut rng).unwrap();
+        assert!(stats.get_best_fitness() > -1e-10);
+        let best = strategy.get_best().unwrap().get_genome();
+        assert!(best.iter().all(|value| (value - 1f64).abs() < 1e-3));
+    }
+
+    #[test]
+    fn candidates_are_evaluated_within_the_bounds() {
+        let mut rng = StdRng::from_seed(&[71usize][..]);
+        let shifted_sphere = |vector: &[f64]| Ok(-vector.iter().map(|value| (value - 10f64).powi(2)).sum::<f64>());
+        let mut strategy = CmaEs::new(vec![0f64; 3], 1f64, shifted_sphere).unwrap();
+        strategy.set_bounds(vec![(-5f64, 5f64); 3]).unwrap();
+        for _ in 0..200 {
+            strategy.next_generation(&mut rng).unwrap();
+            assert!(strategy.get_population().iter().flatten().all(|value| (-5f64..=5f64).contains(value)));
+        }
+        let best = strategy.get_best().unwrap().get_genome();
+        assert!(best.iter().all(|value| (value - 5f64).abs() < 1e-6));
+        assert!(strategy.set_bounds(vec![(0f64, 1f64)]).is_err());
+    }
+
+    #[test]
+    fn restarts_grow_the_population_on_a_multimodal_problem() {
+        for (seed, &restart_strategy) in [RestartStrategy::Ipop { increase_factor: 2f64 }, RestartStrategy::Bipop].iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[72 + seed][..]);
+            let mut strategy = CmaEs::new(vec![3f64; 3], 2f64, rastrigin).unwrap();
+            strategy.set_bounds(vec![(-5.12, 5.12); 3]).unwrap();
+            strategy.set_restart_strategy(restart_strategy);
+            let mut termination = any(vec![Box::new(MaxEvaluations::new(100000)), Box::new(FitnessThreshold::new(-1e-8))]);
+            let stats = strategy.run(&mut termination, &mut rng).unwrap();
+            assert!(strategy.get_restarts() > 0);
+            assert!(stats.get_best_fitness() > -1e-8, "{:?} reached {}", restart_strategy, stats.get_best_fitness());
+        }
+    }
+}
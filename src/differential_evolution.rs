@@ -0,0 +1,525 @@
+//! Differential evolution, which evolves a population of real vectors by adding scaled
+//! differences between members of the population to other members.
+//!
+//! The fitness function is a closure over the candidate vector. As everywhere in the crate,
+//! higher fitness is better, so minimising a cost means returning its negation.
+
+use std::f64::consts::PI;
+
+use rand::distributions::normal::StandardNormal;
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+const DEFAULT_SCALE_FACTOR: f64 = 0.5;
+const DEFAULT_CROSSOVER_RATE: f64 = 0.9;
+/// The probability with which jDE draws a new scale factor or crossover rate for a target.
+const JDE_RESAMPLE_PROBABILITY: f64 = 0.1;
+const JDE_MINIMUM_SCALE_FACTOR: f64 = 0.1;
+/// The spread of the distributions SHADE draws its parameters from around its memory.
+const SHADE_SPREAD: f64 = 0.1;
+/// The largest share of the population SHADE picks the "best" of current-to-best/1 from.
+const SHADE_MAXIMUM_GREEDINESS: f64 = 0.2;
+
+/// How the donor vector of each target is built. Every strategy is followed by binomial
+/// crossover between the donor and the target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeStrategy {
+    /// rand/1: a random member plus one scaled difference.
+    RandOne,
+    /// best/1: the fittest member plus one scaled difference.
+    BestOne,
+    /// current-to-best/1: the target moved towards the fittest member, plus one scaled
+    /// difference.
+    CurrentToBestOne,
+    /// rand/2: a random member plus two scaled differences. Needs at least six members.
+    RandTwo
+}
+
+/// How the scale factor and crossover rate are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParameterControl {
+    /// The configured scale factor and crossover rate are used throughout.
+    Fixed,
+    /// jDE: every member carries its own parameters, which are occasionally redrawn and
+    /// survive along with the trial vectors they produced.
+    JDe,
+    /// SHADE: parameters are drawn around a memory of the parameters that recently produced
+    /// improvements. With current-to-best/1 the "best" is drawn from the fittest few members
+    /// and the second difference may use parents recently replaced.
+    Shade { memory_size: usize }
+}
+
+/// A differential evolution optimizer maximising a fitness function over a box of real
+/// vectors. Vectors whose evaluation fails get a fitness of negative infinity, so they never
+/// replace a vector that can be evaluated.
+pub struct DifferentialEvolution<F> {
+    population: Vec<Vec<f64>>,
+    fitness: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+    fitness_function: F,
+    strategy: DeStrategy,
+    control: ParameterControl,
+    scale_factor: f64,
+    crossover_rate: f64,
+    member_parameters: Vec<(f64, f64)>,
+    memory: Vec<(f64, f64)>,
+    memory_index: usize,
+    archive: Vec<Vec<f64>>,
+    generation: usize,
+    evaluations: usize,
+    evaluated: bool,
+    progress: Progress<Vec<f64>>
+}
+
+impl<F> DifferentialEvolution<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Creates an optimizer whose population is spread uniformly over the bounds. It uses
+    /// rand/1/bin with a scale factor of 0.5 and a crossover rate of 0.9 until configured
+    /// otherwise. The population is evaluated when the first generation is requested.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>population_size - the number of candidate vectors, at least 4</li>
+    /// <li>bounds - the lower and upper bound of each dimension</li>
+    /// <li>fitness_function - scores a candidate vector, higher is fitter</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The optimizer if successful.</li>
+    /// <li>CollectionEmptyError if there are no bounds.</li>
+    /// <li>InvalidParameterError if the population is too small or a bound is empty.</li>
+    /// </ul>
+    pub fn new(population_size: usize, bounds: Vec<(f64, f64)>, fitness_function: F, rng: &mut StdRng)
+               -> GeneticResult<DifferentialEvolution<F>> {
+        if bounds.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        if population_size < 4 || !bounds.iter()
+            .all(|&(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let population = (0..population_size)
+            .map(|_| bounds.iter().map(|&(lower, upper)| lower + rng.gen::<f64>() * (upper - lower)).collect())
+            .collect();
+        Ok(DifferentialEvolution {
+            population,
+            fitness: vec![0f64; population_size],
+            bounds,
+            fitness_function,
+            strategy: DeStrategy::RandOne,
+            control: ParameterControl::Fixed,
+            scale_factor: DEFAULT_SCALE_FACTOR,
+            crossover_rate: DEFAULT_CROSSOVER_RATE,
+            member_parameters: vec![],
+            memory: vec![],
+            memory_index: 0,
+            archive: vec![],
+            generation: 0,
+            evaluations: 0,
+            evaluated: false,
+            progress: Progress::new(f64::NEG_INFINITY)
+        })
+    }
+
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the strategy needs more members than the population has.</li>
+    /// </ul>
+    pub fn set_strategy(&mut self, strategy: DeStrategy) -> GeneticResult<()> {
+        if strategy == DeStrategy::RandTwo && self.population.len() < 6 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.strategy = strategy;
+        Ok(())
+    }
+
+    /// Sets how the scale factor and crossover rate are chosen, starting the adaptation of
+    /// jDE and SHADE afresh.
+    pub fn set_parameter_control(&mut self, control: ParameterControl) {
+        self.control = control;
+        self.member_parameters = match control {
+            ParameterControl::JDe => vec![(self.scale_factor, self.crossover_rate); self.population.len()],
+            _ => vec![]
+        };
+        self.memory = match control {
+            ParameterControl::Shade { memory_size } => vec![(0.5, 0.5); memory_size.max(1)],
+            _ => vec![]
+        };
+        self.memory_index = 0;
+        self.archive.clear();
+    }
+
+    /// Sets the scale factor used with fixed parameters, and the starting scale factor of jDE.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the scale factor isn't greater than 0 and at most 2.</li>
+    /// </ul>
+    pub fn set_scale_factor(&mut self, scale_factor: f64) -> GeneticResult<()> {
+        if !(scale_factor > 0f64 && scale_factor <= 2f64) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.scale_factor = scale_factor;
+        Ok(())
+    }
+
+    /// Sets the crossover rate used with fixed parameters, and the starting crossover rate of
+    /// jDE.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the rate isn't between 0 and 1.</li>
+    /// </ul>
+    pub fn set_crossover_rate(&mut self, crossover_rate: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&crossover_rate) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.crossover_rate = crossover_rate;
+        Ok(())
+    }
+
+    pub fn get_population(&self) -> &[Vec<f64>] {
+        &self.population
+    }
+
+    /// The fitness of each member of the population, once it has been evaluated.
+    pub fn get_fitness(&self) -> &[f64] {
+        &self.fitness
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// The scale factor and crossover rate each member carries under jDE, or the memory of
+    /// successful parameters under SHADE. Empty with fixed parameters.
+    pub fn get_adapted_parameters(&self) -> &[(f64, f64)] {
+        match self.control {
+            ParameterControl::JDe => &self.member_parameters,
+            ParameterControl::Shade { .. } => &self.memory,
+            ParameterControl::Fixed => &[]
+        }
+    }
+
+    fn draw_parameters(&self, target: usize, rng: &mut StdRng) -> (f64, f64) {
+        match self.control {
+            ParameterControl::Fixed => (self.scale_factor, self.crossover_rate),
+            ParameterControl::JDe => {
+                let (mut scale_factor, mut crossover_rate) = self.member_parameters[target];
+                if rng.gen::<f64>() < JDE_RESAMPLE_PROBABILITY {
+                    scale_factor = JDE_MINIMUM_SCALE_FACTOR + rng.gen::<f64>() * (1f64 - JDE_MINIMUM_SCALE_FACTOR);
+                }
+                if rng.gen::<f64>() < JDE_RESAMPLE_PROBABILITY {
+                    crossover_rate = rng.gen();
+                }
+                (scale_factor, crossover_rate)
+            }
+            ParameterControl::Shade { .. } => {
+                let (mean_scale_factor, mean_crossover_rate) = self.memory[rng.gen_range(0, self.memory.len())];
+                let StandardNormal(noise) = rng.gen();
+                let crossover_rate = (mean_crossover_rate + SHADE_SPREAD * noise).clamp(0f64, 1f64);
+                let mut scale_factor = 0f64;
+                while scale_factor <= 0f64 {
+                    let cauchy = (PI * (rng.gen::<f64>() - 0.5)).tan();
+                    scale_factor = mean_scale_factor + SHADE_SPREAD * cauchy;
+                }
+                (scale_factor.min(1f64), crossover_rate)
+            }
+        }
+    }
+
+    fn donor(&self, target: usize, best: usize, ranking: &[usize], scale_factor: f64, rng: &mut StdRng) -> Vec<f64> {
+        let size = self.population.len();
+        let member = |index: usize| &self.population[index];
+        let shade = matches!(self.control, ParameterControl::Shade { .. });
+        match self.strategy {
+            DeStrategy::RandOne => {
+                let others = distinct_others(target, 3, size, rng);
+                combine(member(others[0]), &[(scale_factor, member(others[1]), member(others[2]))])
+            }
+            DeStrategy::BestOne => {
+                let others = distinct_others(target, 2, size, rng);
+                combine(member(best), &[(scale_factor, member(others[0]), member(others[1]))])
+            }
+            DeStrategy::CurrentToBestOne => {
+                let best = if shade {
+                    let greediness = 2f64 / size as f64
+                        + rng.gen::<f64>() * (SHADE_MAXIMUM_GREEDINESS - 2f64 / size as f64).max(0f64);
+                    let fittest = ((greediness * size as f64).round() as usize).max(1);
+                    ranking[rng.gen_range(0, fittest)]
+                } else {
+                    best
+                };
+                let others = distinct_others(target, 2, size, rng);
+                let first = others[0];
+                let second = if shade {
+                    let mut index = rng.gen_range(0, size + self.archive.len());
+                    while index == target || index == first {
+                        index = rng.gen_range(0, size + self.archive.len());
+                    }
+                    if index < size { member(index) } else { &self.archive[index - size] }
+                } else {
+                    member(others[1])
+                };
+                combine(member(target), &[(scale_factor, member(best), member(target)),
+                                          (scale_factor, member(first), second)])
+            }
+            DeStrategy::RandTwo => {
+                let others = distinct_others(target, 5, size, rng);
+                combine(member(others[0]), &[(scale_factor, member(others[1]), member(others[2])),
+                                             (scale_factor, member(others[3]), member(others[4]))])
+            }
+        }
+    }
+
+    /// Binomial crossover. At least one value always comes from the donor, and donor values
+    /// outside the bounds are replaced by the midpoint between the target value and the bound.
+    fn crossover(&self, target: usize, donor: &[f64], crossover_rate: f64, rng: &mut StdRng) -> Vec<f64> {
+        let forced = rng.gen_range(0, donor.len());
+        let target = &self.population[target];
+        (0..donor.len()).map(|dimension| {
+            if dimension != forced && rng.gen::<f64>() >= crossover_rate {
+                return target[dimension];
+            }
+            let (lower, upper) = self.bounds[dimension];
+            if donor[dimension] < lower {
+                (lower + target[dimension]) / 2f64
+            } else if donor[dimension] > upper {
+                (upper + target[dimension]) / 2f64
+            } else {
+                donor[dimension]
+            }
+        }).collect()
+    }
+
+    /// Stores the improvement-weighted means of the successful parameters in the next slot of
+    /// the SHADE memory and trims the archive back to the size of the population.
+    fn update_memory(&mut self, successes: &[(f64, f64, f64)], rng: &mut StdRng) {
+        while self.archive.len() > self.population.len() {
+            let index = rng.gen_range(0, self.archive.len());
+            self.archive.swap_remove(index);
+        }
+        let total_improvement: f64 = successes.iter().map(|&(_, _, improvement)| improvement).sum();
+        if successes.is_empty() || total_improvement <= 0f64 || !total_improvement.is_finite() {
+            return;
+        }
+        let mut crossover_rate = 0f64;
+        let mut scale_squares = 0f64;
+        let mut scale_sum = 0f64;
+        for &(scale_factor, rate, improvement) in successes {
+            let weight = improvement / total_improvement;
+            crossover_rate += weight * rate;
+            scale_squares += weight * scale_factor * scale_factor;
+            scale_sum += weight * scale_factor;
+        }
+        self.memory[self.memory_index] = (scale_squares / scale_sum, crossover_rate);
+        self.memory_index = (self.memory_index + 1) % self.memory.len();
+    }
+
+    /// The indices of the population from fittest to least fit.
+    fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.population.len()).collect();
+        ranking.sort_by(|&a, &b| self.fitness[b].partial_cmp(&self.fitness[a]).unwrap_or(::std::cmp::Ordering::Equal));
+        ranking
+    }
+
+    fn evaluate(&mut self, vector: &[f64]) -> f64 {
+        self.evaluations += 1;
+        (self.fitness_function)(vector).unwrap_or(self.progress.get_invalid_fitness())
+    }
+
+    fn evaluate_initial_population(&mut self) {
+        if self.evaluated {
+            return;
+        }
+        self.evaluated = true;
+        let population = ::std::mem::take(&mut self.population);
+        self.fitness = population.iter().map(|vector| self.evaluate(vector)).collect();
+        self.population = population;
+        let stats = self.summarise();
+        self.update_best(&stats);
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        GenerationStats::from_real_vectors(self.generation, &self.population, &self.fitness, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        self.progress.update_best(self.population.iter().zip(self.fitness.iter().cloned()), stats);
+    }
+}
+
+impl<F> Optimizer<Vec<f64>> for DifferentialEvolution<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Evaluates the population if that hasn't happened yet and summarises it.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.evaluate_initial_population();
+        Ok(self.summarise())
+    }
+
+    /// Builds a trial vector for every member of the population, each of which replaces its
+    /// target if it is at least as fit.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.evaluate_initial_population();
+        let best = fittest_index(&self.fitness);
+        let ranking = self.ranking();
+        let mut next_population = self.population.clone();
+        let mut next_fitness = self.fitness.clone();
+        let mut successes = vec![];
+        for target in 0..self.population.len() {
+            let (scale_factor, crossover_rate) = self.draw_parameters(target, rng);
+            let donor = self.donor(target, best, &ranking, scale_factor, rng);
+            let trial = self.crossover(target, &donor, crossover_rate, rng);
+            let fitness = self.evaluate(&trial);
+            if fitness >= self.fitness[target] {
+                if fitness > self.fitness[target] {
+                    successes.push((scale_factor, crossover_rate, fitness - self.fitness[target]));
+                    if let ParameterControl::Shade { .. } = self.control {
+                        self.archive.push(self.population[target].clone());
+                    }
+                }
+                if self.control == ParameterControl::JDe {
+                    self.member_parameters[target] = (scale_factor, crossover_rate);
+                }
+                next_population[target] = trial;
+                next_fitness[target] = fitness;
+            }
+        }
+        self.population = next_population;
+        self.fitness = next_fitness;
+        if let ParameterControl::Shade { .. } = self.control {
+            self.update_memory(&successes, rng);
+        }
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<Vec<f64>> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<Vec<f64>> {
+        &mut self.progress
+    }
+}
+
+/// Adds each scaled difference to the base vector.
+fn combine(base: &[f64], differences: &[(f64, &Vec<f64>, &Vec<f64>)]) -> Vec<f64> {
+    (0..base.len()).map(|dimension| {
+        base[dimension] + differences.iter()
+            .map(|&(scale_factor, plus, minus)| scale_factor * (plus[dimension] - minus[dimension]))
+            .sum::<f64>()
+    }).collect()
+}
+
+/// Draws the given number of distinct indices below size, none of them the excluded one.
+fn distinct_others(excluded: usize, count: usize, size: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut others: Vec<usize> = (0..size).filter(|&index| index != excluded).collect();
+    rng.shuffle(&mut others);
+    others.truncate(count);
+    others
+}
+
+fn fittest_index(fitness: &[f64]) -> usize {
+    let mut fittest = 0;
+    for (index, &value) in fitness.iter().enumerate() {
+        if value > fitness[fittest] {
+            fittest = index;
+        }
+    }
+    fittest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::termination::MaxGenerations;
+
+    fn sphere(vector: &[f64]) -> GeneticResult<f64> {
+        Ok(-vector.iter().map(|value| value * value).sum::<f64>())
+    }
+
+    type Objective = fn(&[f64]) -> GeneticResult<f64>;
+
+    fn optimizer(rng: &mut StdRng) -> DifferentialEvolution<Objective> {
+        DifferentialEvolution::new(20, vec![(-5f64, 5f64); 4], sphere as Objective, rng).unwrap()
+    }
+
+    #[test]
+    fn every_strategy_closes_in_on_the_optimum_of_the_sphere() {
+        let strategies = [DeStrategy::RandOne, DeStrategy::BestOne, DeStrategy::CurrentToBestOne, DeStrategy::RandTwo];
+        for (seed, &strategy) in strategies.iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[40 + seed][..]);
+            let mut optimizer = optimizer(&mut rng);
+            optimizer.set_strategy(strategy).unwrap();
+            let stats = optimizer.run(&mut MaxGenerations::new(150), &mut rng).unwrap();
+            assert!(stats.get_best_fitness() > -1e-2, "{:?} reached {}", strategy, stats.get_best_fitness());
+            assert_eq!(20 * 151, stats.get_evaluations());
+            let best = optimizer.get_best().unwrap().get_genome();
+            assert!(best.iter().all(|value| (-5f64..=5f64).contains(value)));
+        }
+    }
+
+    #[test]
+    fn self_adaptive_variants_solve_the_sphere_and_adapt_their_parameters() {
+        for (seed, &control) in [ParameterControl::JDe, ParameterControl::Shade { memory_size: 5 }].iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[50 + seed][..]);
+            let mut optimizer = optimizer(&mut rng);
+            optimizer.set_strategy(DeStrategy::CurrentToBestOne).unwrap();
+            optimizer.set_parameter_control(control);
+            let initial = optimizer.get_adapted_parameters().to_vec();
+            let stats = optimizer.run(&mut MaxGenerations::new(150), &mut rng).unwrap();
+            assert!(stats.get_best_fitness() > -1e-3, "{:?} reached {}", control, stats.get_best_fitness());
+            assert_ne!(initial, optimizer.get_adapted_parameters().to_vec());
+        }
+    }
+
+    #[test]
+    fn vectors_the_fitness_function_rejects_lose_to_every_valid_vector() {
+        let mut rng = StdRng::from_seed(&[55usize][..]);
+        // Undefined for a negative first value, and zero there would beat any valid vector.
+        let shifted = |vector: &[f64]| if vector[0] < 0f64 {
+            Err(GeneticError::ComputationError)
+        } else {
+            Ok(-vector.iter().map(|value| (value - 1f64) * (value - 1f64)).sum::<f64>())
+        };
+        let mut optimizer = DifferentialEvolution::new(20, vec![(-5f64, 5f64); 3], shifted, &mut rng).unwrap();
+        let initial = optimizer.get_statistics(&mut rng).unwrap();
+        assert_eq!(f64::NEG_INFINITY, initial.get_worst_fitness());
+        let stats = optimizer.run(&mut MaxGenerations::new(150), &mut rng).unwrap();
+        assert!(stats.get_best_fitness() > -1e-2);
+        assert!(optimizer.get_population().iter().all(|vector| vector[0] >= 0f64));
+        assert!(optimizer.get_best().unwrap().get_genome()[0] >= 0f64);
+    }
+
+    #[test]
+    fn invalid_configurations_are_rejected() {
+        let mut rng = StdRng::from_seed(&[60usize][..]);
+        assert!(DifferentialEvolution::new(3, vec![(0f64, 1f64)], sphere, &mut rng).is_err());
+        assert!(DifferentialEvolution::new(10, vec![], sphere, &mut rng).is_err());
+        assert!(DifferentialEvolution::new(10, vec![(1f64, 0f64)], sphere, &mut rng).is_err());
+        let mut small = DifferentialEvolution::new(5, vec![(0f64, 1f64)], sphere, &mut rng).unwrap();
+        assert!(small.set_strategy(DeStrategy::RandTwo).is_err());
+        assert!(small.set_scale_factor(0f64).is_err());
+        assert!(small.set_crossover_rate(1.5).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Measures of how varied a population is, used to detect premature convergence.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use ::error::GeneticError;
+use ::genetic_string::GeneticString;
+use ::interfaces::distance_metric::DistanceMetric;
+use ::interfaces::genetic_collection_component::GeneticCollectionComponent;
+use ::GeneticResult;
+
+/// The number of positions at which two equally long sequences differ.
+///
+/// # Returns
+/// <ul>
+/// <li>The distance if successful.</li>
+/// <li>LengthMismatchError if the sequences have different lengths.</li>
+/// </ul>
+pub fn hamming_distance<T: PartialEq>(a: &[T], b: &[T]) -> GeneticResult<usize> {
+    if a.len() != b.len() {
+        return Err(GeneticError::LengthMismatchError);
+    }
+    Ok(a.iter().zip(b).filter(|&(x, y)| x != y).count())
+}
+
+/// The minimum number of insertions, deletions and substitutions needed to turn one
+/// sequence into the other.
+pub fn levenshtein_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous_row: Vec<usize> = (0..b.len() + 1).collect();
+    let mut current_row = vec![0; b.len() + 1];
+    for (i, a_element) in a.iter().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_element) in b.iter().enumerate() {
+            let substitution_cost = if a_element == b_element { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        ::std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[b.len()]
+}
+
+/// Hamming distance between the components of two equally long genetic strings.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HammingDistance;
+
+/// Edit distance between the components of two genetic strings of any length.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LevenshteinDistance;
+
+/// The absolute difference between the values of the expressions two genetic strings
+/// represent.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhenotypicDistance;
+
+impl<T> DistanceMetric<GeneticString<T>> for HammingDistance where T: GeneticCollectionComponent + PartialEq {
+    fn distance(&self, a: &GeneticString<T>, b: &GeneticString<T>) -> GeneticResult<f64> {
+        Ok(hamming_distance(a.get_vector_immutable(), b.get_vector_immutable())? as f64)
+    }
+}
+
+impl<T> DistanceMetric<GeneticString<T>> for LevenshteinDistance where T: GeneticCollectionComponent + PartialEq {
+    fn distance(&self, a: &GeneticString<T>, b: &GeneticString<T>) -> GeneticResult<f64> {
+        Ok(levenshtein_distance(a.get_vector_immutable(), b.get_vector_immutable()) as f64)
+    }
+}
+
+impl<T> DistanceMetric<GeneticString<T>> for PhenotypicDistance where T: GeneticCollectionComponent {
+    fn distance(&self, a: &GeneticString<T>, b: &GeneticString<T>) -> GeneticResult<f64> {
+        Ok((a.evaluate_expression()? as f64 - b.evaluate_expression()? as f64).abs())
+    }
+}
+
+/// The mean distance over every pair of genomes in the population.
+///
+/// # Returns
+/// The mean distance, 0 for populations with fewer than two genomes, or the first error
+/// raised by the metric.
+pub fn mean_pairwise_distance<G, D>(population: &[&G], metric: &D) -> GeneticResult<f64>
+    where D: DistanceMetric<G> + ?Sized {
+    let mut total = 0f64;
+    let mut pairs = 0usize;
+    for (i, a) in population.iter().enumerate() {
+        for b in &population[i + 1..] {
+            total += metric.distance(a, b)?;
+            pairs += 1;
+        }
+    }
+    Ok(if pairs == 0 { 0f64 } else { total / pairs as f64 })
+}
+
+/// The standard deviation of the values of the expressions in the population. Strings whose
+/// expression can't be evaluated are left out.
+pub fn phenotypic_diversity<T: GeneticCollectionComponent>(population: &[&GeneticString<T>]) -> f64 {
+    let values: Vec<f64> = population.iter()
+        .filter_map(|genetic_string| genetic_string.evaluate_expression().ok())
+        .map(|value| value as f64)
+        .collect();
+    if values.is_empty() {
+        return 0f64;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+/// The Shannon entropy, in bits, of the components found at each position across the
+/// population. Genomes shorter than a position don't count towards it.
+pub fn locus_entropy<T: Hash + Eq>(genomes: &[&[T]]) -> Vec<f64> {
+    let longest = genomes.iter().map(|genome| genome.len()).max().unwrap_or(0);
+    (0..longest).map(|locus| {
+        let mut counts: HashMap<&T, usize> = HashMap::new();
+        for genome in genomes.iter().filter(|genome| genome.len() > locus) {
+            *counts.entry(&genome[locus]).or_insert(0) += 1;
+        }
+        let total = counts.values().sum::<usize>() as f64;
+        counts.values()
+            .map(|&count| count as f64 / total)
+            .map(|probability| -probability * probability.log2())
+            .sum()
+    }).collect()
+}
+
+/// The fraction of the population made up of distinct genomes.
+pub fn unique_fraction<G: Hash + Eq>(population: &[&G]) -> f64 {
+    if population.is_empty() {
+        return 0f64;
+    }
+    population.iter().collect::<HashSet<_>>().len() as f64 / population.len() as f64
+}
+
+/// The diversity measures of a population of genetic strings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiversityStats {
+    mean_hamming_distance: Option<f64>,
+    mean_edit_distance: f64,
+    phenotypic_diversity: f64,
+    locus_entropy: Vec<f64>,
+    unique_fraction: f64
+}
+
+impl DiversityStats {
+    /// Measures the diversity of a population of genetic strings.
+    ///
+    /// # Parameters
+    /// population - the genetic strings to be measured
+    ///
+    /// # Returns
+    /// The diversity measures. The mean Hamming distance is only given when every genetic
+    /// string has the same length.
+    pub fn from_genetic_strings<T>(population: &[&GeneticString<T>]) -> DiversityStats
+        where T: GeneticCollectionComponent + Hash + Eq {
+        let genomes: Vec<&[T]> = population.iter()
+            .map(|genetic_string| genetic_string.get_vector_immutable().as_slice())
+            .collect();
+        DiversityStats {
+            mean_hamming_distance: mean_pairwise_distance(population, &HammingDistance).ok(),
+            mean_edit_distance: mean_pairwise_distance(population, &LevenshteinDistance).unwrap_or(0f64),
+            phenotypic_diversity: phenotypic_diversity(population),
+            locus_entropy: locus_entropy(&genomes),
+            unique_fraction: unique_fraction(population)
+        }
+    }
+
+    pub fn get_mean_hamming_distance(&self) -> Option<f64> {
+        self.mean_hamming_distance
+    }
+
+    pub fn get_mean_edit_distance(&self) -> f64 {
+        self.mean_edit_distance
+    }
+
+    pub fn get_phenotypic_diversity(&self) -> f64 {
+        self.phenotypic_diversity
+    }
+
+    pub fn get_locus_entropy(&self) -> &[f64] {
+        &self.locus_entropy
+    }
+
+    /// The mean entropy over every locus, or 0 for a population of empty genomes.
+    pub fn get_mean_locus_entropy(&self) -> f64 {
+        if self.locus_entropy.is_empty() {
+            0f64
+        } else {
+            self.locus_entropy.iter().sum::<f64>() / self.locus_entropy.len() as f64
+        }
+    }
+
+    pub fn get_unique_fraction(&self) -> f64 {
+        self.unique_fraction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::genetic_byte::{GeneticByte, GeneticByteTestUtilities};
+    use ::genetic_string::GeneticStringTestUtilities;
+
+    fn build_genetic_string(values: &[u8]) -> GeneticString<GeneticByte> {
+        let bytes = values.iter().enumerate()
+            .map(|(index, &value)| GeneticByteTestUtilities::new(value, index).unwrap())
+            .collect();
+        GeneticStringTestUtilities::new(bytes)
+    }
+
+    #[test]
+    fn hamming_distance_counts_differences_and_rejects_unequal_lengths() {
+        assert_eq!(2, hamming_distance(&[1, 2, 3, 4], &[1, 0, 3, 0]).unwrap());
+        assert!(hamming_distance(&[1, 2], &[1]).is_err());
+    }
+
+    #[test]
+    fn levenshtein_distance_of_kitten_and_sitting_is_3() {
+        let kitten: Vec<char> = "kitten".chars().collect();
+        let sitting: Vec<char> = "sitting".chars().collect();
+        assert_eq!(3, levenshtein_distance(&kitten, &sitting));
+        assert_eq!(6, levenshtein_distance(&kitten, &[]));
+    }
+
+    #[test]
+    fn identical_population_has_no_diversity() {
+        let genetic_string = build_genetic_string(&[5, b'+', 2]);
+        let population = vec![&genetic_string, &genetic_string, &genetic_string];
+        let diversity = DiversityStats::from_genetic_strings(&population);
+        assert_eq!(Some(0f64), diversity.get_mean_hamming_distance());
+        assert_eq!(0f64, diversity.get_phenotypic_diversity());
+        assert_eq!(vec![0f64, 0f64, 0f64], diversity.get_locus_entropy());
+        assert_eq!(1f64 / 3f64, diversity.get_unique_fraction());
+    }
+
+    #[test]
+    fn mixed_length_population_reports_edit_distance_but_no_hamming_distance() {
+        let short = build_genetic_string(&[5]);
+        let long = build_genetic_string(&[5, b'*', 2]);
+        let diversity = DiversityStats::from_genetic_strings(&[&short, &long]);
+        assert_eq!(None, diversity.get_mean_hamming_distance());
+        assert_eq!(2f64, diversity.get_mean_edit_distance());
+        assert_eq!(2.5f64, diversity.get_phenotypic_diversity());
+        assert_eq!(1f64, diversity.get_unique_fraction());
+    }
+}
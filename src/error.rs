@@ -10,37 +10,43 @@ pub enum GeneticError {
     CollectionEmptyError,
     InvalidGeneticByteType,
     MutationError,
-    MatchError
+    MatchError,
+    NoTargetFitnessError,
+    LengthMismatchError,
+    InvalidParameterError,
+    ConversionError,
+    GrammarError,
+    MappingError
 }
 
-impl Error for GeneticError{
-    fn description(&self) -> &str {
-        match self {
-            &GeneticError::MutationError => "An error occurred in the mutation process",
-            &GeneticError::ComputationError => "the computation was invalid.\n\
+impl Error for GeneticError {}
+
+impl Display for GeneticError{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            GeneticError::MutationError => "An error occurred in the mutation process",
+            GeneticError::ComputationError => "the computation was invalid.\n\
             The most likely cause was a division by zero.",
-            &GeneticError::RNGCreationError => "The mutation failed because RNG couldn't be created.",
-            &GeneticError::GenByteCreationError=> "The mutation failed because a genetic byte \
+            GeneticError::RNGCreationError => "The mutation failed because RNG couldn't be created.",
+            GeneticError::GenByteCreationError=> "The mutation failed because a genetic byte \
             couldn't be created.",
-            &GeneticError::CollectionEmptyError => "The mutation failed because the method tried to \n\
+            GeneticError::CollectionEmptyError => "The mutation failed because the method tried to \n\
             remove an element from an empty Collection.",
-            &GeneticError::InvalidGeneticByteType => "The type of the Genetic Byte was invalid for \n\
+            GeneticError::InvalidGeneticByteType => "The type of the Genetic Byte was invalid for \n\
             it's position in the genetic string.",
-            &GeneticError::MatchError => "You reached the error case on a match statement."
-       }
+            GeneticError::MatchError => "You reached the error case on a match statement.",
+            GeneticError::NoTargetFitnessError => "The genome has no fitness against a target; \
+            score it with a FitnessFunction instead.",
+            GeneticError::LengthMismatchError => "The genetic collections must have the same \
+            length for this operation.",
+            GeneticError::InvalidParameterError => "A parameter was outside of its valid range.",
+            GeneticError::ConversionError => "The genome can't be represented in the requested \
+            form.",
+            GeneticError::GrammarError => "The grammar was malformed, referred to an undefined \
+            rule or contained a rule that can never finish.",
+            GeneticError::MappingError => "The genome ran out of codons before its program was \
+            complete."
+        };
+        description.fmt(f)
     }
 }
-
-impl Display for GeneticError{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &GeneticError::MutationError => self.description().fmt(f),
-            &GeneticError::ComputationError => self.description().fmt(f),
-            &GeneticError::RNGCreationError => self.description().fmt(f),
-            &GeneticError::GenByteCreationError => self.description().fmt(f),
-            &GeneticError::CollectionEmptyError => self.description().fmt(f),
-            &GeneticError::InvalidGeneticByteType => self.description().fmt(f),
-            &GeneticError::MatchError=> self.description().fmt(f),
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,476 @@
+//! Estimation-of-distribution algorithms, which replace crossover and mutation with a
+//! probabilistic model that is sampled for new candidates and updated from the fittest ones.
+//!
+//! Candidates are strings of symbols below the size of the alphabet: 0 and 1 for bit strings,
+//! converted with `ProbabilityModel::to_bit_string`, or up to 256 symbols for alphabets such
+//! as the values of `GeneticByte`, converted with `ProbabilityModel::to_genetic_string`. The
+//! model keeps one probability per symbol at every position and can be written as text and
+//! parsed back.
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+use rand::{Rng, StdRng};
+
+use ::bit_string::BitString;
+use ::error::GeneticError;
+use ::genetic_byte::GeneticByte;
+use ::genetic_string::GeneticString;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+const DEFAULT_LEARNING_RATE: f64 = 0.1;
+const DEFAULT_MUTATION_PROBABILITY: f64 = 0.02;
+const DEFAULT_MUTATION_SHIFT: f64 = 0.05;
+/// How far the probabilities of a row may stray from summing to 1.
+const SUM_TOLERANCE: f64 = 1e-6;
+
+/// A probability for every symbol of the alphabet at every position of a candidate, each
+/// position independent of the others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbabilityModel {
+    probabilities: Vec<Vec<f64>>
+}
+
+impl ProbabilityModel {
+    /// Creates a model in which every symbol is equally likely at every position.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The model if successful.</li>
+    /// <li>CollectionEmptyError if the length is 0.</li>
+    /// <li>InvalidParameterError if the alphabet has fewer than 2 or more than 256 symbols.</li>
+    /// </ul>
+    pub fn uniform(length: usize, alphabet_size: usize) -> GeneticResult<ProbabilityModel> {
+        ProbabilityModel::from_probabilities(vec![vec![1f64 / alphabet_size as f64; alphabet_size]; length])
+    }
+
+    /// Creates a model from the probabilities of each symbol at each position.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The model if successful.</li>
+    /// <li>CollectionEmptyError if there are no positions.</li>
+    /// <li>LengthMismatchError if the positions have alphabets of different sizes.</li>
+    /// <li>InvalidParameterError if the alphabet has fewer than 2 or more than 256 symbols, or
+    /// the probabilities of a position are negative or don't sum to 1.</li>
+    /// </ul>
+    pub fn from_probabilities(probabilities: Vec<Vec<f64>>) -> GeneticResult<ProbabilityModel> {
+        let alphabet_size = match probabilities.first() {
+            Some(row) => row.len(),
+            None => return Err(GeneticError::CollectionEmptyError)
+        };
+        if !(2..=256).contains(&alphabet_size) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        for row in &probabilities {
+            if row.len() != alphabet_size {
+                return Err(GeneticError::LengthMismatchError);
+            }
+            if row.iter().any(|&probability| !(0f64..=1f64).contains(&probability))
+                || (row.iter().sum::<f64>() - 1f64).abs() > SUM_TOLERANCE {
+                return Err(GeneticError::InvalidParameterError);
+            }
+        }
+        Ok(ProbabilityModel { probabilities })
+    }
+
+    /// Parses a model written by `Display`: one line per position holding the probability of
+    /// each symbol, separated by whitespace. Blank lines are ignored.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The model if successful.</li>
+    /// <li>ConversionError if a probability isn't a number.</li>
+    /// <li>Any error of `from_probabilities` if the numbers don't form a valid model.</li>
+    /// </ul>
+    pub fn parse(text: &str) -> GeneticResult<ProbabilityModel> {
+        let mut probabilities = vec![];
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let row = line.split_whitespace()
+                .map(|number| number.parse::<f64>().map_err(|_| GeneticError::ConversionError))
+                .collect::<GeneticResult<Vec<f64>>>()?;
+            probabilities.push(row);
+        }
+        ProbabilityModel::from_probabilities(probabilities)
+    }
+
+    pub fn get_probabilities(&self) -> &[Vec<f64>] {
+        &self.probabilities
+    }
+
+    /// The probability of the symbol at the position.
+    pub fn get_probability(&self, position: usize, symbol: u8) -> f64 {
+        self.probabilities[position][symbol as usize]
+    }
+
+    pub fn get_length(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn get_alphabet_size(&self) -> usize {
+        self.probabilities[0].len()
+    }
+
+    /// Draws a candidate with each symbol chosen by its probability at its position.
+    pub fn sample(&self, rng: &mut StdRng) -> Vec<u8> {
+        self.probabilities.iter().map(|row| {
+            let mut remaining: f64 = rng.gen();
+            for (symbol, &probability) in row.iter().enumerate() {
+                if remaining < probability {
+                    return symbol as u8;
+                }
+                remaining -= probability;
+            }
+            (row.len() - 1) as u8
+        }).collect()
+    }
+
+    /// The candidate made of the most likely symbol at every position.
+    pub fn most_likely(&self) -> Vec<u8> {
+        self.probabilities.iter().map(|row| {
+            (0..row.len()).fold(0, |best, symbol| if row[symbol] > row[best] { symbol } else { best }) as u8
+        }).collect()
+    }
+
+    /// Converts a candidate of a two symbol model into a bit string.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The bit string if successful.</li>
+    /// <li>ConversionError if a symbol is neither 0 nor 1.</li>
+    /// </ul>
+    pub fn to_bit_string(candidate: &[u8]) -> GeneticResult<BitString> {
+        if candidate.iter().any(|&symbol| symbol > 1) {
+            return Err(GeneticError::ConversionError);
+        }
+        let bits: Vec<bool> = candidate.iter().map(|&symbol| symbol == 1).collect();
+        Ok(BitString::from_bools(&bits))
+    }
+
+    /// Converts a candidate into a genetic string holding its symbols at the value positions,
+    /// with `+` at the operator positions between them.
+    pub fn to_genetic_string(candidate: &[u8]) -> GeneticString<GeneticByte> {
+        let mut bytes = vec!();
+        for (index, &symbol) in candidate.iter().enumerate() {
+            if index > 0 {
+                bytes.push(GeneticByte::GeneticOperator(b'+'));
+            }
+            bytes.push(GeneticByte::GeneticValue(symbol));
+        }
+        GeneticString::from_vector(bytes)
+    }
+
+    /// Moves every position towards the symbol the candidate holds there by the given share.
+    fn shift_towards(&mut self, candidate: &[u8], rate: f64) {
+        for (row, &symbol) in self.probabilities.iter_mut().zip(candidate) {
+            shift_row(row, symbol as usize, rate);
+        }
+    }
+
+    /// Raises every probability to at least the minimum, taking the difference from the
+    /// other symbols of the position.
+    fn bound(&mut self, minimum: f64) {
+        for row in &mut self.probabilities {
+            let deficit: f64 = row.iter().map(|&probability| (minimum - probability).max(0f64)).sum();
+            if deficit <= 0f64 {
+                continue;
+            }
+            let surplus: f64 = row.iter().map(|&probability| (probability - minimum).max(0f64)).sum();
+            for probability in row.iter_mut() {
+                *probability = if *probability <= minimum {
+                    minimum
+                } else {
+                    *probability - (*probability - minimum) / surplus * deficit
+                };
+            }
+        }
+    }
+}
+
+impl Display for ProbabilityModel {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for row in &self.probabilities {
+            let numbers: Vec<String> = row.iter().map(|probability| probability.to_string()).collect();
+            writeln!(f, "{}", numbers.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// How the model is updated each generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdaAlgorithm {
+    /// Population-based incremental learning: the model moves towards the fittest sample by
+    /// the learning rate, and each position is then moved towards a random symbol by the
+    /// mutation shift with the mutation probability.
+    Pbil { learning_rate: f64, mutation_probability: f64, mutation_shift: f64 },
+    /// The univariate marginal distribution algorithm: the model becomes the symbol
+    /// frequencies of the fittest `selected` samples, with no probability below one over the
+    /// length times the alphabet size less one, so that no symbol is lost for good.
+    Umda { selected: usize },
+    /// The compact genetic algorithm: two samples compete and, wherever they differ, the
+    /// probability of the winner's symbol rises and that of the loser's falls by one over the
+    /// size of the virtual population. The population size is ignored.
+    CompactGa { virtual_population: usize }
+}
+
+impl EdaAlgorithm {
+    /// PBIL with a learning rate of 0.1, a mutation probability of 0.02 and a mutation shift
+    /// of 0.05.
+    pub fn pbil() -> EdaAlgorithm {
+        EdaAlgorithm::Pbil {
+            learning_rate: DEFAULT_LEARNING_RATE,
+            mutation_probability: DEFAULT_MUTATION_PROBABILITY,
+            mutation_shift: DEFAULT_MUTATION_SHIFT
+        }
+    }
+}
+
+/// An estimation-of-distribution algorithm maximising a fitness function over strings of
+/// symbols. Candidates whose evaluation fails get a fitness of negative infinity, so the model
+/// never learns from them while any candidate can be evaluated.
+pub struct EstimationOfDistribution<F> {
+    model: ProbabilityModel,
+    algorithm: EdaAlgorithm,
+    population_size: usize,
+    fitness_function: F,
+    population: Vec<Vec<u8>>,
+    fitness: Vec<f64>,
+    generation: usize,
+    evaluations: usize,
+    progress: Progress<Vec<u8>>
+}
+
+impl<F> EstimationOfDistribution<F> where F: Fn(&[u8]) -> GeneticResult<f64> {
+    /// # Parameters
+    /// <ul>
+    /// <li>model - the model the first generation is sampled from, usually uniform</li>
+    /// <li>algorithm - how the model is updated</li>
+    /// <li>population_size - the number of samples drawn each generation</li>
+    /// <li>fitness_function - scores a candidate, higher is fitter</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The algorithm if successful.</li>
+    /// <li>InvalidParameterError if a rate isn't between 0 and 1, the population is empty,
+    /// UMDA selects no samples or more than the population, or the virtual population of the
+    /// compact GA is empty.</li>
+    /// </ul>
+    pub fn new(model: ProbabilityModel, algorithm: EdaAlgorithm, population_size: usize, fitness_function: F)
+               -> GeneticResult<EstimationOfDistribution<F>> {
+        let valid = match algorithm {
+            EdaAlgorithm::Pbil { learning_rate, mutation_probability, mutation_shift } =>
+                [learning_rate, mutation_probability, mutation_shift].iter().all(|rate| (0f64..=1f64).contains(rate)),
+            EdaAlgorithm::Umda { selected } => selected > 0 && selected <= population_size,
+            EdaAlgorithm::CompactGa { virtual_population } => virtual_population > 0
+        };
+        if !valid || population_size == 0 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(EstimationOfDistribution {
+            model,
+            algorithm,
+            population_size,
+            fitness_function,
+            population: vec![],
+            fitness: vec![],
+            generation: 0,
+            evaluations: 0,
+            progress: Progress::new(f64::NEG_INFINITY)
+        })
+    }
+
+    pub fn get_model(&self) -> &ProbabilityModel {
+        &self.model
+    }
+
+    /// Replaces the model, e.g. with one parsed from an earlier run.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>LengthMismatchError if the model has a different length or alphabet.</li>
+    /// </ul>
+    pub fn set_model(&mut self, model: ProbabilityModel) -> GeneticResult<()> {
+        if model.get_length() != self.model.get_length() || model.get_alphabet_size() != self.model.get_alphabet_size() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        self.model = model;
+        Ok(())
+    }
+
+    /// The samples of the last generation.
+    pub fn get_population(&self) -> &[Vec<u8>] {
+        &self.population
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn evaluate(&mut self, candidate: &[u8]) -> f64 {
+        self.evaluations += 1;
+        (self.fitness_function)(candidate).unwrap_or(self.progress.get_invalid_fitness())
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        let unique = self.population.iter().collect::<HashSet<_>>().len();
+        GenerationStats::new(self.generation, &self.fitness, self.model.get_length() as f64, unique, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        self.progress.update_best(self.population.iter().zip(self.fitness.iter().cloned()), stats);
+    }
+}
+
+impl<F> Optimizer<Vec<u8>> for EstimationOfDistribution<F> where F: Fn(&[u8]) -> GeneticResult<f64> {
+    /// Summarises the last generation. Before the first generation the most likely
+    /// candidate of the model is evaluated and summarised as a population of one.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if self.population.is_empty() {
+            let candidate = self.model.most_likely();
+            self.fitness = vec![self.evaluate(&candidate)];
+            self.population = vec![candidate];
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Samples and evaluates a generation and updates the model from it.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        let sample_count = match self.algorithm {
+            EdaAlgorithm::CompactGa { .. } => 2,
+            _ => self.population_size
+        };
+        let population: Vec<Vec<u8>> = (0..sample_count).map(|_| self.model.sample(rng)).collect();
+        let fitness: Vec<f64> = population.iter().map(|candidate| self.evaluate(candidate)).collect();
+        let mut ranking: Vec<usize> = (0..population.len()).collect();
+        ranking.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap_or(::std::cmp::Ordering::Equal));
+        match self.algorithm {
+            EdaAlgorithm::Pbil { learning_rate, mutation_probability, mutation_shift } => {
+                self.model.shift_towards(&population[ranking[0]], learning_rate);
+                let alphabet_size = self.model.get_alphabet_size();
+                for position in 0..self.model.get_length() {
+                    if rng.gen::<f64>() < mutation_probability {
+                        let symbol = rng.gen_range(0, alphabet_size);
+                        shift_row(&mut self.model.probabilities[position], symbol, mutation_shift);
+                    }
+                }
+            }
+            EdaAlgorithm::Umda { selected } => {
+                let alphabet_size = self.model.get_alphabet_size();
+                for position in 0..self.model.get_length() {
+                    let mut counts = vec![0f64; alphabet_size];
+                    for &index in ranking.iter().take(selected) {
+                        counts[population[index][position] as usize] += 1f64;
+                    }
+                    self.model.probabilities[position] = counts.iter().map(|count| count / selected as f64).collect();
+                }
+                let minimum = 1f64 / (self.model.get_length() * (alphabet_size - 1)) as f64;
+                self.model.bound(minimum.min(1f64 / alphabet_size as f64));
+            }
+            EdaAlgorithm::CompactGa { virtual_population } => {
+                let step = 1f64 / virtual_population as f64;
+                let (winner, loser) = (&population[ranking[0]], &population[ranking[1]]);
+                for (position, row) in self.model.probabilities.iter_mut().enumerate() {
+                    let (won, lost) = (winner[position] as usize, loser[position] as usize);
+                    if won != lost {
+                        let shift = step.min(row[lost]);
+                        row[won] += shift;
+                        row[lost] -= shift;
+                    }
+                }
+            }
+        }
+        self.population = population;
+        self.fitness = fitness;
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<Vec<u8>> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<Vec<u8>> {
+        &mut self.progress
+    }
+}
+
+/// Moves the probabilities of a position towards the symbol by the given share.
+fn shift_row(row: &mut [f64], symbol: usize, rate: f64) {
+    for (index, probability) in row.iter_mut().enumerate() {
+        let target = if index == symbol { 1f64 } else { 0f64 };
+        *probability = (1f64 - rate) * *probability + rate * target;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::interfaces::genetic_collection::GeneticCollection;
+    use ::termination::{any, FitnessThreshold, MaxGenerations};
+
+    fn one_max(candidate: &[u8]) -> GeneticResult<f64> {
+        Ok(ProbabilityModel::to_bit_string(candidate)?.count_ones() as f64)
+    }
+
+    #[test]
+    fn model_survives_a_round_trip_through_text() {
+        let model = ProbabilityModel::from_probabilities(vec![vec![0.25, 0.75], vec![1f64, 0f64], vec![0.1, 0.9]]).unwrap();
+        assert_eq!(model, ProbabilityModel::parse(&model.to_string()).unwrap());
+        assert_eq!(vec![1, 0, 1], model.most_likely());
+        let mut rng = StdRng::from_seed(&[80usize][..]);
+        assert!((0..50).all(|_| model.sample(&mut rng)[1] == 0));
+        assert!(ProbabilityModel::parse("0.5 0.5\n0.5 x").is_err());
+        assert!(ProbabilityModel::parse("0.5 0.6").is_err());
+        assert!(ProbabilityModel::parse("0.5 0.5\n1").is_err());
+        assert!(ProbabilityModel::uniform(3, 257).is_err());
+    }
+
+    #[test]
+    fn every_algorithm_solves_one_max() {
+        let algorithms = [(EdaAlgorithm::pbil(), 50), (EdaAlgorithm::Umda { selected: 25 }, 50),
+                          (EdaAlgorithm::CompactGa { virtual_population: 60 }, 2)];
+        for (seed, &(algorithm, population_size)) in algorithms.iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[81 + seed][..]);
+            let model = ProbabilityModel::uniform(30, 2).unwrap();
+            let mut eda = EstimationOfDistribution::new(model, algorithm, population_size, one_max).unwrap();
+            let mut termination = any(vec![Box::new(MaxGenerations::new(3000)), Box::new(FitnessThreshold::new(30f64))]);
+            let stats = eda.run(&mut termination, &mut rng).unwrap();
+            assert_eq!(30f64, stats.get_best_fitness(), "{:?}", algorithm);
+            assert!(eda.get_model().get_probabilities().iter().filter(|row| row[1] > 0.5).count() > 20);
+        }
+    }
+
+    #[test]
+    fn larger_alphabets_learn_the_target_symbols() {
+        let target: Vec<u8> = (0..12).map(|index| (index * 7 % 16) as u8).collect();
+        let matches = |candidate: &[u8]| Ok(candidate.iter().zip(&target).filter(|&(a, b)| a == b).count() as f64);
+        let mut rng = StdRng::from_seed(&[85usize][..]);
+        let model = ProbabilityModel::uniform(12, 16).unwrap();
+        let mut eda = EstimationOfDistribution::new(model, EdaAlgorithm::Umda { selected: 30 }, 100, matches).unwrap();
+        let stats = eda.run(&mut MaxGenerations::new(60), &mut rng).unwrap();
+        assert_eq!(12f64, stats.get_best_fitness());
+        assert_eq!(target, eda.get_model().most_likely());
+        let genetic_string = ProbabilityModel::to_genetic_string(&target);
+        assert_eq!(23, genetic_string.get_size());
+        assert_eq!(target.iter().map(|&symbol| i32::from(symbol)).sum::<i32>(), genetic_string.evaluate_expression().unwrap());
+        assert!(eda.set_model(ProbabilityModel::uniform(12, 2).unwrap()).is_err());
+        assert!(EstimationOfDistribution::new(ProbabilityModel::uniform(2, 2).unwrap(),
+                                              EdaAlgorithm::Umda { selected: 5 }, 4, one_max).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! A memoizing wrapper around a fitness function.
+//!
+//! Many children in a generation are copies of a parent or of each other, so the cache
+//! remembers the fitness of recently seen genomes, keyed by the genome itself, and only
+//! calls the wrapped fitness function for genomes it hasn't seen. The cache is bounded and
+//! evicts the least recently used entry once full. It can be shared between evaluation
+//! threads, e.g. behind an `Arc`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use ::interfaces::fitness_function::FitnessFunction;
+use ::GeneticResult;
+
+/// Hit and miss counts of a fitness cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheStatistics {
+    hits: usize,
+    misses: usize
+}
+
+impl CacheStatistics {
+    pub fn get_hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn get_misses(&self) -> usize {
+        self.misses
+    }
+
+    /// The fraction of lookups answered from the cache, or 0 if there were no lookups.
+    pub fn get_hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0f64
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Cached fitness values together with the order in which they were last used.
+struct LruEntries<G> {
+    /// genome -> (fitness, time of last use)
+    values: HashMap<G, (f64, u64)>,
+    /// time of last use -> genome, oldest first
+    recency: BTreeMap<u64, G>,
+    clock: u64
+}
+
+impl<G> LruEntries<G> where G: Hash + Eq + Clone {
+    fn get(&mut self, genome: &G) -> Option<f64> {
+        let now = self.tick();
+        let entry = self.values.get_mut(genome)?;
+        if let Some(key) = self.recency.remove(&entry.1) {
+            self.recency.insert(now, key);
+        }
+        entry.1 = now;
+        Some(entry.0)
+    }
+
+    fn insert(&mut self, genome: &G, fitness: f64, capacity: usize) {
+        let now = self.tick();
+        if let Some((_, last_used)) = self.values.insert(genome.clone(), (fitness, now)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(now, genome.clone());
+        while self.values.len() > capacity {
+            let oldest = match self.recency.keys().next() {
+                Some(&oldest) => oldest,
+                None => break
+            };
+            if let Some(evicted) = self.recency.remove(&oldest) {
+                self.values.remove(&evicted);
+            }
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+/// A fitness function that remembers the fitness of the most recently evaluated genomes.
+/// Genomes are compared for equality on every lookup, so two different genomes with the
+/// same hash never share a fitness.
+pub struct FitnessCache<G, F> {
+    fitness_function: F,
+    capacity: usize,
+    entries: Mutex<LruEntries<G>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize
+}
+
+impl<G, F> FitnessCache<G, F> {
+    /// Wraps a fitness function in a cache.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>fitness_function - the function whose results will be cached</li>
+    /// <li>capacity - the maximum number of genomes remembered at once</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// An empty fitness cache.
+    pub fn new(fitness_function: F, capacity: usize) -> FitnessCache<G, F> {
+        FitnessCache {
+            fitness_function,
+            capacity,
+            entries: Mutex::new(LruEntries {
+                values: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0
+            }),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of genomes currently remembered.
+    pub fn get_size(&self) -> usize {
+        self.lock_entries().values.len()
+    }
+
+    pub fn get_statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Forgets every cached fitness and resets the hit and miss counts.
+    pub fn clear(&self) {
+        let mut entries = self.lock_entries();
+        entries.values.clear();
+        entries.recency.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    pub fn get_fitness_function(&self) -> &F {
+        &self.fitness_function
+    }
+
+    fn lock_entries(&self) -> ::std::sync::MutexGuard<'_, LruEntries<G>> {
+        // A panic while holding the lock can't leave the entries half updated in a way
+        // that matters for a cache, so a poisoned lock is still usable.
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<G, F> FitnessFunction<G> for FitnessCache<G, F> where G: Hash + Eq + Clone, F: FitnessFunction<G> {
+    /// Returns the cached fitness of the genome, evaluating and caching it on a miss.
+    /// Evaluation errors are returned as is and are not cached.
+    fn evaluate(&self, genome: &G) -> GeneticResult<f64> {
+        if let Some(fitness) = self.lock_entries().get(genome) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(fitness);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // The lock isn't held during evaluation so other threads can keep using the cache.
+        let fitness = self.fitness_function.evaluate(genome)?;
+        if self.capacity > 0 {
+            self.lock_entries().insert(genome, fitness, self.capacity);
+        }
+        Ok(fitness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn counting_fitness(calls: &AtomicUsize) -> impl Fn(&u32) -> GeneticResult<f64> + '_ {
+        move |genome: &u32| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(*genome as f64)
+        }
+    }
+
+    #[test]
+    fn repeated_genome_is_only_evaluated_once() {
+        let calls = AtomicUsize::new(0);
+        let cache = FitnessCache::new(counting_fitness(&calls), 10);
+        assert_eq!(7f64, cache.evaluate(&7).unwrap());
+        assert_eq!(7f64, cache.evaluate(&7).unwrap());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+        let statistics = cache.get_statistics();
+        assert_eq!(1, statistics.get_hits());
+        assert_eq!(1, statistics.get_misses());
+        assert_eq!(0.5, statistics.get_hit_rate());
+    }
+
+    #[test]
+    fn least_recently_used_genome_is_evicted_when_full() {
+        let calls = AtomicUsize::new(0);
+        let cache = FitnessCache::new(counting_fitness(&calls), 2);
+        cache.evaluate(&1).unwrap();
+        cache.evaluate(&2).unwrap();
+        cache.evaluate(&1).unwrap();
+        cache.evaluate(&3).unwrap();
+        assert_eq!(2, cache.get_size());
+        cache.evaluate(&1).unwrap();
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        cache.evaluate(&2).unwrap();
+        assert_eq!(4, calls.load(Ordering::SeqCst));
+    }
+
+    /// A genome whose hash ignores its value, so every instance collides.
+    #[derive(Clone, PartialEq, Eq)]
+    struct Colliding(u32);
+
+    impl Hash for Colliding {
+        fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+            0u32.hash(state);
+        }
+    }
+
+    #[test]
+    fn genomes_with_the_same_hash_keep_their_own_fitness() {
+        let cache = FitnessCache::new(|genome: &Colliding| Ok(genome.0 as f64), 10);
+        assert_eq!(1f64, cache.evaluate(&Colliding(1)).unwrap());
+        assert_eq!(2f64, cache.evaluate(&Colliding(2)).unwrap());
+        assert_eq!(1f64, cache.evaluate(&Colliding(1)).unwrap());
+        assert_eq!(2, cache.get_size());
+        assert_eq!(1, cache.get_statistics().get_hits());
+    }
+
+    #[test]
+    fn cache_can_be_shared_between_threads() {
+        let cache = Arc::new(FitnessCache::new(|genome: &u32| Ok(*genome as f64 * 2f64), 100));
+        let handles: Vec<_> = (0..4).map(|_| {
+            let cache = cache.clone();
+            thread::spawn(move || {
+                for genome in 0..10u32 {
+                    assert_eq!(genome as f64 * 2f64, cache.evaluate(&genome).unwrap());
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let statistics = cache.get_statistics();
+        assert_eq!(40, statistics.get_hits() + statistics.get_misses());
+        assert_eq!(10, cache.get_size());
+    }
+}
@@ -0,0 +1,472 @@
+//! A generational genetic algorithm over any genetic collection.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use rand::{Rng, StdRng};
+
+use ::diversity::DiversityStats;
+use ::error::GeneticError;
+use ::individual::Individual;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::interfaces::local_search::LocalSearch;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::interfaces::replacement::Replacement;
+use ::interfaces::selection_fitness::SelectionFitness;
+use ::local_search::LearningMode;
+use ::replacement::Generational;
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+const DEFAULT_TOURNAMENT_SIZE: usize = 2;
+const DEFAULT_ELITE_COUNT: usize = 1;
+
+/// Measures the diversity of a population, e.g. `DiversityStats::from_genetic_strings`.
+pub type DiversityMeasure<G> = fn(&[&G]) -> DiversityStats;
+
+/// Decides how many of the offspring of a generation receive local search, given the number
+/// of the generation being bred and the number of offspring.
+pub type LocalSearchSchedule = fn(usize, usize) -> usize;
+
+/// Evolves a population of genomes by tournament selection and breeding, reporting each
+/// generation to its observers. By default the offspring replace the whole population except
+/// for its fittest genome, and genomes whose evaluation fails, e.g. a genetic string that
+/// divides by zero, get a fitness of 0.
+pub struct GeneticAlgorithm<G, F> {
+    population: Vec<Individual<G>>,
+    fitness_function: F,
+    tournament_size: usize,
+    generation: usize,
+    evaluations: usize,
+    evaluated: bool,
+    diversity_measure: Option<DiversityMeasure<G>>,
+    selection_fitness: Option<Box<dyn SelectionFitness<G> + Send>>,
+    replacement: Box<dyn Replacement<G> + Send>,
+    local_search: Option<(Box<dyn LocalSearch<G> + Send>, LearningMode)>,
+    local_search_schedule: Option<LocalSearchSchedule>,
+    learned_fitness: HashMap<G, f64>,
+    progress: Progress<G>
+}
+
+impl<G, F> GeneticAlgorithm<G, F>
+    where G: GeneticCollection<Child = G> + Clone + Hash + Eq, F: FitnessFunction<G> {
+    /// Creates a genetic algorithm. The population is evaluated when the first generation
+    /// is requested.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>initial_population - the genomes of generation 0</li>
+    /// <li>fitness_function - scores the genomes, higher is fitter</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The genetic algorithm if successful.</li>
+    /// <li>CollectionEmptyError if the initial population is empty.</li>
+    /// </ul>
+    pub fn new(initial_population: Vec<G>, fitness_function: F) -> GeneticResult<GeneticAlgorithm<G, F>> {
+        if initial_population.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        Ok(GeneticAlgorithm {
+            population: initial_population.into_iter()
+                .map(|genome| Individual::new(genome, 0f64))
+                .collect(),
+            fitness_function,
+            tournament_size: DEFAULT_TOURNAMENT_SIZE,
+            generation: 0,
+            evaluations: 0,
+            evaluated: false,
+            diversity_measure: None,
+            selection_fitness: None,
+            replacement: Box::new(Generational::new(DEFAULT_ELITE_COUNT)),
+            local_search: None,
+            local_search_schedule: None,
+            learned_fitness: HashMap::new(),
+            progress: Progress::new(0f64)
+        })
+    }
+
+    /// Sets how many genomes compete in each selection tournament. Defaults to 2.
+    pub fn set_tournament_size(&mut self, tournament_size: usize) {
+        self.tournament_size = tournament_size.max(1);
+    }
+
+    /// Sets how parents are scored for selection, e.g. with fitness sharing. Parents are
+    /// selected by their fitness by default.
+    pub fn set_selection_fitness(&mut self, selection_fitness: Box<dyn SelectionFitness<G> + Send>) {
+        self.selection_fitness = Some(selection_fitness);
+    }
+
+    /// Sets how the offspring replace the population, e.g. with deterministic crowding.
+    /// Defaults to generational replacement keeping the single fittest genome.
+    pub fn set_replacement(&mut self, replacement: Box<dyn Replacement<G> + Send>) {
+        self.replacement = replacement;
+    }
+
+    /// Applies a local search to the offspring before they replace the population, making
+    /// the algorithm memetic. The evaluations made by the local search are counted in the
+    /// statistics.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>local_search - improves a single genome</li>
+    /// <li>mode - whether improved genomes replace the offspring or only lend them their
+    /// fitness for parent selection</li>
+    /// </ul>
+    pub fn set_local_search(&mut self, local_search: Box<dyn LocalSearch<G> + Send>, mode: LearningMode) {
+        self.local_search = Some((local_search, mode));
+    }
+
+    /// Sets how many offspring receive local search each generation. The fittest offspring
+    /// are chosen. Every offspring receives it by default.
+    pub fn set_local_search_schedule(&mut self, schedule: LocalSearchSchedule) {
+        self.local_search_schedule = Some(schedule);
+    }
+
+    /// Sets the measure used to add diversity figures to the statistics of every
+    /// generation. No diversity figures are taken by default.
+    pub fn set_diversity_measure(&mut self, diversity_measure: DiversityMeasure<G>) {
+        self.diversity_measure = Some(diversity_measure);
+    }
+
+    pub fn get_population(&self) -> &[Individual<G>] {
+        &self.population
+    }
+
+    /// Gives access to the evaluated population, e.g. to exchange migrants between islands.
+    /// Any genome put into the population must come with its fitness.
+    pub fn get_population_mutable(&mut self) -> &mut [Individual<G>] {
+        &mut self.population
+    }
+
+    pub fn get_fitness_function(&self) -> &F {
+        &self.fitness_function
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// Applies the local search, if there is one, to the fittest offspring the schedule
+    /// allows. In Baldwinian mode the offspring keep their own fitness and the fitness of
+    /// the improved genome is remembered for parent selection only.
+    fn improve(&mut self, offspring: &mut [Individual<G>], rng: &mut StdRng) -> GeneticResult<()> {
+        let (local_search, mode) = match self.local_search {
+            Some((ref mut local_search, mode)) => (local_search, mode),
+            None => return Ok(())
+        };
+        let count = match self.local_search_schedule {
+            Some(schedule) => schedule(self.generation + 1, offspring.len()),
+            None => offspring.len()
+        };
+        let mut ranking: Vec<usize> = (0..offspring.len()).collect();
+        ranking.sort_by(|&a, &b| offspring[b].get_fitness().partial_cmp(&offspring[a].get_fitness())
+            .unwrap_or(::std::cmp::Ordering::Equal));
+        for &index in ranking.iter().take(count) {
+            let (improved, evaluations) = local_search.search(offspring[index].clone(), &self.fitness_function, rng)?;
+            self.evaluations += evaluations;
+            match mode {
+                LearningMode::Lamarckian => offspring[index] = improved,
+                LearningMode::Baldwinian => {
+                    self.learned_fitness.insert(offspring[index].get_genome().clone(), improved.get_fitness());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A copy of the population carrying the fitness learned by Baldwinian local search, or
+    /// None if no member has learned any.
+    fn with_learned_fitness(&self) -> Option<Vec<Individual<G>>> {
+        if self.learned_fitness.is_empty() {
+            return None;
+        }
+        Some(self.population.iter()
+            .map(|individual| {
+                let fitness = self.learned_fitness.get(individual.get_genome()).cloned()
+                    .unwrap_or_else(|| individual.get_fitness());
+                Individual::new(individual.get_genome().clone(), fitness)
+            })
+            .collect())
+    }
+
+    fn evaluate(&mut self, genome: &G) -> f64 {
+        self.evaluations += 1;
+        self.fitness_function.evaluate(genome).unwrap_or(self.progress.get_invalid_fitness())
+    }
+
+    fn evaluate_initial_population(&mut self) {
+        if self.evaluated {
+            return;
+        }
+        self.evaluated = true;
+        let mut population = ::std::mem::take(&mut self.population);
+        for individual in &mut population {
+            let fitness = self.evaluate(individual.get_genome());
+            individual.set_fitness(fitness);
+        }
+        self.population = population;
+        let stats = self.summarise();
+        self.update_best(&stats);
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        let mut stats = GenerationStats::from_population(self.generation, &self.population, self.evaluations);
+        if let Some(diversity_measure) = self.diversity_measure {
+            let genomes: Vec<&G> = self.population.iter().map(|individual| individual.get_genome()).collect();
+            stats.set_diversity(diversity_measure(&genomes));
+        }
+        stats
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        let candidates = self.population.iter().map(|individual| (individual.get_genome(), individual.get_fitness()));
+        self.progress.update_best(candidates, stats);
+    }
+
+    fn tournament(&self, scores: &[f64], rng: &mut StdRng) -> usize {
+        let mut winner = rng.gen_range(0, scores.len());
+        for _ in 1..self.tournament_size {
+            let challenger = rng.gen_range(0, scores.len());
+            if scores[challenger] > scores[winner] {
+                winner = challenger;
+            }
+        }
+        winner
+    }
+}
+
+impl<G, F> Optimizer<G> for GeneticAlgorithm<G, F>
+    where G: GeneticCollection<Child = G> + Clone + Hash + Eq, F: FitnessFunction<G> {
+    /// Evaluates the population if that hasn't happened yet and summarises it.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.evaluate_initial_population();
+        Ok(self.summarise())
+    }
+
+    /// Breeds the next generation and replaces the current one with it. Of the two tournament
+    /// winners, the one with the higher selection score breeds with the other as its mate.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.evaluate_initial_population();
+        let learned = self.with_learned_fitness();
+        let selected_from = learned.as_ref().unwrap_or(&self.population);
+        let scores = match self.selection_fitness {
+            Some(ref mut selection_fitness) => selection_fitness.selection_fitness(selected_from)?,
+            None => selected_from.iter().map(|individual| individual.get_fitness()).collect()
+        };
+        let offspring_count = self.replacement.offspring_count(self.population.len());
+        let mut offspring = Vec::with_capacity(offspring_count);
+        let mut parents = Vec::with_capacity(offspring_count);
+        for _ in 0..offspring_count {
+            let first = self.tournament(&scores, rng);
+            let second = self.tournament(&scores, rng);
+            // Genomes such as NEAT take their structure from the fitter parent, which is self.
+            let (father, mother) = if scores[second] > scores[first] {
+                (second, first)
+            } else {
+                (first, second)
+            };
+            let child = self.population[father].get_genome()
+                .breed(self.population[mother].get_genome(), rng)?;
+            let fitness = self.evaluate(&child);
+            offspring.push(Individual::new(child, fitness));
+            parents.push((father, mother));
+        }
+        self.improve(&mut offspring, rng)?;
+        let population = ::std::mem::take(&mut self.population);
+        self.population = self.replacement.replace(population, offspring, &parents, rng)?;
+        let survivors: HashSet<&G> = self.population.iter().map(|individual| individual.get_genome()).collect();
+        self.learned_fitness.retain(|genome, _| survivors.contains(genome));
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<G> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<G> {
+        &mut self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use rand::SeedableRng;
+    use ::genetic_byte::GeneticByte;
+    use ::genetic_string::GeneticString;
+    use ::interfaces::fitness_function::TargetFitness;
+    use ::interfaces::observer::Observer;
+    use ::termination::{any, FitnessThreshold, MaxEvaluations, MaxGenerations};
+    use ::diversity::LevenshteinDistance;
+    use ::niching::{DeterministicCrowding, FitnessSharing};
+    use ::bit_string::BitString;
+    use ::local_search::{HillClimbing, Improvement};
+
+    struct Recorder {
+        generations: Arc<Mutex<Vec<GenerationStats>>>,
+        finished: Arc<Mutex<bool>>
+    }
+
+    impl Observer<GeneticString<GeneticByte>> for Recorder {
+        fn on_generation(&mut self, stats: &GenerationStats) {
+            self.generations.lock().unwrap().push(stats.clone());
+        }
+
+        fn on_finish(&mut self, _best: &GeneticString<GeneticByte>, _fitness: f64, _stats: &GenerationStats) {
+            *self.finished.lock().unwrap() = true;
+        }
+    }
+
+    fn build_genetic_algorithm(rng: &mut StdRng) -> GeneticAlgorithm<GeneticString<GeneticByte>, TargetFitness> {
+        let population = (0..20).map(|_| GeneticString::new(7, rng).unwrap()).collect();
+        GeneticAlgorithm::new(population, TargetFitness::new(100f64)).unwrap()
+    }
+
+    #[test]
+    fn observers_are_notified_of_every_generation_and_the_finish() {
+        let mut rng = StdRng::from_seed(&[7usize][..]);
+        let mut genetic_algorithm = build_genetic_algorithm(&mut rng);
+        let generations = Arc::new(Mutex::new(vec!()));
+        let finished = Arc::new(Mutex::new(false));
+        genetic_algorithm.add_observer(Box::new(Recorder {
+            generations: generations.clone(),
+            finished: finished.clone()
+        }));
+        let stats = genetic_algorithm.run(&mut MaxGenerations::new(5), &mut rng).unwrap();
+        assert_eq!(5, generations.lock().unwrap().len());
+        assert!(*finished.lock().unwrap());
+        assert_eq!(5, stats.get_generation());
+        assert_eq!(20 + 5 * 19, stats.get_evaluations());
+    }
+
+    #[test]
+    fn elitism_never_lets_the_best_fitness_drop() {
+        let mut rng = StdRng::from_seed(&[11usize][..]);
+        let mut genetic_algorithm = build_genetic_algorithm(&mut rng);
+        let mut best_fitness = genetic_algorithm.get_statistics(&mut rng).unwrap().get_best_fitness();
+        for _ in 0..10 {
+            let stats = genetic_algorithm.next_generation(&mut rng).unwrap();
+            assert!(stats.get_best_fitness() >= best_fitness);
+            best_fitness = stats.get_best_fitness();
+        }
+    }
+
+    #[test]
+    fn run_stops_at_the_first_criterion_met() {
+        let mut rng = StdRng::from_seed(&[3usize][..]);
+        let mut genetic_algorithm = build_genetic_algorithm(&mut rng);
+        let mut termination = any(vec![Box::new(MaxGenerations::new(50)), Box::new(MaxEvaluations::new(60))]);
+        let stats = genetic_algorithm.run(&mut termination, &mut rng).unwrap();
+        assert_eq!(3, stats.get_generation());
+    }
+
+    #[test]
+    fn diversity_is_reported_once_a_measure_is_set() {
+        let mut rng = StdRng::from_seed(&[5usize][..]);
+        let mut genetic_algorithm = build_genetic_algorithm(&mut rng);
+        assert!(genetic_algorithm.get_statistics(&mut rng).unwrap().get_diversity().is_none());
+        genetic_algorithm.set_diversity_measure(DiversityStats::from_genetic_strings);
+        let stats = genetic_algorithm.next_generation(&mut rng).unwrap();
+        assert!(stats.get_diversity().unwrap().get_unique_fraction() > 0f64);
+    }
+
+    #[test]
+    fn niching_operators_plug_into_the_engine() {
+        let mut rng = StdRng::from_seed(&[13usize][..]);
+        let mut genetic_algorithm = build_genetic_algorithm(&mut rng);
+        genetic_algorithm.set_selection_fitness(Box::new(FitnessSharing::new(LevenshteinDistance, 3f64, 1f64)));
+        genetic_algorithm.set_replacement(Box::new(DeterministicCrowding::new(LevenshteinDistance)));
+        let stats = genetic_algorithm.run(&mut MaxGenerations::new(5), &mut rng).unwrap();
+        assert_eq!(20, stats.get_population_size());
+        assert_eq!(20 + 5 * 20, stats.get_evaluations());
+    }
+
+    /// Learns the all ones genome from any start.
+    struct LearnAllOnes;
+
+    impl LocalSearch<BitString> for LearnAllOnes {
+        fn search(&mut self, start: Individual<BitString>, _fitness_function: &dyn FitnessFunction<BitString>,
+                  _rng: &mut StdRng) -> GeneticResult<(Individual<BitString>, usize)> {
+            let length = start.get_genome().get_size();
+            Ok((Individual::new(BitString::from_bools(&vec![true; length]), length as f64), 1))
+        }
+    }
+
+    /// Passes the fitness through unchanged and records the scores it saw.
+    struct SeenScores {
+        scores: Arc<Mutex<Vec<f64>>>
+    }
+
+    impl SelectionFitness<BitString> for SeenScores {
+        fn selection_fitness(&mut self, population: &[Individual<BitString>]) -> GeneticResult<Vec<f64>> {
+            let scores: Vec<f64> = population.iter().map(|individual| individual.get_fitness()).collect();
+            self.scores.lock().unwrap().extend(scores.iter().cloned());
+            Ok(scores)
+        }
+    }
+
+    #[test]
+    fn lamarckian_learning_rewrites_genomes_and_baldwinian_learning_only_changes_selection() {
+        for &mode in &[LearningMode::Lamarckian, LearningMode::Baldwinian] {
+            let mut rng = StdRng::from_seed(&[17usize][..]);
+            let population = (0..10).map(|_| BitString::new(30)).collect();
+            let one_max = |bit_string: &BitString| Ok(bit_string.count_ones() as f64);
+            let mut genetic_algorithm = GeneticAlgorithm::new(population, one_max).unwrap();
+            genetic_algorithm.set_local_search(Box::new(LearnAllOnes), mode);
+            let seen = Arc::new(Mutex::new(vec!()));
+            genetic_algorithm.set_selection_fitness(Box::new(SeenScores { scores: seen.clone() }));
+            genetic_algorithm.next_generation(&mut rng).unwrap();
+            let stats = genetic_algorithm.next_generation(&mut rng).unwrap();
+            // Nine offspring beside the elite each generation, each evaluated and searched once.
+            assert_eq!(10 + 2 * (9 + 9), stats.get_evaluations());
+            let inconsistent = genetic_algorithm.get_population().iter()
+                .filter(|individual| individual.get_fitness() != individual.get_genome().count_ones() as f64)
+                .count();
+            assert_eq!(0, inconsistent);
+            let learned = genetic_algorithm.get_population().iter()
+                .filter(|individual| individual.get_genome().count_ones() == 30)
+                .count();
+            // Both modes select the second generation by the learned fitness, but only
+            // Lamarckian learning writes the learned genomes back into the population.
+            assert!(seen.lock().unwrap().contains(&30f64));
+            match mode {
+                LearningMode::Lamarckian => assert!(learned > 0),
+                LearningMode::Baldwinian => {
+                    assert_eq!(0, learned);
+                    assert!(stats.get_best_fitness() < 30f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn baldwinian_learning_reports_the_fitness_the_best_genome_really_has() {
+        let mut rng = StdRng::from_seed(&[18usize][..]);
+        let population = (0..10).map(|_| BitString::new(30)).collect();
+        let one_max = |bit_string: &BitString| Ok(bit_string.count_ones() as f64);
+        let mut genetic_algorithm = GeneticAlgorithm::new(population, one_max).unwrap();
+        genetic_algorithm.set_local_search(Box::new(HillClimbing::new(Improvement::First, 30, 5)), LearningMode::Baldwinian);
+        let stats = genetic_algorithm.run(&mut any(vec![Box::new(MaxGenerations::new(10)), Box::new(FitnessThreshold::new(30f64))]), &mut rng).unwrap();
+        let best = genetic_algorithm.get_best().unwrap();
+        assert_eq!(one_max(best.get_genome()).unwrap(), best.get_fitness());
+        assert_eq!(best.get_fitness(), stats.get_best_fitness());
+    }
+
+    #[test]
+    fn empty_initial_population_is_rejected() {
+        let population: Vec<GeneticString<GeneticByte>> = vec!();
+        assert!(GeneticAlgorithm::new(population, TargetFitness::new(0f64)).is_err());
+    }
+}
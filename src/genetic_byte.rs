@@ -6,7 +6,7 @@ use rand::Rng;
 use std::fmt;
 use ::interfaces::genetic_collection_component::GeneticCollectionComponent;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GeneticByte{
     GeneticValue(u8),
     GeneticOperator(u8)
@@ -15,13 +15,13 @@ pub enum GeneticByte{
 
 impl Mutable for GeneticByte {
     fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
-        match self {
-            &mut GeneticByte::GeneticValue(_) => {
+        match *self {
+            GeneticByte::GeneticValue(_) => {
                 let bit_to_flip = rng.gen_range(0, 8);
                 self.mutate_value(bit_to_flip as u32)?;
                 Ok(())
             }
-            &mut GeneticByte::GeneticOperator(ref mut operator) => {
+            GeneticByte::GeneticOperator(ref mut operator) => {
                 let value = rng.gen_range(1, 5);
                 *operator = match value {
                     1 => '+',
@@ -44,10 +44,10 @@ impl GeneticCollectionComponent for GeneticByte {
                 Ok(GeneticByte::GeneticValue(random_value))
             },
             1 => match rng.gen_range(0, 4) {
-                0 => Ok(GeneticByte::GeneticOperator('+' as u8)),
-                1 => Ok(GeneticByte::GeneticOperator('-' as u8)),
-                2 => Ok(GeneticByte::GeneticOperator('*' as u8)),
-                3 => Ok(GeneticByte::GeneticOperator('/' as u8)),
+                0 => Ok(GeneticByte::GeneticOperator(b'+')),
+                1 => Ok(GeneticByte::GeneticOperator(b'-')),
+                2 => Ok(GeneticByte::GeneticOperator(b'*')),
+                3 => Ok(GeneticByte::GeneticOperator(b'/')),
                 _ => Err(GeneticError::GenByteCreationError)
             },
             _ => Err(GeneticError::GenByteCreationError)
@@ -65,8 +65,8 @@ impl GeneticCollectionComponent for GeneticByte {
     ///     <li> false - if higher priority
     /// </ul>
     fn get_operator_precedence(&self) -> GeneticResult<u8> {
-        match self {
-            &GeneticByte::GeneticOperator(operator) => {
+        match *self {
+            GeneticByte::GeneticOperator(operator) => {
                 let result = match operator as char {
                     '+' => 0,
                     '-' => 0,
@@ -76,22 +76,22 @@ impl GeneticCollectionComponent for GeneticByte {
                 };
                 Ok(result)
             },
-            &GeneticByte::GeneticValue(_) => {
+            GeneticByte::GeneticValue(_) => {
                 Err(GeneticError::InvalidGeneticByteType)
             }
         }
     }
     fn get_value(&self) -> GeneticResult<u8> {
-        match self {
-            &GeneticByte::GeneticValue(value) => Ok(value),
-            &GeneticByte::GeneticOperator(_) => Err(GeneticError::InvalidGeneticByteType)
+        match *self {
+            GeneticByte::GeneticValue(value) => Ok(value),
+            GeneticByte::GeneticOperator(_) => Err(GeneticError::InvalidGeneticByteType)
         }
     }
 
     fn get_operator(&self) -> GeneticResult<u8> {
-        match self {
-            &GeneticByte::GeneticValue(_) => Err(GeneticError::InvalidGeneticByteType),
-            &GeneticByte::GeneticOperator(operator) => Ok(operator)
+        match *self {
+            GeneticByte::GeneticValue(_) => Err(GeneticError::InvalidGeneticByteType),
+            GeneticByte::GeneticOperator(operator) => Ok(operator)
         }
     }
 }
@@ -106,8 +106,8 @@ impl GeneticByte {
     /// none
     fn mutate_value(&mut self, bit_to_flip: u32) -> GeneticResult<()>
     {
-        match self {
-            &mut GeneticByte::GeneticValue(ref mut value) => {
+        match *self {
+            GeneticByte::GeneticValue(ref mut value) => {
             let mut u8_bit_representation = [false; 8];
                 let mut u8_value = *value;
                 for bool in &mut u8_bit_representation[..] {
@@ -118,7 +118,7 @@ impl GeneticByte {
                     };
                     u8_value /= 2
                 }
-                if u8_bit_representation[bit_to_flip as usize] == true {
+                if u8_bit_representation[bit_to_flip as usize] {
                     *value -= 2u8.pow(bit_to_flip);
                     Ok(())
                 } else {
@@ -132,18 +132,18 @@ impl GeneticByte {
 }
 impl fmt::Display for GeneticByte {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            &GeneticByte::GeneticValue(value) => {
+        match *self {
+            GeneticByte::GeneticValue(value) => {
                 write!(f, "{} ", value)
             },
-            &GeneticByte::GeneticOperator(operator) => {
+            GeneticByte::GeneticOperator(operator) => {
                 write!(f, "{} ", operator as char)
             }
         }
     }
 }
 pub trait GeneticByteTestUtilities {
-    fn new(u8, usize) -> GeneticResult<Self> where Self: Sized;
+    fn new(starting_value: u8, index: usize) -> GeneticResult<Self> where Self: Sized;
 }
 
 impl GeneticByteTestUtilities for GeneticByte {
@@ -163,9 +163,9 @@ mod tests{
     #[test]
     fn mutate_changes_1_byte() {
         let mut random_number_generator = StdRng::new().unwrap();
-        let mut g_byte =create_g_byte(&mut random_number_generator);
+        let mut g_byte = create_g_byte();
         let start_value= g_byte.get_value().unwrap() as i16;
-        g_byte.mutate(&mut random_number_generator);
+        g_byte.mutate(&mut random_number_generator).unwrap();
         let new_value = g_byte.get_value().unwrap() as i16 ;
         let one_bit_was_changed = one_bit_changed(new_value, start_value);
         assert!(one_bit_was_changed);
@@ -181,7 +181,7 @@ mod tests{
         number_bits_changed.abs() ==1
     }
 
-    fn create_g_byte(rng: &mut StdRng) -> GeneticByte {
+    fn create_g_byte() -> GeneticByte {
         GeneticByteTestUtilities::new(0, 0).unwrap()
     }
 
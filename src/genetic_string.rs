@@ -10,15 +10,17 @@ use ::GeneticResult;
 //Constants
 const LEVELS_OF_PRECEDENCE: u8 = 2;
 
+/// A binary arithmetic operation over the values of two genetic string slices.
+type Operation = Box<dyn Fn(GeneticResult<i32>, GeneticResult<i32>) -> GeneticResult<i32>>;
+
 
 //
 use rand::{StdRng, Rng};
 use probability::distribution::Binomial;
 use std::fmt;
-use std;
 
 /// This struct will be used
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GeneticString<T> where T: GeneticCollectionComponent{
     vector: Vec<T>
 }
@@ -56,15 +58,14 @@ impl<T> GeneticCollection for GeneticString<T> where T: GeneticCollectionCompone
     ///     <li>MutationError If the process fails.</li>
     /// </ul>
     fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
-        let mut child: Self;
-        if self.get_size() <= mate.get_size() {
-            child = GeneticString::mix_elements(&self, &mate, rng)?;
-        } else {
-            child = GeneticString::mix_elements(&self, &mate, rng)?;
-        }
+        let mut child = GeneticString::mix_elements(self, mate, rng)?;
         child.mutate(rng)?;
         Ok(child)
     }
+
+    fn get_size(&self) -> usize {
+        self.get_vector_immutable().len()
+    }
 }
 
 
@@ -78,6 +79,11 @@ impl<T> Mutable for GeneticString<T> where T: GeneticCollectionComponent{
             self.get_vector_mutable()[element_to_mutate_index].mutate(random_number_generator)?;
         }
         if random_number_generator.gen_range(0,100) == 99 {
+            if self.get_size() == 0 {
+                let value = T::new(0, random_number_generator)?;
+                self.get_vector_mutable().push(value);
+                return Ok(());
+            }
             let element_index = random_number_generator.gen_range(0,self.get_size());
             if random_number_generator.gen_range(0,2) == 1 {
                 self.insert_element_at_index(element_index, random_number_generator)?;
@@ -111,7 +117,7 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
     /// <li> Arithmetic error if an error occurred</li>
     /// </ul>
     pub fn evaluate_expression(&self) -> GeneticResult<i32> {
-        GeneticString::calculate_value(&self.get_vector_immutable())
+        GeneticString::calculate_value(self.get_vector_immutable())
     }
 
     /// # Purpose
@@ -134,6 +140,12 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
         Ok(GeneticString { vector: genetic_string_vec })
     }
 
+    /// Creates a genetic string holding the given components, e.g. one built from another
+    /// representation of an expression.
+    pub fn from_vector(vector: Vec<T>) -> GeneticString<T> {
+        GeneticString { vector }
+    }
+
     /// Returns the vector of genetic bytes contained in the genetic string. Designed to be less
     /// restrictive then get vector immutable.
     pub fn get_vector_immutable(&self) -> &Vec<T> {
@@ -144,10 +156,6 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
         &mut self.vector
     }
 
-    fn get_size(&self) -> usize {
-        self.get_vector_immutable().len()
-    }
-
     fn mix_elements(parent_1: &Self, parent_2: &Self, rand_gen:&mut StdRng) -> GeneticResult<Self>{
         if  parent_1.get_size() == 0 {
             let child: Self = GeneticString::new(0, rand_gen)?;
@@ -167,7 +175,7 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
     }
     fn insert_element_at_index(&mut self, mut index: usize, rng: &mut StdRng) -> GeneticResult<()> {
         if index % 2 == 1 {
-            index = index - 1
+            index -= 1
         }
         self.get_vector_mutable().insert(index, T::new(index, rng)?);
         index +=1;
@@ -181,15 +189,13 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
         } else if self.get_size() == 1 {
             self.get_vector_mutable().pop();
         } else {
-            if self.get_size() == index {
-                index = index - 2;
-            } else if index & 2 == 1{
-                index = index - 1;
+            if index + 1 == self.get_size() || index % 2 == 1 {
+                index -= 1;
             }
             self.get_vector_mutable().remove(index);
             self.get_vector_mutable().remove(index);
         }
-        return Ok(());
+        Ok(())
     }
 
 
@@ -201,7 +207,7 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
     /// # Return
     /// GeneticResult<i32> if successful
     /// None if a failure occurs
-    fn operate(gen_str_slice: &[T], index: usize) -> Box<Fn(GeneticResult<i32>, GeneticResult<i32>) -> GeneticResult<i32>> {
+    fn operate(gen_str_slice: &[T], index: usize) -> Operation {
         match gen_str_slice[index].get_operator().unwrap() as char {                                //TODO: Remove unwrap call eventually
             '+' => Box::new(move |a: GeneticResult<i32>, b: GeneticResult<i32>| {
                 a?.checked_add(b?).ok_or(GeneticError::ComputationError)
@@ -227,7 +233,7 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
     /// # returns
     /// The genetic string slices value.
     fn calculate_value(gen_str_slice: &[T]) -> GeneticResult<i32>{
-        if gen_str_slice.len() == 0 {
+        if gen_str_slice.is_empty() {
             Ok(0)
         } else if gen_str_slice.len() == 1 {
             Ok(gen_str_slice[0].get_value()? as i32)
@@ -248,10 +254,6 @@ impl<T> GeneticString<T> where T: GeneticCollectionComponent{
         }
     }
 
-    fn create_random_number_generator() -> GeneticResult<StdRng> {
-        StdRng::new().map_err(|_: std::io::Error | GeneticError::RNGCreationError)
-    }
-
 }
 
 
@@ -266,7 +268,7 @@ pub trait GeneticStringTestUtilities <T: GeneticCollectionComponent> {
     ///
     /// # Returns
     /// a genetic string
-    fn new(Vec<T>) -> Self;
+    fn new(g_byte_vec: Vec<T>) -> Self;
 }
 
 impl<T> GeneticStringTestUtilities<T> for GeneticString<T> where T: GeneticCollectionComponent{
@@ -279,42 +281,71 @@ impl<T> GeneticStringTestUtilities<T> for GeneticString<T> where T: GeneticColle
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
     use ::genetic_byte::{GeneticByte, GeneticByteTestUtilities};
     #[test]
     fn single_byte_calculate_value_should_equal_23_when_byte_equal_23() {
-        let mut single_byte_genetic_string = build_single_item_genetic_string();
+        let single_byte_genetic_string = build_single_item_genetic_string();
         let value = match single_byte_genetic_string.evaluate_expression() {
             Ok(value) => value,
-            Err(error) => panic!("Test should not have received an error.")
+            Err(_) => panic!("Test should not have received an error.")
         };
         assert!(23 == value)
     }
 
+    #[test]
+    fn removing_the_last_value_also_removes_the_operator_before_it() {
+        let mut genetic_string = build_multi_item_gen_string();
+        genetic_string.remove_element_at_index(6).unwrap();
+        assert_eq!(5, genetic_string.get_size());
+        assert_eq!(3, genetic_string.evaluate_expression().unwrap());
+        genetic_string.remove_element_at_index(3).unwrap();
+        assert_eq!(3, genetic_string.get_size());
+        assert_eq!(9, genetic_string.evaluate_expression().unwrap());
+    }
+
+    #[test]
+    fn mutating_an_empty_genetic_string_can_only_grow_it() {
+        let mut rng = StdRng::from_seed(&[27usize][..]);
+        let mut genetic_string = build_empty_genetic_string();
+        while genetic_string.get_size() == 0 {
+            genetic_string.mutate(&mut rng).unwrap();
+        }
+        assert_eq!(1, genetic_string.get_size());
+        assert!(genetic_string.get_vector_immutable()[0].get_value().is_ok());
+    }
+
+    #[test]
+    fn removing_an_operator_also_removes_the_value_before_it() {
+        let mut genetic_string = build_multi_item_gen_string();
+        genetic_string.remove_element_at_index(1).unwrap();
+        assert_eq!(5, genetic_string.get_size());
+        assert_eq!(2, genetic_string.get_vector_immutable()[0].get_value().unwrap());
+        assert_eq!(-10, genetic_string.evaluate_expression().unwrap());
+    }
+
     #[test]
     fn empty_genetic_string_calculate_value_should_equal_0_when_blank() {
-        let mut empty_genetic_string = build_empty_genetic_string();
+        let empty_genetic_string = build_empty_genetic_string();
         let value = match empty_genetic_string.evaluate_expression() {
             Ok(value) => value,
-            Err(error) => panic!("Test should not have received an error.")
+            Err(_) => panic!("Test should not have received an error.")
         };
         assert_eq!(0, value)
     }
     #[test]
     fn error_should_occur_when_invalid_multi_item_gen_string_is_evaluated() {
-        let mut bad_mult_item_gen_str = build_bad_multi_item_gen_string();
+        let bad_mult_item_gen_str = build_bad_multi_item_gen_string();
         let result = match bad_mult_item_gen_str.evaluate_expression() {
-            Ok(value) => panic!("value should not be okay"),
+            Ok(_) => panic!("value should not be okay"),
             Err(error) => error
         };
-        assert!(match result{
-            ::error::GeneticError::ComputationError => true,
-            _ => false
-        })
+        assert!(matches!(result, ::error::GeneticError::ComputationError))
     }
 
     #[test]
     fn calculate_value_returns_negative_5_when_given_expression_which_equals_negative_5() {
-        let mut mult_item_gen_str = build_multi_item_gen_string();
+        let mult_item_gen_str = build_multi_item_gen_string();
         let result = match mult_item_gen_str.evaluate_expression() {
             Ok(value) => value,
             Err(error) => panic!("Test should not have received the error: {}.", error)
@@ -340,32 +371,29 @@ mod tests {
     /// # Return
     /// The genetic string
     fn build_single_item_genetic_string() -> GeneticString<GeneticByte> {
-        let new_g_vec: Vec<GeneticByte> = vec!();
-        let mut g_byte_1 = GeneticByteTestUtilities::new(23, 0).unwrap();
-        let mut g_vec = vec![g_byte_1];
+        let g_byte_1 = GeneticByteTestUtilities::new(23, 0).unwrap();
+        let g_vec = vec![g_byte_1];
         GeneticStringTestUtilities::new(g_vec)
     }
 
     fn build_multi_item_gen_string() -> GeneticString<GeneticByte> {
-        let new_g_vec: Vec<GeneticByte> = vec!();
-        let mut g_byte_1 = GeneticByteTestUtilities::new(5, 0).unwrap();
-        let mut g_byte_2 = GeneticByteTestUtilities::new('+' as u8, 1).unwrap();
-        let mut g_byte_3 = GeneticByteTestUtilities::new(2, 2).unwrap();
-        let mut g_byte_4 = GeneticByteTestUtilities::new('-' as u8, 3).unwrap();
-        let mut g_byte_5 = GeneticByteTestUtilities::new(4, 4).unwrap();
-        let mut g_byte_6 = GeneticByteTestUtilities::new('*' as u8, 5).unwrap();
-        let mut g_byte_7 = GeneticByteTestUtilities::new(3, 6).unwrap();
+        let g_byte_1 = GeneticByteTestUtilities::new(5, 0).unwrap();
+        let g_byte_2 = GeneticByteTestUtilities::new(b'+', 1).unwrap();
+        let g_byte_3 = GeneticByteTestUtilities::new(2, 2).unwrap();
+        let g_byte_4 = GeneticByteTestUtilities::new(b'-', 3).unwrap();
+        let g_byte_5 = GeneticByteTestUtilities::new(4, 4).unwrap();
+        let g_byte_6 = GeneticByteTestUtilities::new(b'*', 5).unwrap();
+        let g_byte_7 = GeneticByteTestUtilities::new(3, 6).unwrap();
         let vector = vec![g_byte_1, g_byte_2, g_byte_3, g_byte_4, g_byte_5, g_byte_6, g_byte_7];
         GeneticStringTestUtilities::new(vector)
     }
 
     fn build_bad_multi_item_gen_string() -> GeneticString<GeneticByte> {
-        let new_g_vec: Vec<GeneticByte> = vec!();
-        let mut g_byte_1 = GeneticByteTestUtilities::new(5, 0).unwrap();
-        let mut g_byte_2 = GeneticByteTestUtilities::new('+' as u8, 1).unwrap();
-        let mut g_byte_3 = GeneticByteTestUtilities::new(2, 2).unwrap();
-        let mut g_byte_4 = GeneticByteTestUtilities::new('/' as u8, 3).unwrap();
-        let mut g_byte_5 = GeneticByteTestUtilities::new(0, 4).unwrap();
+        let g_byte_1 = GeneticByteTestUtilities::new(5, 0).unwrap();
+        let g_byte_2 = GeneticByteTestUtilities::new(b'+', 1).unwrap();
+        let g_byte_3 = GeneticByteTestUtilities::new(2, 2).unwrap();
+        let g_byte_4 = GeneticByteTestUtilities::new(b'/', 3).unwrap();
+        let g_byte_5 = GeneticByteTestUtilities::new(0, 4).unwrap();
         let vector = vec![g_byte_1, g_byte_2, g_byte_3, g_byte_4, g_byte_5];
         GeneticStringTestUtilities::new(vector)
     }
@@ -0,0 +1,725 @@
+//! Tree based genetic programming over integer arithmetic.
+//!
+//! A tree is stored as its nodes in prefix order, so every subtree is a contiguous slice and
+//! subtree crossover and mutation come down to splicing slices. The function set is supplied
+//! by the user; the arithmetic set evaluates with the same checked `i32` arithmetic as
+//! `GeneticString`, and a tree over it converts to and from a `GeneticString` whenever the
+//! flat string parses back into the same tree.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::genetic_byte::GeneticByte;
+use ::genetic_string::GeneticString;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::interfaces::genetic_collection_component::GeneticCollectionComponent;
+use ::mutable::Mutable;
+use ::prefix_tree::{self, DEFAULT_MAXIMUM_DEPTH, MUTATION_DEPTH};
+use ::GeneticResult;
+
+/// Computes the result of a function from its arguments, of which there are as many as the
+/// function's arity.
+pub type GpEvaluator = fn(&[i32]) -> GeneticResult<i32>;
+
+/// The identity given to the next primitive set created.
+static NEXT_SET_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A function of a primitive set. Functions are created by `PrimitiveSet::add_function` and
+/// carry the identity of the set that created them, so no other set accepts them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GpFunction {
+    set: usize,
+    index: usize,
+    arity: usize
+}
+
+impl GpFunction {
+    /// The position of the function in its primitive set.
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn get_arity(&self) -> usize {
+        self.arity
+    }
+}
+
+/// The name, arity and evaluator of a function in a primitive set.
+#[derive(Clone, Debug)]
+pub struct FunctionDefinition {
+    name: String,
+    arity: usize,
+    evaluator: GpEvaluator
+}
+
+impl FunctionDefinition {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn get_evaluator(&self) -> GpEvaluator {
+        self.evaluator
+    }
+
+    /// Whether the function is written between its two arguments, e.g. `(a + b)`, rather than
+    /// in front of them, e.g. `max(a, b, c)`.
+    fn is_infix(&self) -> bool {
+        self.arity == 2 && self.name.chars().all(|character| !character.is_alphanumeric())
+    }
+}
+
+/// A terminal of the terminal set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GpTerminal {
+    Constant(u8),
+    /// The input at the given index.
+    Variable(usize)
+}
+
+/// A node of a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GpNode {
+    Function(GpFunction),
+    Terminal(GpTerminal)
+}
+
+impl GpNode {
+    pub fn get_arity(&self) -> usize {
+        match *self {
+            GpNode::Function(function) => function.get_arity(),
+            GpNode::Terminal(_) => 0
+        }
+    }
+}
+
+/// The functions and terminals trees are built from. Every set, a clone included, has its own
+/// identity, and only accepts the functions it created.
+#[derive(Debug)]
+pub struct PrimitiveSet {
+    id: usize,
+    functions: Vec<FunctionDefinition>,
+    terminals: Vec<GpTerminal>
+}
+
+impl Clone for PrimitiveSet {
+    /// Copies the functions and terminals into a set with an identity of its own, so the
+    /// two can grow apart without accepting each other's functions.
+    fn clone(&self) -> PrimitiveSet {
+        PrimitiveSet { id: next_set_id(), functions: self.functions.clone(), terminals: self.terminals.clone() }
+    }
+}
+
+impl PrimitiveSet {
+    /// Creates a set with the given terminals and no functions yet.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The primitive set if successful.</li>
+    /// <li>CollectionEmptyError if there are no terminals.</li>
+    /// </ul>
+    pub fn new(terminals: Vec<GpTerminal>) -> GeneticResult<PrimitiveSet> {
+        if terminals.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        Ok(PrimitiveSet { id: next_set_id(), functions: vec!(), terminals })
+    }
+
+    /// The four checked arithmetic functions `+`, `-`, `*` and `/`, with the constants 0 to
+    /// 255 and the given number of variables as terminals.
+    pub fn arithmetic(variables: usize) -> PrimitiveSet {
+        let mut set = PrimitiveSet {
+            id: next_set_id(),
+            functions: vec!(),
+            terminals: (0..256).map(|constant| GpTerminal::Constant(constant as u8))
+                .chain((0..variables).map(GpTerminal::Variable))
+                .collect()
+        };
+        set.push("+", 2, |a| a[0].checked_add(a[1]).ok_or(GeneticError::ComputationError));
+        set.push("-", 2, |a| a[0].checked_sub(a[1]).ok_or(GeneticError::ComputationError));
+        set.push("*", 2, |a| a[0].checked_mul(a[1]).ok_or(GeneticError::ComputationError));
+        set.push("/", 2, |a| a[0].checked_div(a[1]).ok_or(GeneticError::ComputationError));
+        set
+    }
+
+    /// Adds a function to the set.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>name - how the function is written; a symbolic name such as `%` is written between
+    /// the arguments of a function of arity 2</li>
+    /// <li>arity - the number of arguments the function takes</li>
+    /// <li>evaluator - computes the function</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The function, for building nodes, if successful.</li>
+    /// <li>InvalidParameterError if the arity is 0, as constants belong in the terminal set,
+    /// or the set already has a function of that name.</li>
+    /// </ul>
+    pub fn add_function(&mut self, name: &str, arity: usize, evaluator: GpEvaluator) -> GeneticResult<GpFunction> {
+        if arity == 0 || self.get_function(name).is_some() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(self.push(name, arity, evaluator))
+    }
+
+    pub fn add_terminal(&mut self, terminal: GpTerminal) {
+        self.terminals.push(terminal);
+    }
+
+    /// The function with the given name.
+    pub fn get_function(&self, name: &str) -> Option<GpFunction> {
+        self.functions.iter().position(|definition| definition.name == name)
+            .map(|index| self.function_at(index))
+    }
+
+    /// The definition of a function of this set, or None if the function came from another
+    /// set.
+    pub fn get_definition(&self, function: GpFunction) -> Option<&FunctionDefinition> {
+        if function.set != self.id {
+            return None;
+        }
+        self.functions.get(function.index)
+    }
+
+    pub fn get_functions(&self) -> &[FunctionDefinition] {
+        &self.functions
+    }
+
+    pub fn get_terminals(&self) -> &[GpTerminal] {
+        &self.terminals
+    }
+
+    /// Whether the node is one of the functions or terminals of this set.
+    pub fn contains(&self, node: &GpNode) -> bool {
+        match *node {
+            GpNode::Function(function) => self.get_definition(function).is_some(),
+            GpNode::Terminal(terminal) => self.terminals.contains(&terminal)
+        }
+    }
+
+    fn push(&mut self, name: &str, arity: usize, evaluator: GpEvaluator) -> GpFunction {
+        self.functions.push(FunctionDefinition { name: name.to_string(), arity, evaluator });
+        self.function_at(self.functions.len() - 1)
+    }
+
+    fn function_at(&self, index: usize) -> GpFunction {
+        GpFunction { set: self.id, index, arity: self.functions[index].arity }
+    }
+
+    fn random_function(&self, rng: &mut StdRng) -> GpFunction {
+        self.function_at(rng.gen_range(0, self.functions.len()))
+    }
+
+    fn random_terminal(&self, rng: &mut StdRng) -> GpTerminal {
+        self.terminals[rng.gen_range(0, self.terminals.len())]
+    }
+}
+
+/// The mutation applied by `Mutable::mutate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpMutation {
+    /// Replaces a random subtree with a newly grown one.
+    Subtree,
+    /// Replaces a random node with another of the same arity.
+    Point,
+    /// Replaces the tree with one of its own subtrees.
+    Hoist,
+    /// Replaces a random subtree with a terminal.
+    Shrink
+}
+
+#[derive(Clone, Debug)]
+pub struct GpTree {
+    nodes: Vec<GpNode>,
+    primitives: Arc<PrimitiveSet>,
+    maximum_depth: usize,
+    mutation: GpMutation
+}
+
+impl GeneticCollection for GpTree {
+    type Child = GpTree;
+
+    /// Breeds a child by subtree crossover followed by mutation.
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.subtree_crossover(mate, rng);
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl Mutable for GpTree {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        match self.mutation {
+            GpMutation::Subtree => self.subtree_mutation(rng),
+            GpMutation::Point => self.point_mutation(rng),
+            GpMutation::Hoist => self.hoist_mutation(rng),
+            GpMutation::Shrink => self.shrink_mutation(rng)
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for GpTree {
+    fn eq(&self, other: &GpTree) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+impl Eq for GpTree {}
+
+impl Hash for GpTree {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nodes.hash(state);
+    }
+}
+
+impl Display for GpTree {
+    /// Writes the tree as a fully parenthesised infix expression.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_subtree(0, f).map(|_| ())
+    }
+}
+
+impl GpTree {
+    /// Creates a tree from its nodes in prefix order. The tree uses subtree mutation and a
+    /// maximum depth of 17 until configured otherwise.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tree if successful.</li>
+    /// <li>InvalidParameterError if a node isn't in the primitive set or the nodes don't
+    /// form exactly one complete tree.</li>
+    /// </ul>
+    pub fn from_nodes(nodes: Vec<GpNode>, primitives: Arc<PrimitiveSet>) -> GeneticResult<GpTree> {
+        if nodes.is_empty() || !nodes.iter().all(|node| primitives.contains(node))
+            || prefix_tree::subtree_end(&nodes, 0, &GpNode::get_arity) != Some(nodes.len()) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(GpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree })
+    }
+
+    /// Creates a tree in which every branch reaches exactly the given depth.
+    pub fn new_full(depth: usize, primitives: Arc<PrimitiveSet>, rng: &mut StdRng) -> GpTree {
+        let mut nodes = vec!();
+        generate(&primitives, depth, true, &mut nodes, rng);
+        GpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree }
+    }
+
+    /// Creates a tree whose branches stop growing at random, up to the given depth.
+    pub fn new_grow(depth: usize, primitives: Arc<PrimitiveSet>, rng: &mut StdRng) -> GpTree {
+        let mut nodes = vec!();
+        generate(&primitives, depth, false, &mut nodes, rng);
+        GpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree }
+    }
+
+    /// Ramped half-and-half initialisation, see `prefix_tree::ramped_half_and_half`.
+    pub fn ramped_half_and_half(population_size: usize, minimum_depth: usize, maximum_depth: usize,
+                                primitives: Arc<PrimitiveSet>, rng: &mut StdRng) -> GeneticResult<Vec<GpTree>> {
+        prefix_tree::ramped_half_and_half(population_size, minimum_depth, maximum_depth, |depth, full| Ok(if full {
+            GpTree::new_full(depth, primitives.clone(), rng)
+        } else {
+            GpTree::new_grow(depth, primitives.clone(), rng)
+        }))
+    }
+
+    /// Parses a genetic string into a tree using the same precedence rules as
+    /// `GeneticString::evaluate_expression`. Each operator becomes the function of arity 2
+    /// named after it, so over the arithmetic set both evaluate to the same value.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tree if successful.</li>
+    /// <li>The error `evaluate_expression` would run into if the string isn't a valid
+    /// expression.</li>
+    /// <li>ConversionError if the set has no function for an operator.</li>
+    /// <li>InvalidParameterError if the set lacks a constant the string uses.</li>
+    /// </ul>
+    pub fn from_genetic_string<T>(genetic_string: &GeneticString<T>, primitives: Arc<PrimitiveSet>) -> GeneticResult<GpTree>
+        where T: GeneticCollectionComponent {
+        let mut nodes = vec!();
+        parse_slice(genetic_string.get_vector_immutable(), &primitives, &mut nodes)?;
+        GpTree::from_nodes(nodes, primitives)
+    }
+
+    /// Writes the tree as a genetic string, where the expression fits one.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The genetic string if successful.</li>
+    /// <li>ConversionError if the tree uses variables or functions other than `+`, `-`, `*`
+    /// and `/`, or its shape can't be expressed through the precedence rules of genetic
+    /// strings.</li>
+    /// </ul>
+    pub fn to_genetic_string(&self) -> GeneticResult<GeneticString<GeneticByte>> {
+        let mut bytes = vec!();
+        for node in self.in_order()? {
+            bytes.push(match node {
+                GpNode::Function(function) => GeneticByte::GeneticOperator(self.operator(function)?),
+                GpNode::Terminal(GpTerminal::Constant(constant)) => GeneticByte::GeneticValue(constant),
+                GpNode::Terminal(GpTerminal::Variable(_)) => return Err(GeneticError::ConversionError)
+            });
+        }
+        let genetic_string = GeneticString::from_vector(bytes);
+        let reparsed = GpTree::from_genetic_string(&genetic_string, self.primitives.clone())?;
+        if reparsed.nodes == self.nodes {
+            Ok(genetic_string)
+        } else {
+            Err(GeneticError::ConversionError)
+        }
+    }
+
+    pub fn get_nodes(&self) -> &[GpNode] {
+        &self.nodes
+    }
+
+    pub fn get_primitives(&self) -> &Arc<PrimitiveSet> {
+        &self.primitives
+    }
+
+    /// The depth of the tree, where a lone terminal has depth 0.
+    pub fn get_depth(&self) -> usize {
+        prefix_tree::subtree_depth(&self.nodes, 0, &GpNode::get_arity)
+    }
+
+    pub fn set_maximum_depth(&mut self, maximum_depth: usize) {
+        self.maximum_depth = maximum_depth;
+    }
+
+    pub fn set_mutation(&mut self, mutation: GpMutation) {
+        self.mutation = mutation;
+    }
+
+    /// Evaluates the expression.
+    ///
+    /// # Parameters
+    /// inputs - the values of the variables
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The value of the expression if successful.</li>
+    /// <li>InvalidParameterError if a variable has no input.</li>
+    /// <li>Any error a function returns, e.g. ComputationError if the arithmetic overflows or
+    /// divides by zero.</li>
+    /// </ul>
+    pub fn evaluate(&self, inputs: &[i32]) -> GeneticResult<i32> {
+        let mut stack: Vec<i32> = vec!();
+        for node in self.nodes.iter().rev() {
+            let value = match *node {
+                GpNode::Terminal(GpTerminal::Constant(constant)) => constant as i32,
+                GpNode::Terminal(GpTerminal::Variable(index)) =>
+                    *inputs.get(index).ok_or(GeneticError::InvalidParameterError)?,
+                GpNode::Function(function) => {
+                    let mut arguments = Vec::with_capacity(function.get_arity());
+                    for _ in 0..function.get_arity() {
+                        arguments.push(stack.pop().ok_or(GeneticError::ComputationError)?);
+                    }
+                    (self.definition(function).evaluator)(&arguments)?
+                }
+            };
+            stack.push(value);
+        }
+        stack.pop().ok_or(GeneticError::ComputationError)
+    }
+
+    /// Replaces a random subtree of self with a random subtree of the mate. The crossover is
+    /// retried a few times if the child would exceed the maximum depth, after which self is
+    /// returned unchanged.
+    pub fn subtree_crossover(&self, mate: &GpTree, rng: &mut StdRng) -> GpTree {
+        let child = prefix_tree::subtree_crossover(&self.nodes, &mate.nodes, self.maximum_depth, &GpNode::get_arity, || {
+            Some((self.crossover_point(rng), mate.crossover_point(rng)))
+        });
+        GpTree { nodes: child.unwrap_or_else(|| self.nodes.clone()), ..self.clone() }
+    }
+
+    pub fn subtree_mutation(&mut self, rng: &mut StdRng) {
+        let target = rng.gen_range(0, self.nodes.len());
+        let depth_left = self.maximum_depth.saturating_sub(prefix_tree::depth_of(&self.nodes, target, &GpNode::get_arity));
+        let mut replacement = vec!();
+        generate(&self.primitives, MUTATION_DEPTH.min(depth_left), false, &mut replacement, rng);
+        *self = self.replace_subtree(target, &replacement);
+    }
+
+    pub fn point_mutation(&mut self, rng: &mut StdRng) {
+        let target = rng.gen_range(0, self.nodes.len());
+        self.nodes[target] = match self.nodes[target] {
+            GpNode::Function(function) => {
+                let same_arity: Vec<usize> = (0..self.primitives.functions.len())
+                    .filter(|&index| self.primitives.functions[index].arity == function.get_arity())
+                    .collect();
+                GpNode::Function(self.primitives.function_at(same_arity[rng.gen_range(0, same_arity.len())]))
+            },
+            GpNode::Terminal(_) => GpNode::Terminal(self.primitives.random_terminal(rng))
+        };
+    }
+
+    pub fn hoist_mutation(&mut self, rng: &mut StdRng) {
+        let root = rng.gen_range(0, self.nodes.len());
+        let end = prefix_tree::subtree_end(&self.nodes, root, &GpNode::get_arity).unwrap_or(self.nodes.len());
+        self.nodes = self.nodes[root..end].to_vec();
+    }
+
+    pub fn shrink_mutation(&mut self, rng: &mut StdRng) {
+        let functions: Vec<usize> = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].get_arity() > 0)
+            .collect();
+        if functions.is_empty() {
+            return;
+        }
+        let target = functions[rng.gen_range(0, functions.len())];
+        let terminal = GpNode::Terminal(self.primitives.random_terminal(rng));
+        *self = self.replace_subtree(target, &[terminal]);
+    }
+
+    fn crossover_point(&self, rng: &mut StdRng) -> usize {
+        prefix_tree::crossover_point(&self.nodes, |_| true, &GpNode::get_arity, rng).unwrap_or(0)
+    }
+
+    fn replace_subtree(&self, root: usize, replacement: &[GpNode]) -> GpTree {
+        GpTree { nodes: prefix_tree::replace_subtree(&self.nodes, root, replacement, &GpNode::get_arity), ..self.clone() }
+    }
+
+    /// The nodes in the order they appear in the infix expression, or ConversionError if a
+    /// function doesn't have arity 2.
+    fn in_order(&self) -> GeneticResult<Vec<GpNode>> {
+        if self.nodes.iter().any(|node| node.get_arity() != 0 && node.get_arity() != 2) {
+            return Err(GeneticError::ConversionError);
+        }
+        let mut order = vec!();
+        in_order_from(&self.nodes, 0, &mut order);
+        Ok(order)
+    }
+
+    /// The genetic string operator a function is written as.
+    fn operator(&self, function: GpFunction) -> GeneticResult<u8> {
+        match self.definition(function).name.as_str() {
+            "+" => Ok(b'+'),
+            "-" => Ok(b'-'),
+            "*" => Ok(b'*'),
+            "/" => Ok(b'/'),
+            _ => Err(GeneticError::ConversionError)
+        }
+    }
+
+    /// Every tree is checked against its primitive set when it is built, so its functions
+    /// always have a definition.
+    fn definition(&self, function: GpFunction) -> &FunctionDefinition {
+        &self.primitives.functions[function.index]
+    }
+
+    fn write_subtree(&self, index: usize, f: &mut Formatter) -> Result<usize, fmt::Error> {
+        match self.nodes[index] {
+            GpNode::Terminal(GpTerminal::Constant(constant)) => {
+                write!(f, "{}", constant)?;
+                Ok(index + 1)
+            },
+            GpNode::Terminal(GpTerminal::Variable(variable)) => {
+                write!(f, "x{}", variable)?;
+                Ok(index + 1)
+            },
+            GpNode::Function(function) => {
+                let definition = self.definition(function);
+                if definition.is_infix() {
+                    write!(f, "(")?;
+                    let right = self.write_subtree(index + 1, f)?;
+                    write!(f, " {} ", definition.name)?;
+                    let end = self.write_subtree(right, f)?;
+                    write!(f, ")")?;
+                    return Ok(end);
+                }
+                write!(f, "{}(", definition.name)?;
+                let mut child = index + 1;
+                for argument in 0..definition.arity {
+                    if argument > 0 {
+                        write!(f, ", ")?;
+                    }
+                    child = self.write_subtree(child, f)?;
+                }
+                write!(f, ")")?;
+                Ok(child)
+            }
+        }
+    }
+}
+
+fn in_order_from(nodes: &[GpNode], root: usize, order: &mut Vec<GpNode>) -> usize {
+    match nodes[root] {
+        GpNode::Terminal(_) => {
+            order.push(nodes[root]);
+            root + 1
+        },
+        GpNode::Function(_) => {
+            let right = in_order_from(nodes, root + 1, order);
+            order.push(nodes[root]);
+            in_order_from(nodes, right, order)
+        }
+    }
+}
+
+/// Appends a random tree of at most the given depth to the nodes, in prefix order.
+fn generate(primitives: &PrimitiveSet, depth: usize, full: bool, nodes: &mut Vec<GpNode>, rng: &mut StdRng) {
+    let terminal_share = primitives.terminals.len() as f64
+        / (primitives.terminals.len() + primitives.functions.len()) as f64;
+    if depth == 0 || primitives.functions.is_empty() || (!full && rng.gen::<f64>() < terminal_share) {
+        nodes.push(GpNode::Terminal(primitives.random_terminal(rng)));
+        return;
+    }
+    let function = primitives.random_function(rng);
+    nodes.push(GpNode::Function(function));
+    for _ in 0..function.get_arity() {
+        generate(primitives, depth - 1, full, nodes, rng);
+    }
+}
+
+fn next_set_id() -> usize {
+    NEXT_SET_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Mirrors `GeneticString::calculate_value`: an empty slice is the constant 0, and otherwise
+/// the slice is split at its leftmost operator of the lowest precedence.
+fn parse_slice<T: GeneticCollectionComponent>(slice: &[T], primitives: &PrimitiveSet, nodes: &mut Vec<GpNode>)
+                                              -> GeneticResult<()> {
+    if slice.is_empty() {
+        nodes.push(GpNode::Terminal(GpTerminal::Constant(0)));
+        return Ok(());
+    }
+    if slice.len() == 1 {
+        nodes.push(GpNode::Terminal(GpTerminal::Constant(slice[0].get_value()?)));
+        return Ok(());
+    }
+    for precedence in 0..2 {
+        for index in (1..slice.len()).step_by(2) {
+            if slice[index].get_operator_precedence()? == precedence {
+                let operator = (slice[index].get_operator()? as char).to_string();
+                let function = primitives.get_function(&operator)
+                    .filter(|function| function.get_arity() == 2)
+                    .ok_or(GeneticError::ConversionError)?;
+                nodes.push(GpNode::Function(function));
+                parse_slice(&slice[..index], primitives, nodes)?;
+                return parse_slice(&slice[index + 1..], primitives, nodes);
+            }
+        }
+    }
+    Err(GeneticError::ComputationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::genetic_byte::GeneticByteTestUtilities;
+
+    fn build_genetic_string(values: &[u8]) -> GeneticString<GeneticByte> {
+        let bytes = values.iter().enumerate()
+            .map(|(index, &value)| GeneticByteTestUtilities::new(value, index).unwrap())
+            .collect();
+        GeneticString::from_vector(bytes)
+    }
+
+    #[test]
+    fn tree_parsed_from_a_genetic_string_evaluates_to_the_same_value() {
+        let genetic_string = build_genetic_string(&[5, b'+', 2, b'-', 4, b'*', 3]);
+        let tree = GpTree::from_genetic_string(&genetic_string, Arc::new(PrimitiveSet::arithmetic(0))).unwrap();
+        assert_eq!(genetic_string.evaluate_expression().unwrap(), tree.evaluate(&[]).unwrap());
+        assert_eq!(genetic_string, tree.to_genetic_string().unwrap());
+    }
+
+    #[test]
+    fn tree_whose_shape_breaks_precedence_does_not_convert() {
+        let primitives = Arc::new(PrimitiveSet::arithmetic(0));
+        // (5 + 2) * 3 can't be written without brackets.
+        let nodes = vec![
+            GpNode::Function(primitives.get_function("*").unwrap()),
+            GpNode::Function(primitives.get_function("+").unwrap()),
+            GpNode::Terminal(GpTerminal::Constant(5)),
+            GpNode::Terminal(GpTerminal::Constant(2)),
+            GpNode::Terminal(GpTerminal::Constant(3))
+        ];
+        let tree = GpTree::from_nodes(nodes, primitives).unwrap();
+        assert_eq!(21, tree.evaluate(&[]).unwrap());
+        assert_eq!("((5 + 2) * 3)", tree.to_string());
+        assert!(tree.to_genetic_string().is_err());
+    }
+
+    #[test]
+    fn user_functions_evaluate_and_foreign_nodes_are_rejected() {
+        let mut primitives = PrimitiveSet::new(vec![GpTerminal::Variable(0), GpTerminal::Constant(4)]).unwrap();
+        let maximum = primitives.add_function("max", 3, |a| Ok(a[0].max(a[1]).max(a[2]))).unwrap();
+        let remainder = primitives.add_function("%", 2, |a| a[0].checked_rem(a[1]).ok_or(GeneticError::ComputationError))
+            .unwrap();
+        assert!(primitives.add_function("zero", 0, |_| Ok(0)).is_err());
+        assert!(primitives.add_function("max", 2, |a| Ok(a[0].max(a[1]))).is_err());
+        let mut copy = primitives.clone();
+        let copied_function = copy.add_function("min", 3, |a| Ok(a[0].min(a[1]).min(a[2]))).unwrap();
+        let primitives = Arc::new(primitives);
+        let x = GpNode::Terminal(GpTerminal::Variable(0));
+        let four = GpNode::Terminal(GpTerminal::Constant(4));
+        let nodes = vec![GpNode::Function(maximum), x, GpNode::Function(remainder), x, four, four];
+        let tree = GpTree::from_nodes(nodes, primitives.clone()).unwrap();
+        assert_eq!("max(x0, (x0 % 4), 4)", tree.to_string());
+        assert_eq!(9, tree.evaluate(&[9]).unwrap());
+        assert_eq!(4, tree.evaluate(&[-7]).unwrap());
+        assert!(tree.to_genetic_string().is_err());
+
+        // Same index and arity as a function of the set, but created by another set.
+        let mut other = PrimitiveSet::new(vec![GpTerminal::Variable(0)]).unwrap();
+        let foreign_function = other.add_function("max", 3, |a| Ok(a[0].max(a[1]).max(a[2]))).unwrap();
+        assert_eq!((maximum.get_index(), maximum.get_arity()), (foreign_function.get_index(), foreign_function.get_arity()));
+        let foreign = vec![GpNode::Function(foreign_function), x, four, four];
+        assert!(GpTree::from_nodes(foreign, primitives.clone()).is_err());
+        assert!(!primitives.contains(&GpNode::Function(copied_function)));
+        assert!(!primitives.contains(&GpNode::Function(copy.get_function("max").unwrap())));
+        let unknown_terminal = vec![GpNode::Function(remainder), x, GpNode::Terminal(GpTerminal::Constant(5))];
+        assert!(GpTree::from_nodes(unknown_terminal, primitives).is_err());
+    }
+
+    #[test]
+    fn ramped_half_and_half_spreads_depths_and_keeps_trees_complete() {
+        let mut rng = StdRng::from_seed(&[16usize][..]);
+        let primitives = Arc::new(PrimitiveSet::arithmetic(1));
+        let trees = GpTree::ramped_half_and_half(12, 1, 3, primitives, &mut rng).unwrap();
+        for (index, tree) in trees.iter().enumerate() {
+            let depth = 1 + index % 3;
+            if (index / 3).is_multiple_of(2) {
+                assert_eq!(depth, tree.get_depth());
+            } else {
+                assert!(tree.get_depth() <= depth);
+            }
+        }
+        for tree in &trees {
+            assert!(GpTree::from_nodes(tree.get_nodes().to_vec(), tree.get_primitives().clone()).is_ok());
+        }
+    }
+
+    #[test]
+    fn crossover_and_mutation_respect_the_maximum_depth() {
+        let mut rng = StdRng::from_seed(&[17usize][..]);
+        let primitives = Arc::new(PrimitiveSet::arithmetic(2));
+        let mut first = GpTree::new_full(4, primitives.clone(), &mut rng);
+        let second = GpTree::new_full(4, primitives, &mut rng);
+        first.set_maximum_depth(5);
+        for &mutation in &[GpMutation::Subtree, GpMutation::Point, GpMutation::Hoist, GpMutation::Shrink] {
+            first.set_mutation(mutation);
+            for _ in 0..20 {
+                let mut child = first.breed(&second, &mut rng).unwrap();
+                child.mutate(&mut rng).unwrap();
+                assert!(child.get_depth() <= 5);
+                assert!(GpTree::from_nodes(child.get_nodes().to_vec(), child.get_primitives().clone()).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! Grammatical evolution.
+//!
+//! The genome is a vector of integer codons, much like the values of a
+//! `GeneticString<GeneticByte>`. It is turned into a program in any language described by a BNF
+//! grammar by expanding the leftmost non-terminal again and again, each time using the next
+//! codon, modulo the number of choices, to pick a production.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+const DEFAULT_MAXIMUM_WRAPS: usize = 2;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Symbol {
+    Terminal(String),
+    /// The index of a rule of the grammar.
+    NonTerminal(usize)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    name: String,
+    productions: Vec<Vec<Symbol>>
+}
+
+impl Rule {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_productions(&self) -> &[Vec<Symbol>] {
+        &self.productions
+    }
+}
+
+/// A context free grammar whose first rule is the start symbol.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grammar {
+    rules: Vec<Rule>,
+    /// For each rule, the production with the shallowest complete derivation.
+    shortest_productions: Vec<usize>
+}
+
+impl Grammar {
+    /// Parses a grammar written in BNF, one rule per line:
+    ///
+    /// ```text
+    /// <expr> ::= <expr> <op> <expr> | <var>
+    /// <op>   ::= + | - | "|"
+    ///          | *
+    /// <var>  ::= x | y
+    /// ```
+    ///
+    /// Lines starting with `|` add productions to the rule above them. Everything in a
+    /// production that isn't a `<non-terminal>` is copied into the program as written, inner
+    /// whitespace included; quotes hold text containing `|`, `<` or `>`.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The grammar if successful.</li>
+    /// <li>GrammarError if a line can't be parsed, a non-terminal has no rule, there are no
+    /// rules or a rule can never be expanded into terminals alone.</li>
+    /// </ul>
+    pub fn parse(text: &str) -> GeneticResult<Grammar> {
+        let mut names: Vec<String> = vec!();
+        let mut parsed: Vec<Vec<Vec<ParsedSymbol>>> = vec!();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (rule, alternatives) = if let Some(definition) = line.find("::=") {
+                let name = parse_non_terminal(line[..definition].trim())?;
+                let rule = match names.iter().position(|existing| *existing == name) {
+                    Some(rule) => rule,
+                    None => {
+                        names.push(name);
+                        parsed.push(vec!());
+                        names.len() - 1
+                    }
+                };
+                (rule, &line[definition + 3..])
+            } else if line.starts_with('|') && !names.is_empty() {
+                (names.len() - 1, &line[1..])
+            } else {
+                return Err(GeneticError::GrammarError);
+            };
+            parsed[rule].extend(parse_alternatives(alternatives)?);
+        }
+        if names.is_empty() {
+            return Err(GeneticError::GrammarError);
+        }
+        let mut rules = vec!();
+        for (name, productions) in names.iter().zip(parsed) {
+            let mut resolved = vec!();
+            for production in productions {
+                let mut symbols = vec!();
+                for symbol in production {
+                    symbols.push(match symbol {
+                        ParsedSymbol::Terminal(text) => Symbol::Terminal(text),
+                        ParsedSymbol::NonTerminal(name) => Symbol::NonTerminal(
+                            names.iter().position(|existing| *existing == name).ok_or(GeneticError::GrammarError)?)
+                    });
+                }
+                resolved.push(symbols);
+            }
+            rules.push(Rule { name: name.clone(), productions: resolved });
+        }
+        let shortest_productions = shortest_productions(&rules)?;
+        Ok(Grammar { rules, shortest_productions })
+    }
+
+    pub fn get_rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Maps codons to a program, wrapping around to the first codon when they run out.
+    /// Rules with a single production don't use up a codon.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>codons - the genotype</li>
+    /// <li>maximum_wraps - how many times the codons may be reused</li>
+    /// <li>repair - whether to finish the program with the shallowest productions once the
+    /// codons run out, instead of failing</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The mapping if successful.</li>
+    /// <li>MappingError if the codons ran out and the program couldn't be repaired.</li>
+    /// </ul>
+    pub fn map(&self, codons: &[u8], maximum_wraps: usize, repair: bool) -> GeneticResult<Mapping> {
+        let available = codons.len() * (maximum_wraps + 1);
+        let mut program = String::new();
+        let mut codons_used = 0usize;
+        let mut repaired = false;
+        let mut stack = vec![Symbol::NonTerminal(0)];
+        while let Some(symbol) = stack.pop() {
+            let index = match symbol {
+                Symbol::Terminal(text) => {
+                    program.push_str(&text);
+                    continue;
+                },
+                Symbol::NonTerminal(index) => index
+            };
+            let rule = &self.rules[index];
+            let choice = if rule.productions.len() == 1 {
+                0
+            } else if codons_used < available {
+                codons_used += 1;
+                codons[(codons_used - 1) % codons.len()] as usize % rule.productions.len()
+            } else if repair {
+                repaired = true;
+                self.shortest_productions[index]
+            } else {
+                return Err(GeneticError::MappingError);
+            };
+            stack.extend(rule.productions[choice].iter().rev().cloned());
+        }
+        let wraps = if codons.is_empty() { 0 } else { codons_used.saturating_sub(1) / codons.len() };
+        Ok(Mapping { program, codons_used, wraps, repaired })
+    }
+}
+
+/// The outcome of mapping a genotype through a grammar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mapping {
+    program: String,
+    codons_used: usize,
+    wraps: usize,
+    repaired: bool
+}
+
+impl Mapping {
+    pub fn get_program(&self) -> &str {
+        &self.program
+    }
+
+    /// How many codons were read, counting reread codons again after a wrap.
+    pub fn get_codons_used(&self) -> usize {
+        self.codons_used
+    }
+
+    pub fn get_wraps(&self) -> usize {
+        self.wraps
+    }
+
+    /// Whether the program had to be finished with the shallowest productions.
+    pub fn is_repaired(&self) -> bool {
+        self.repaired
+    }
+}
+
+/// What happens to an individual whose codons run out before its program is complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidIndividualPolicy {
+    /// Mapping fails with a MappingError, which the genetic algorithm scores with its invalid
+    /// fitness.
+    Fail,
+    /// The individual gets a fitness of zero, the worst fitness there is.
+    WorstFitness,
+    /// The program is finished by choosing the shallowest production of each remaining rule.
+    Repair
+}
+
+/// Computes the value of a program, which `get_fitness` compares to the target.
+pub type Interpreter = fn(&str) -> GeneticResult<f64>;
+
+#[derive(Clone, Debug)]
+pub struct GrammaticalGenome {
+    codons: Vec<u8>,
+    grammar: Arc<Grammar>,
+    maximum_wraps: usize,
+    invalid_policy: InvalidIndividualPolicy,
+    interpreter: Option<Interpreter>
+}
+
+impl GeneticCollection for GrammaticalGenome {
+    type Child = GrammaticalGenome;
+
+    /// Gets the fitness of the genome as the inverse distance between the interpreted value of
+    /// its program and the target, so a program that hits the target exactly scores infinity.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The fitness if successful.</li>
+    /// <li>MappingError if the genome is invalid under the Fail policy.</li>
+    /// <li>InvalidParameterError if no interpreter has been set.</li>
+    /// </ul>
+    fn get_fitness(&self, target: f64) -> GeneticResult<f64> {
+        let mapping = match self.map() {
+            Ok(mapping) => mapping,
+            Err(_) if self.invalid_policy == InvalidIndividualPolicy::WorstFitness => return Ok(0f64),
+            Err(error) => return Err(error)
+        };
+        let interpreter = self.interpreter.ok_or(GeneticError::InvalidParameterError)?;
+        Ok((1f64 / (interpreter(mapping.get_program())? - target)).abs())
+    }
+
+    /// Breeds a child by one point crossover, with the point chosen separately in each parent,
+    /// followed by mutation.
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.one_point_crossover(mate, rng);
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.codons.len()
+    }
+}
+
+impl Mutable for GrammaticalGenome {
+    /// Replaces each codon with a random one with a probability of one over the number of
+    /// codons.
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        let mutation_rate = 1f64 / self.codons.len() as f64;
+        for codon in &mut self.codons {
+            if rng.gen::<f64>() < mutation_rate {
+                *codon = rng.gen();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for GrammaticalGenome {
+    fn eq(&self, other: &GrammaticalGenome) -> bool {
+        self.codons == other.codons
+    }
+}
+
+impl Eq for GrammaticalGenome {}
+
+impl Hash for GrammaticalGenome {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.codons.hash(state);
+    }
+}
+
+impl Display for GrammaticalGenome {
+    /// Writes the program, or the codons if the genome can't be mapped.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.map() {
+            Ok(mapping) => write!(f, "{}", mapping.get_program()),
+            Err(_) => write!(f, "invalid {:?}", self.codons)
+        }
+    }
+}
+
+impl GrammaticalGenome {
+    /// Creates a genome of random codons. Genomes may wrap twice and fail to map when they
+    /// run out of codons until configured otherwise.
+    pub fn new(length: usize, grammar: Arc<Grammar>, rng: &mut StdRng) -> GrammaticalGenome {
+        GrammaticalGenome::from_codons((0..length).map(|_| rng.gen()).collect(), grammar)
+    }
+
+    pub fn from_codons(codons: Vec<u8>, grammar: Arc<Grammar>) -> GrammaticalGenome {
+        GrammaticalGenome {
+            codons,
+            grammar,
+            maximum_wraps: DEFAULT_MAXIMUM_WRAPS,
+            invalid_policy: InvalidIndividualPolicy::Fail,
+            interpreter: None
+        }
+    }
+
+    pub fn get_codons(&self) -> &[u8] {
+        &self.codons
+    }
+
+    pub fn get_grammar(&self) -> &Arc<Grammar> {
+        &self.grammar
+    }
+
+    pub fn set_maximum_wraps(&mut self, maximum_wraps: usize) {
+        self.maximum_wraps = maximum_wraps;
+    }
+
+    pub fn set_invalid_policy(&mut self, invalid_policy: InvalidIndividualPolicy) {
+        self.invalid_policy = invalid_policy;
+    }
+
+    pub fn set_interpreter(&mut self, interpreter: Interpreter) {
+        self.interpreter = Some(interpreter);
+    }
+
+    /// Maps the codons through the grammar.
+    pub fn map(&self) -> GeneticResult<Mapping> {
+        self.grammar.map(&self.codons, self.maximum_wraps,
+                         self.invalid_policy == InvalidIndividualPolicy::Repair)
+    }
+
+    /// Takes the codons before a random point of self and those after a random point of the
+    /// mate, so the child's length can differ from both parents.
+    pub fn one_point_crossover(&self, mate: &GrammaticalGenome, rng: &mut StdRng) -> GrammaticalGenome {
+        let own_point = rng.gen_range(0, self.codons.len() + 1);
+        let mate_point = rng.gen_range(0, mate.codons.len() + 1);
+        let mut child = self.clone();
+        child.codons.truncate(own_point);
+        child.codons.extend_from_slice(&mate.codons[mate_point..]);
+        if child.codons.is_empty() {
+            child.codons = self.codons.clone();
+        }
+        child
+    }
+}
+
+enum ParsedSymbol {
+    Terminal(String),
+    NonTerminal(String)
+}
+
+fn parse_non_terminal(text: &str) -> GeneticResult<String> {
+    if text.len() > 2 && text.starts_with('<') && text.ends_with('>') {
+        Ok(text[1..text.len() - 1].to_string())
+    } else {
+        Err(GeneticError::GrammarError)
+    }
+}
+
+/// Splits the right hand side of a rule into its productions.
+fn parse_alternatives(text: &str) -> GeneticResult<Vec<Vec<ParsedSymbol>>> {
+    let mut productions = vec!();
+    let mut symbols = vec!();
+    let mut literal = String::new();
+    let mut characters = text.trim().chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '|' => {
+                push_literal(&mut literal, &mut symbols);
+                productions.push(trim_production(symbols));
+                symbols = vec!();
+            },
+            '<' => {
+                push_literal(&mut literal, &mut symbols);
+                let name: String = characters.by_ref().take_while(|&next| next != '>').collect();
+                if name.is_empty() {
+                    return Err(GeneticError::GrammarError);
+                }
+                symbols.push(ParsedSymbol::NonTerminal(name));
+            },
+            '"' | '\'' => {
+                let mut closed = false;
+                for next in characters.by_ref() {
+                    if next == character {
+                        closed = true;
+                        break;
+                    }
+                    literal.push(next);
+                }
+                if !closed {
+                    return Err(GeneticError::GrammarError);
+                }
+            },
+            _ => literal.push(character)
+        }
+    }
+    push_literal(&mut literal, &mut symbols);
+    productions.push(trim_production(symbols));
+    Ok(productions)
+}
+
+fn push_literal(literal: &mut String, symbols: &mut Vec<ParsedSymbol>) {
+    if !literal.is_empty() {
+        symbols.push(ParsedSymbol::Terminal(literal.clone()));
+        literal.clear();
+    }
+}
+
+/// Drops the whitespace around the `|` separators, keeping whitespace between symbols.
+fn trim_production(mut symbols: Vec<ParsedSymbol>) -> Vec<ParsedSymbol> {
+    if let Some(&mut ParsedSymbol::Terminal(ref mut text)) = symbols.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some(&mut ParsedSymbol::Terminal(ref mut text)) = symbols.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    symbols.retain(|symbol| match *symbol {
+        ParsedSymbol::Terminal(ref text) => !text.is_empty(),
+        ParsedSymbol::NonTerminal(_) => true
+    });
+    symbols
+}
+
+/// Finds, for every rule, the production with the shallowest complete derivation.
+///
+/// # Returns
+/// <ul>
+/// <li>The index of that production for each rule if successful.</li>
+/// <li>GrammarError if some rule can never be expanded into terminals alone.</li>
+/// </ul>
+fn shortest_productions(rules: &[Rule]) -> GeneticResult<Vec<usize>> {
+    let mut depths: Vec<Option<usize>> = vec![None; rules.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (rule, definition) in rules.iter().enumerate() {
+            for production in &definition.productions {
+                let depth = production.iter().try_fold(1usize, |deepest, symbol| match *symbol {
+                    Symbol::Terminal(_) => Some(deepest),
+                    Symbol::NonTerminal(other) => depths[other].map(|depth| deepest.max(depth + 1))
+                });
+                if let Some(depth) = depth {
+                    if depths[rule].is_none_or(|current| depth < current) {
+                        depths[rule] = Some(depth);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    rules.iter().enumerate().map(|(rule, definition)| {
+        let target = depths[rule].ok_or(GeneticError::GrammarError)?;
+        definition.productions.iter().position(|production| {
+            production.iter().all(|symbol| match *symbol {
+                Symbol::Terminal(_) => true,
+                Symbol::NonTerminal(other) => depths[other].is_some_and(|depth| depth < target)
+            })
+        }).ok_or(GeneticError::GrammarError)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    const EXPRESSION_GRAMMAR: &str = "
+        <expr> ::= <expr> <op> <expr> | <var>
+        <op>   ::= + | -
+                 | \"*\"
+        <var>  ::= x | y
+    ";
+
+    fn grammar() -> Arc<Grammar> {
+        Arc::new(Grammar::parse(EXPRESSION_GRAMMAR).unwrap())
+    }
+
+    #[test]
+    fn grammar_parser_reads_rules_continuations_and_quotes() {
+        let grammar = grammar();
+        assert_eq!(3, grammar.get_rules().len());
+        assert_eq!("op", grammar.get_rules()[1].get_name());
+        assert_eq!(vec![Symbol::Terminal("*".to_string())], grammar.get_rules()[1].get_productions()[2]);
+        assert_eq!(vec![Symbol::NonTerminal(0), Symbol::Terminal(" ".to_string()), Symbol::NonTerminal(1),
+                        Symbol::Terminal(" ".to_string()), Symbol::NonTerminal(0)],
+                   grammar.get_rules()[0].get_productions()[0]);
+        assert!(Grammar::parse("<a> ::= <b>").is_err());
+        assert!(Grammar::parse("<a> ::= <a> x").is_err());
+        assert!(Grammar::parse("a ::= x").is_err());
+    }
+
+    #[test]
+    fn codons_map_through_the_grammar_and_wrap() {
+        let genome = GrammaticalGenome::from_codons(vec![0, 1, 0, 0, 1, 1], grammar());
+        let mapping = genome.map().unwrap();
+        assert_eq!("x + y", mapping.get_program());
+        assert_eq!(6, mapping.get_codons_used());
+        assert_eq!(0, mapping.get_wraps());
+        let wrapped = GrammaticalGenome::from_codons(vec![0, 1, 1], grammar()).map().unwrap();
+        assert_eq!("y + y", wrapped.get_program());
+        assert_eq!(1, wrapped.get_wraps());
+    }
+
+    #[test]
+    fn invalid_individuals_follow_the_policy() {
+        let mut genome = GrammaticalGenome::from_codons(vec![0, 1], grammar());
+        genome.set_interpreter(|program| Ok(program.len() as f64));
+        assert!(matches!(genome.get_fitness(0f64), Err(GeneticError::MappingError)));
+        genome.set_invalid_policy(InvalidIndividualPolicy::WorstFitness);
+        assert_eq!(0f64, genome.get_fitness(0f64).unwrap());
+        genome.set_invalid_policy(InvalidIndividualPolicy::Repair);
+        genome.set_maximum_wraps(0);
+        let mapping = genome.map().unwrap();
+        assert!(mapping.is_repaired());
+        assert_eq!("x + x", mapping.get_program());
+        assert_eq!(1f64 / 5f64, genome.get_fitness(0f64).unwrap());
+    }
+
+    #[test]
+    fn breeding_keeps_codons_from_the_parents() {
+        let mut rng = StdRng::from_seed(&[21usize][..]);
+        let first = GrammaticalGenome::new(10, grammar(), &mut rng);
+        let second = GrammaticalGenome::new(30, grammar(), &mut rng);
+        for _ in 0..20 {
+            let child = first.one_point_crossover(&second, &mut rng).get_codons().to_vec();
+            let from_parents = (0..first.get_size() + 1).any(|own| {
+                child.starts_with(&first.get_codons()[..own]) && second.get_codons().ends_with(&child[own..])
+            });
+            assert!(from_parents || child == first.get_codons());
+            first.breed(&second, &mut rng).unwrap();
+        }
+    }
+}
@@ -0,0 +1,28 @@
+/// A genome together with the fitness it was last evaluated to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Individual<G> {
+    genome: G,
+    fitness: f64
+}
+
+impl<G> Individual<G> {
+    pub fn new(genome: G, fitness: f64) -> Individual<G> {
+        Individual { genome, fitness }
+    }
+
+    pub fn get_genome(&self) -> &G {
+        &self.genome
+    }
+
+    pub fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    pub fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = fitness
+    }
+
+    pub fn into_genome(self) -> G {
+        self.genome
+    }
+}
@@ -0,0 +1,416 @@
+//! A vector of integers, each kept within its own inclusive range, for combinatorial parameter
+//! search. The values of a `GeneticString<GeneticByte>` are the special case where every range
+//! is 0 to 255, and the two convert through `From` and `TryFrom`.
+
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::genetic_byte::GeneticByte;
+use ::genetic_string::GeneticString;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// The way values change under mutation. Each mutation changes every value with a
+/// probability of one over the length of the vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntMutation {
+    /// Draws a new value uniformly from the range.
+    RandomReset,
+    /// Adds a uniformly drawn step between -k and k, other than zero, clamped to the range.
+    Creep { k: i64 },
+    /// Adds a step whose size follows a geometric distribution with the given mean, at least one,
+    /// and whose sign is random, clamped to the range. Small steps are common, large ones possible.
+    Geometric { mean_step: f64 }
+}
+
+/// The way two parent vectors are recombined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntCrossover {
+    /// Takes each value from either parent with equal probability.
+    Uniform,
+    /// Cuts both parents at k random points and takes the segments from each in turn.
+    KPoint { k: usize }
+}
+
+#[derive(Clone, Debug)]
+pub struct IntVector {
+    values: Vec<i64>,
+    ranges: Vec<(i64, i64)>,
+    mutation: IntMutation,
+    crossover: IntCrossover
+}
+
+impl GeneticCollection for IntVector {
+    type Child = IntVector;
+
+    /// Breeds a child by the crossover of self followed by mutation.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>The child vector if successful.</li>
+    ///     <li>LengthMismatchError if the parents have different lengths.</li>
+    /// </ul>
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = match self.crossover {
+            IntCrossover::Uniform => self.uniform_crossover(mate, rng)?,
+            IntCrossover::KPoint { k } => self.k_point_crossover(mate, k, rng)?
+        };
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl Mutable for IntVector {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        match self.mutation {
+            IntMutation::RandomReset => self.random_reset_mutation(rng),
+            IntMutation::Creep { k } => self.creep_mutation(k, rng),
+            IntMutation::Geometric { mean_step } => self.geometric_mutation(mean_step, rng)?
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for IntVector {
+    /// Vectors are equal when their values are the same.
+    fn eq(&self, other: &IntVector) -> bool {
+        self.values == other.values
+    }
+}
+
+impl Eq for IntVector {}
+
+impl Hash for IntVector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.values.hash(state);
+    }
+}
+
+impl Display for IntVector {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|value| value.to_string()).collect();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl<'a> From<&'a GeneticString<GeneticByte>> for IntVector {
+    /// Takes the values of the genetic string, skipping its operators, each with the range 0
+    /// to 255.
+    fn from(genetic_string: &'a GeneticString<GeneticByte>) -> IntVector {
+        let values: Vec<i64> = genetic_string.get_vector_immutable().iter()
+            .filter_map(|byte| match *byte {
+                GeneticByte::GeneticValue(value) => Some(i64::from(value)),
+                GeneticByte::GeneticOperator(_) => None
+            })
+            .collect();
+        let ranges = vec![(0, i64::from(u8::MAX)); values.len()];
+        IntVector::with_values(values, ranges)
+    }
+}
+
+impl<'a> TryFrom<&'a IntVector> for GeneticString<GeneticByte> {
+    type Error = GeneticError;
+
+    /// Writes the values of the vector to the value positions of a genetic string. The
+    /// operator positions between them hold `+`, so the string evaluates to the sum.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The genetic string if successful.</li>
+    /// <li>ConversionError if a value doesn't fit in a byte.</li>
+    /// </ul>
+    fn try_from(vector: &'a IntVector) -> GeneticResult<GeneticString<GeneticByte>> {
+        let mut bytes = vec!();
+        for (index, &value) in vector.values.iter().enumerate() {
+            if index > 0 {
+                bytes.push(GeneticByte::GeneticOperator(b'+'));
+            }
+            let value = u8::try_from(value).map_err(|_| GeneticError::ConversionError)?;
+            bytes.push(GeneticByte::GeneticValue(value));
+        }
+        Ok(GeneticString::from_vector(bytes))
+    }
+}
+
+impl IntVector {
+    /// Creates a vector whose values are drawn uniformly from their ranges. The vector uses
+    /// creep mutation with a step of at most 1 and uniform crossover until configured
+    /// otherwise.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>ranges - the inclusive lower and upper bound of each position</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The vector if successful.</li>
+    /// <li>InvalidParameterError if a lower bound is above its upper bound.</li>
+    /// </ul>
+    pub fn new(ranges: Vec<(i64, i64)>, rng: &mut StdRng) -> GeneticResult<IntVector> {
+        check_ranges(&ranges)?;
+        let values = ranges.iter().map(|&range| uniform(range, rng)).collect();
+        Ok(IntVector::with_values(values, ranges))
+    }
+
+    /// Creates a vector with the given values.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The vector if successful.</li>
+    /// <li>LengthMismatchError if there isn't one range per value.</li>
+    /// <li>InvalidParameterError if a range is empty or a value lies outside its range.</li>
+    /// </ul>
+    pub fn from_values(values: Vec<i64>, ranges: Vec<(i64, i64)>) -> GeneticResult<IntVector> {
+        if values.len() != ranges.len() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        check_ranges(&ranges)?;
+        if values.iter().zip(&ranges).any(|(value, &(lower, upper))| !(lower..=upper).contains(value)) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(IntVector::with_values(values, ranges))
+    }
+
+    fn with_values(values: Vec<i64>, ranges: Vec<(i64, i64)>) -> IntVector {
+        IntVector { values, ranges, mutation: IntMutation::Creep { k: 1 }, crossover: IntCrossover::Uniform }
+    }
+
+    pub fn get_values(&self) -> &[i64] {
+        &self.values
+    }
+
+    pub fn get_ranges(&self) -> &[(i64, i64)] {
+        &self.ranges
+    }
+
+    pub fn set_mutation(&mut self, mutation: IntMutation) {
+        self.mutation = mutation;
+    }
+
+    pub fn set_crossover(&mut self, crossover: IntCrossover) {
+        self.crossover = crossover;
+    }
+
+    pub fn random_reset_mutation(&mut self, rng: &mut StdRng) {
+        let mutation_rate = 1f64 / self.values.len().max(1) as f64;
+        for index in 0..self.values.len() {
+            if rng.gen::<f64>() < mutation_rate {
+                self.values[index] = uniform(self.ranges[index], rng);
+            }
+        }
+    }
+
+    pub fn creep_mutation(&mut self, k: i64, rng: &mut StdRng) {
+        if k < 1 {
+            return;
+        }
+        let mutation_rate = 1f64 / self.values.len().max(1) as f64;
+        for index in 0..self.values.len() {
+            let (lower, upper) = self.ranges[index];
+            // A step wider than the range only ever clamps, so the cap keeps the draw below
+            // from overflowing when k is the largest i64.
+            let largest_step = k.min(upper.saturating_sub(lower));
+            if rng.gen::<f64>() < mutation_rate && largest_step > 0 {
+                let step = 1 + rng.gen_range(0, largest_step);
+                let step = if rng.gen() { step } else { -step };
+                self.values[index] = self.clamp(index, self.values[index].saturating_add(step));
+            }
+        }
+    }
+
+    /// Adds a random signed step to each value with a probability of one over the length of the
+    /// vector. Step sizes are geometrically distributed on 1, 2, 3, ... with the given mean.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>Nothing if successful.</li>
+    ///     <li>InvalidParameterError if the mean step is below one, the smallest step.</li>
+    /// </ul>
+    pub fn geometric_mutation(&mut self, mean_step: f64, rng: &mut StdRng) -> GeneticResult<()> {
+        if mean_step.is_nan() || mean_step < 1f64 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mutation_rate = 1f64 / self.values.len().max(1) as f64;
+        // A geometric distribution on 1, 2, 3, ... has a mean of one over its success probability.
+        let success = 1f64 / mean_step;
+        let log_failure = (1f64 - success).ln();
+        for index in 0..self.values.len() {
+            if rng.gen::<f64>() < mutation_rate {
+                let uniform: f64 = rng.gen();
+                let step = 1f64 + ((1f64 - uniform).ln() / log_failure).floor();
+                let step = if step.is_finite() { step.min(i64::MAX as f64) as i64 } else { 1 };
+                let step = if rng.gen() { step } else { -step };
+                self.values[index] = self.clamp(index, self.values[index].saturating_add(step));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn uniform_crossover(&self, mate: &IntVector, rng: &mut StdRng) -> GeneticResult<IntVector> {
+        self.check_length(mate)?;
+        let mut child = self.clone();
+        for index in 0..self.values.len() {
+            if rng.gen() {
+                child.values[index] = child.clamp(index, mate.values[index]);
+            }
+        }
+        Ok(child)
+    }
+
+    /// Cuts the parents at k distinct random points, fewer if the vectors are too short, and
+    /// alternates between taking segments from self and from the mate.
+    pub fn k_point_crossover(&self, mate: &IntVector, k: usize, rng: &mut StdRng) -> GeneticResult<IntVector> {
+        self.check_length(mate)?;
+        let mut points: Vec<usize> = (1..self.values.len()).collect();
+        rng.shuffle(&mut points);
+        points.truncate(k);
+        points.sort();
+        let mut child = self.clone();
+        let mut from_mate = false;
+        let mut next_point = points.into_iter().peekable();
+        for index in 0..self.values.len() {
+            if next_point.peek() == Some(&index) {
+                next_point.next();
+                from_mate = !from_mate;
+            }
+            if from_mate {
+                child.values[index] = child.clamp(index, mate.values[index]);
+            }
+        }
+        Ok(child)
+    }
+
+    fn clamp(&self, index: usize, value: i64) -> i64 {
+        let (lower, upper) = self.ranges[index];
+        value.max(lower).min(upper)
+    }
+
+    fn check_length(&self, other: &IntVector) -> GeneticResult<()> {
+        if self.values.len() == other.values.len() {
+            Ok(())
+        } else {
+            Err(GeneticError::LengthMismatchError)
+        }
+    }
+}
+
+fn check_ranges(ranges: &[(i64, i64)]) -> GeneticResult<()> {
+    if ranges.iter().all(|&(lower, upper)| lower <= upper) {
+        Ok(())
+    } else {
+        Err(GeneticError::InvalidParameterError)
+    }
+}
+
+fn uniform((lower, upper): (i64, i64), rng: &mut StdRng) -> i64 {
+    if upper == i64::MAX {
+        // gen_range excludes its upper end, which can't be raised past the largest i64.
+        return if lower == upper || rng.gen::<f64>() < 1f64 / (upper as f64 - lower as f64 + 1f64) {
+            upper
+        } else {
+            rng.gen_range(lower, upper)
+        };
+    }
+    rng.gen_range(lower, upper + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn within_ranges(vector: &IntVector) -> bool {
+        vector.get_values().iter().zip(vector.get_ranges())
+            .all(|(value, &(lower, upper))| (lower..=upper).contains(value))
+    }
+
+    #[test]
+    fn every_mutation_keeps_values_within_their_own_ranges() {
+        let mut rng = StdRng::from_seed(&[29usize][..]);
+        let ranges = vec![(0, 0), (-3, 3), (0, 255), (10, 12)];
+        let mut vector = IntVector::new(ranges, &mut rng).unwrap();
+        for _ in 0..200 {
+            vector.random_reset_mutation(&mut rng);
+            vector.creep_mutation(5, &mut rng);
+            vector.geometric_mutation(4f64, &mut rng).unwrap();
+            assert!(within_ranges(&vector));
+        }
+        assert!(IntVector::from_values(vec![4], vec![(0, 3)]).is_err());
+        assert!(IntVector::from_values(vec![1], vec![(2, 1)]).is_err());
+    }
+
+    #[test]
+    fn geometric_steps_have_the_requested_mean() {
+        let mut rng = StdRng::from_seed(&[31usize][..]);
+        let mut vector = IntVector::from_values(vec![0], vec![(i64::MIN / 2, i64::MAX / 2)]).unwrap();
+        let samples = 20000;
+        let mut total = 0;
+        for _ in 0..samples {
+            let before = vector.get_values()[0];
+            vector.geometric_mutation(4f64, &mut rng).unwrap();
+            total += (vector.get_values()[0] - before).abs();
+        }
+        assert!((total as f64 / samples as f64 - 4f64).abs() < 0.15f64);
+        vector.geometric_mutation(1f64, &mut rng).unwrap();
+        assert!(vector.geometric_mutation(0.5f64, &mut rng).is_err());
+        assert!(vector.geometric_mutation(f64::NAN, &mut rng).is_err());
+    }
+
+    #[test]
+    fn creep_moves_a_value_by_at_most_k() {
+        let mut rng = StdRng::from_seed(&[30usize][..]);
+        let mut vector = IntVector::from_values(vec![50], vec![(0, 100)]).unwrap();
+        for _ in 0..100 {
+            let before = vector.get_values()[0];
+            vector.creep_mutation(3, &mut rng);
+            let step = (vector.get_values()[0] - before).abs();
+            assert!((1..=3).contains(&step));
+        }
+        let mut wide = IntVector::from_values(vec![0, 5], vec![(i64::MIN, i64::MAX), (5, 5)]).unwrap();
+        for _ in 0..20 {
+            wide.creep_mutation(i64::MAX, &mut rng);
+            assert!(within_ranges(&wide));
+        }
+    }
+
+    #[test]
+    fn genetic_string_values_convert_to_and_from_a_byte_ranged_vector() {
+        let genetic_string = GeneticString::from_vector(vec![GeneticByte::GeneticValue(7),
+            GeneticByte::GeneticOperator(b'*'), GeneticByte::GeneticValue(255)]);
+        let vector = IntVector::from(&genetic_string);
+        assert_eq!(&[7, 255], vector.get_values());
+        assert_eq!(&[(0, 255), (0, 255)], vector.get_ranges());
+        let round_trip = GeneticString::try_from(&vector).unwrap();
+        assert_eq!(262, round_trip.evaluate_expression().unwrap());
+        let too_large = IntVector::from_values(vec![256], vec![(0, 300)]).unwrap();
+        assert!(matches!(GeneticString::try_from(&too_large), Err(GeneticError::ConversionError)));
+    }
+
+    #[test]
+    fn k_point_crossover_switches_parents_k_times() {
+        let mut rng = StdRng::from_seed(&[31usize][..]);
+        let ranges = vec![(0, 1); 20];
+        let zeros = IntVector::from_values(vec![0; 20], ranges.clone()).unwrap();
+        let ones = IntVector::from_values(vec![1; 20], ranges).unwrap();
+        for k in 1..4 {
+            let child = zeros.k_point_crossover(&ones, k, &mut rng).unwrap();
+            let switches = child.get_values().windows(2).filter(|pair| pair[0] != pair[1]).count();
+            assert_eq!(k, switches);
+            assert_eq!(0, child.get_values()[0]);
+        }
+        let child = zeros.uniform_crossover(&ones, &mut rng).unwrap();
+        assert!(within_ranges(&child));
+        assert!(zeros.k_point_crossover(&IntVector::from_values(vec![0], vec![(0, 1)]).unwrap(), 1, &mut rng).is_err());
+    }
+}
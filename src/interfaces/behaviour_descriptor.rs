@@ -0,0 +1,18 @@
+use ::GeneticResult;
+
+/// Characterises what a genome does rather than how fit it is, as a point in a space of
+/// behaviours, e.g. the length of an expression and the mix of its operators.
+pub trait BehaviourDescriptor<G> {
+    /// Describes the behaviour of a genome.
+    ///
+    /// # Returns
+    /// One value per dimension of the behaviour space, or an error if the genome's behaviour
+    /// couldn't be determined.
+    fn describe(&self, genome: &G) -> GeneticResult<Vec<f64>>;
+}
+
+impl<G, F> BehaviourDescriptor<G> for F where F: Fn(&G) -> GeneticResult<Vec<f64>> {
+    fn describe(&self, genome: &G) -> GeneticResult<Vec<f64>> {
+        self(genome)
+    }
+}
@@ -0,0 +1,16 @@
+use ::GeneticResult;
+
+/// A measure of how different two genomes are. Identical genomes are 0 apart.
+pub trait DistanceMetric<G> {
+    /// Measures the distance between two genomes.
+    ///
+    /// # Returns
+    /// The distance, or an error if the genomes can't be compared with this metric.
+    fn distance(&self, a: &G, b: &G) -> GeneticResult<f64>;
+}
+
+impl<G, F> DistanceMetric<G> for F where F: Fn(&G, &G) -> GeneticResult<f64> {
+    fn distance(&self, a: &G, b: &G) -> GeneticResult<f64> {
+        self(a, b)
+    }
+}
@@ -0,0 +1,44 @@
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::GeneticResult;
+
+/// Anything that can score a genome. Higher values are fitter.
+pub trait FitnessFunction<G> {
+    /// Scores a genome.
+    ///
+    /// # Parameters
+    /// genome - the genome to be scored
+    ///
+    /// # Returns
+    /// The fitness of the genome, or an error if it couldn't be evaluated.
+    fn evaluate(&self, genome: &G) -> GeneticResult<f64>;
+}
+
+impl<G, F> FitnessFunction<G> for F where F: Fn(&G) -> GeneticResult<f64> {
+    fn evaluate(&self, genome: &G) -> GeneticResult<f64> {
+        self(genome)
+    }
+}
+
+/// Scores genetic collections by how close they come to a target value, using
+/// `GeneticCollection::get_fitness`. Genomes that don't implement it fail with
+/// NoTargetFitnessError and need a problem specific fitness function instead.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetFitness {
+    target: f64
+}
+
+impl TargetFitness {
+    pub fn new(target: f64) -> TargetFitness {
+        TargetFitness { target }
+    }
+
+    pub fn get_target(&self) -> f64 {
+        self.target
+    }
+}
+
+impl<G> FitnessFunction<G> for TargetFitness where G: GeneticCollection {
+    fn evaluate(&self, genome: &G) -> GeneticResult<f64> {
+        genome.get_fitness(self.target)
+    }
+}
@@ -2,14 +2,29 @@
 
 use ::mutable::Mutable;
 use ::GeneticResult;
+use ::error::GeneticError;
 use std::fmt::{Display, Debug};
 use rand::StdRng;
 
 pub trait GeneticCollection: Mutable + Debug + Display {
     type Child;
 
-    fn get_fitness(&self, f64) -> GeneticResult<f64>;
+    /// Gets the fitness of the collection by how close it comes to the target, as used by
+    /// `TargetFitness`. Only collections with a natural value to compare against the target
+    /// implement it; any other genome is scored with a `FitnessFunction` instead.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The fitness if the collection implements it.</li>
+    /// <li>NoTargetFitnessError otherwise.</li>
+    /// </ul>
+    fn get_fitness(&self, _target: f64) -> GeneticResult<f64> {
+        Err(GeneticError::NoTargetFitnessError)
+    }
 
-    fn breed(&self, &Self, &mut StdRng) -> GeneticResult<Self::Child>;
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child>;
+
+    /// Returns the number of components in the collection.
+    fn get_size(&self) -> usize;
 
 }
@@ -7,7 +7,7 @@ use rand::StdRng;
 
 pub trait GeneticCollectionComponent: Mutable + Clone + Debug + Display{
 
-    fn new(location: usize, &mut StdRng) -> GeneticResult<Self> where Self: Sized;
+    fn new(location: usize, rng: &mut StdRng) -> GeneticResult<Self> where Self: Sized;
 
     fn get_value(&self) -> GeneticResult<u8>;
 
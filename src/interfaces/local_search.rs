@@ -0,0 +1,24 @@
+use rand::StdRng;
+
+use ::individual::Individual;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::GeneticResult;
+
+/// Improves a single genome by exploring its neighbourhood, e.g. the genomes one mutation
+/// away from it. Combined with a genetic algorithm this makes a memetic algorithm.
+pub trait LocalSearch<G> {
+    /// Searches from the starting genome.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>start - the evaluated genome to start from</li>
+    /// <li>fitness_function - scores the genomes visited, higher is fitter</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// The fittest genome found, which is the starting genome if nothing better was found,
+    /// together with the number of fitness evaluations made.
+    fn search(&mut self, start: Individual<G>, fitness_function: &dyn FitnessFunction<G>, rng: &mut StdRng)
+              -> GeneticResult<(Individual<G>, usize)>;
+}
@@ -1,2 +1,11 @@
 pub mod genetic_collection;
 pub mod genetic_collection_component;
+pub mod fitness_function;
+pub mod observer;
+pub mod termination;
+pub mod distance_metric;
+pub mod selection_fitness;
+pub mod replacement;
+pub mod local_search;
+pub mod behaviour_descriptor;
+pub mod optimizer;
@@ -0,0 +1,14 @@
+use ::statistics::GenerationStats;
+
+/// Receives progress notifications from a run. Every hook does nothing by default, so an
+/// observer only implements the ones it cares about.
+pub trait Observer<G> {
+    /// Called once every generation has been evaluated.
+    fn on_generation(&mut self, _stats: &GenerationStats) {}
+
+    /// Called whenever a genome fitter than every genome seen before is found.
+    fn on_new_best(&mut self, _best: &G, _fitness: f64, _stats: &GenerationStats) {}
+
+    /// Called when the run ends, with the best genome found and the final statistics.
+    fn on_finish(&mut self, _best: &G, _fitness: f64, _stats: &GenerationStats) {}
+}
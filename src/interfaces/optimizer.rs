@@ -0,0 +1,155 @@
+use rand::StdRng;
+
+use ::individual::Individual;
+use ::interfaces::observer::Observer;
+use ::interfaces::termination::Termination;
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+/// What every optimizer keeps track of besides its search: the fittest genome found so far,
+/// the observers of the run and the fitness given to genomes whose evaluation fails.
+pub struct Progress<G> {
+    best: Option<Individual<G>>,
+    observers: Vec<Box<dyn Observer<G> + Send>>,
+    invalid_fitness: f64
+}
+
+impl<G> Progress<G> where G: Clone {
+    /// Creates the progress of a run that hasn't evaluated anything yet.
+    ///
+    /// # Parameters
+    /// invalid_fitness - the fitness given to genomes whose evaluation fails
+    pub fn new(invalid_fitness: f64) -> Progress<G> {
+        Progress { best: None, observers: vec![], invalid_fitness }
+    }
+
+    pub fn get_best(&self) -> Option<&Individual<G>> {
+        self.best.as_ref()
+    }
+
+    pub fn get_invalid_fitness(&self) -> f64 {
+        self.invalid_fitness
+    }
+
+    pub fn set_invalid_fitness(&mut self, invalid_fitness: f64) {
+        self.invalid_fitness = invalid_fitness;
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer<G> + Send>) {
+        self.observers.push(observer);
+    }
+
+    /// Keeps the fittest of the candidates, the first one on a tie, if it is fitter than the
+    /// best genome so far and reports it to the observers.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>candidates - the genomes of a generation together with their fitness</li>
+    /// <li>stats - the statistics of that generation</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// true if the best genome changed.
+    pub fn update_best<'a, I>(&mut self, candidates: I, stats: &GenerationStats) -> bool
+        where I: IntoIterator<Item = (&'a G, f64)>, G: 'a {
+        let fittest = candidates.into_iter()
+            .fold(None, |fittest: Option<(&G, f64)>, candidate| match fittest {
+                Some(fittest) if fittest.1 >= candidate.1 => Some(fittest),
+                _ => Some(candidate)
+            });
+        let (genome, fitness) = match fittest {
+            Some(fittest) => fittest,
+            None => return false
+        };
+        let improved = match self.best {
+            Some(ref best) => fitness > best.get_fitness(),
+            None => true
+        };
+        if improved {
+            for observer in &mut self.observers {
+                observer.on_new_best(genome, fitness, stats);
+            }
+            self.best = Some(Individual::new(genome.clone(), fitness));
+        }
+        improved
+    }
+
+    /// Tells the observers that a generation has been evaluated.
+    pub fn notify_generation(&mut self, stats: &GenerationStats) {
+        for observer in &mut self.observers {
+            observer.on_generation(stats);
+        }
+    }
+
+    /// Tells the observers that the run is over, if anything has been evaluated.
+    pub fn finish(&mut self, stats: &GenerationStats) {
+        if let Some(ref best) = self.best {
+            for observer in &mut self.observers {
+                observer.on_finish(best.get_genome(), best.get_fitness(), stats);
+            }
+        }
+    }
+}
+
+/// An optimizer that improves genomes one generation at a time, such as a genetic algorithm
+/// or an evolution strategy. Implementors produce the generations and keep a `Progress`,
+/// through which the provided methods track the best genome and notify the observers.
+pub trait Optimizer<G> where G: Clone {
+    /// Evaluates the starting point of the run if that hasn't happened yet and summarises the
+    /// current generation.
+    ///
+    /// # Parameters
+    /// rng - the random number generator, for optimizers whose starting point is random
+    ///
+    /// # Returns
+    /// The statistics of the current generation, or the error raised while starting the run.
+    fn get_statistics(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats>;
+
+    /// Produces and evaluates the next generation, reporting it to the observers.
+    ///
+    /// # Parameters
+    /// rng - the random number generator driving the search
+    ///
+    /// # Returns
+    /// The statistics of the new generation, or the first error raised while producing it.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats>;
+
+    fn get_progress(&self) -> &Progress<G>;
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<G>;
+
+    /// The fittest genome found so far, if anything has been evaluated.
+    fn get_best(&self) -> Option<&Individual<G>> {
+        self.get_progress().get_best()
+    }
+
+    /// Sets the fitness given to genomes whose evaluation fails.
+    fn set_invalid_fitness(&mut self, invalid_fitness: f64) {
+        self.get_progress_mutable().set_invalid_fitness(invalid_fitness);
+    }
+
+    fn add_observer(&mut self, observer: Box<dyn Observer<G> + Send>) {
+        self.get_progress_mutable().add_observer(observer);
+    }
+
+    /// Produces generations until the termination criterion is met, then notifies the
+    /// observers that the run is over. The criterion is also checked against the starting
+    /// point.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>termination - decides when to stop</li>
+    /// <li>rng - the random number generator driving the search</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// The statistics of the last generation.
+    fn run(&mut self, termination: &mut dyn Termination, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        let mut stats = self.get_statistics(rng)?;
+        while !termination.should_terminate(&stats) {
+            stats = self.next_generation(rng)?;
+        }
+        self.get_progress_mutable().finish(&stats);
+        Ok(stats)
+    }
+}
@@ -0,0 +1,28 @@
+use rand::StdRng;
+
+use ::individual::Individual;
+use ::GeneticResult;
+
+/// Decides how many offspring are bred each step and which individuals survive into the
+/// next population.
+pub trait Replacement<G> {
+    /// The number of offspring to breed from a population of the given size.
+    fn offspring_count(&self, population_size: usize) -> usize {
+        population_size
+    }
+
+    /// Builds the next population.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>population - the current, evaluated population</li>
+    /// <li>offspring - the evaluated offspring</li>
+    /// <li>parents - the indices in the population of the two parents of each offspring</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// The next population.
+    fn replace(&mut self, population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               parents: &[(usize, usize)], rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>>;
+}
@@ -0,0 +1,16 @@
+use ::individual::Individual;
+use ::GeneticResult;
+
+/// Turns the fitness of a population into the scores parents are selected by, e.g. to
+/// share fitness between similar genomes. The fitness reported in the statistics is left
+/// untouched.
+pub trait SelectionFitness<G> {
+    /// Scores the population for selection.
+    ///
+    /// # Parameters
+    /// population - the evaluated population
+    ///
+    /// # Returns
+    /// One selection score per member of the population, in the same order.
+    fn selection_fitness(&mut self, population: &[Individual<G>]) -> GeneticResult<Vec<f64>>;
+}
@@ -0,0 +1,13 @@
+use ::statistics::GenerationStats;
+
+/// Decides when a run should stop.
+pub trait Termination {
+    /// Called with the statistics of every generation, starting with the initial population.
+    ///
+    /// # Parameters
+    /// stats - the statistics of the generation that was just evaluated
+    ///
+    /// # Returns
+    /// true if the run should stop after this generation.
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool;
+}
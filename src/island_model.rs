@@ -0,0 +1,341 @@
+//! Several independently evolving subpopulations (islands) that exchange individuals.
+//!
+//! Every island has its own random number generator, seeded from the seed of the model, and
+//! migration draws from a generator of its own. A run is therefore reproducible from its seed
+//! whether or not the islands evolve on separate threads.
+
+use std::hash::Hash;
+use std::thread;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use ::error::GeneticError;
+use ::genetic_algorithm::GeneticAlgorithm;
+use ::individual::Individual;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::interfaces::optimizer::Optimizer;
+use ::interfaces::termination::Termination;
+use ::replacement::sort_by_fitness;
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+const DEFAULT_MIGRATION_INTERVAL: usize = 10;
+const DEFAULT_MIGRATION_RATE: f64 = 0.1;
+
+/// Which islands send migrants to which.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Topology {
+    /// Every island sends migrants to the next one, and the last to the first.
+    Ring,
+    /// Every island sends migrants to every other island.
+    FullyConnected,
+    /// Every island sends migrants to one other island chosen at random at each migration.
+    Random,
+    /// Island `i` sends migrants to every island listed at index `i`.
+    Custom(Vec<Vec<usize>>)
+}
+
+/// Which individuals of an island emigrate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantSelection {
+    Best,
+    Random
+}
+
+/// Which individuals of an island are replaced by immigrants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantReplacement {
+    Worst,
+    Random
+}
+
+/// A set of genetic algorithms that evolve side by side and periodically exchange migrants.
+pub struct IslandModel<G, F> {
+    islands: Vec<GeneticAlgorithm<G, F>>,
+    island_rngs: Vec<StdRng>,
+    migration_rng: StdRng,
+    migration_interval: usize,
+    migration_rate: f64,
+    topology: Topology,
+    migrant_selection: MigrantSelection,
+    migrant_replacement: MigrantReplacement,
+    parallel: bool
+}
+
+impl<G, F> IslandModel<G, F>
+    where G: GeneticCollection<Child = G> + Clone + Hash + Eq + Send, F: FitnessFunction<G> + Send {
+    /// Creates an island model. By default the islands form a ring, the best 10% of each
+    /// island migrate every 10 generations replacing the worst of their new island, and the
+    /// islands evolve on separate threads.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>islands - the genetic algorithms evolving each island</li>
+    /// <li>seed - the seed every random choice of the run is derived from</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The island model if successful.</li>
+    /// <li>CollectionEmptyError if there are no islands.</li>
+    /// </ul>
+    pub fn new(islands: Vec<GeneticAlgorithm<G, F>>, seed: usize) -> GeneticResult<IslandModel<G, F>> {
+        if islands.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        let island_rngs = (0..islands.len())
+            .map(|index| StdRng::from_seed(&[seed, index + 1][..]))
+            .collect();
+        Ok(IslandModel {
+            islands,
+            island_rngs,
+            migration_rng: StdRng::from_seed(&[seed, 0][..]),
+            migration_interval: DEFAULT_MIGRATION_INTERVAL,
+            migration_rate: DEFAULT_MIGRATION_RATE,
+            topology: Topology::Ring,
+            migrant_selection: MigrantSelection::Best,
+            migrant_replacement: MigrantReplacement::Worst,
+            parallel: true
+        })
+    }
+
+    /// Sets how many generations pass between migrations. 0 disables migration.
+    pub fn set_migration_interval(&mut self, migration_interval: usize) {
+        self.migration_interval = migration_interval;
+    }
+
+    /// Sets the fraction of each island that emigrates at every migration.
+    ///
+    /// # Returns
+    /// InvalidParameterError if the rate isn't between 0 and 1.
+    pub fn set_migration_rate(&mut self, migration_rate: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&migration_rate) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.migration_rate = migration_rate;
+        Ok(())
+    }
+
+    /// Sets which islands send migrants to which.
+    ///
+    /// # Returns
+    /// InvalidParameterError if a custom topology doesn't list the destinations of every
+    /// island or names an island that doesn't exist.
+    pub fn set_topology(&mut self, topology: Topology) -> GeneticResult<()> {
+        if let Topology::Custom(ref destinations) = topology {
+            let island_count = self.islands.len();
+            if destinations.len() != island_count
+                || destinations.iter().flatten().any(|&destination| destination >= island_count) {
+                return Err(GeneticError::InvalidParameterError);
+            }
+        }
+        self.topology = topology;
+        Ok(())
+    }
+
+    pub fn set_migrant_selection(&mut self, migrant_selection: MigrantSelection) {
+        self.migrant_selection = migrant_selection;
+    }
+
+    pub fn set_migrant_replacement(&mut self, migrant_replacement: MigrantReplacement) {
+        self.migrant_replacement = migrant_replacement;
+    }
+
+    /// Sets whether the islands evolve on separate threads.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    pub fn get_islands(&self) -> &[GeneticAlgorithm<G, F>] {
+        &self.islands
+    }
+
+    /// The fittest genome found so far on any island.
+    pub fn get_best(&self) -> Option<&Individual<G>> {
+        self.islands.iter()
+            .filter_map(|island| island.get_best())
+            .fold(None, |best: Option<&Individual<G>>, candidate| match best {
+                Some(best) if best.get_fitness() >= candidate.get_fitness() => Some(best),
+                _ => Some(candidate)
+            })
+    }
+
+    /// Summarises every island as one population.
+    pub fn get_statistics(&mut self) -> GeneticResult<GenerationStats> {
+        self.for_each_island(|island, rng| island.get_statistics(rng).map(|_| ()))?;
+        Ok(self.summarise())
+    }
+
+    /// Evolves every island by one generation, then exchanges migrants if a migration is due.
+    ///
+    /// # Returns
+    /// The statistics of all islands taken together.
+    pub fn next_generation(&mut self) -> GeneticResult<GenerationStats> {
+        self.for_each_island(|island, rng| island.next_generation(rng).map(|_| ()))?;
+        let generation = self.islands[0].get_generation();
+        if self.migration_interval > 0 && generation.is_multiple_of(self.migration_interval) {
+            self.migrate();
+        }
+        Ok(self.summarise())
+    }
+
+    /// Evolves the islands until the termination criterion, checked against the statistics
+    /// of all islands taken together, is met.
+    ///
+    /// # Returns
+    /// The statistics of the last generation.
+    pub fn run(&mut self, termination: &mut dyn Termination) -> GeneticResult<GenerationStats> {
+        let mut stats = self.get_statistics()?;
+        while !termination.should_terminate(&stats) {
+            stats = self.next_generation()?;
+        }
+        Ok(stats)
+    }
+
+    fn for_each_island<A>(&mut self, action: A) -> GeneticResult<()>
+        where A: Fn(&mut GeneticAlgorithm<G, F>, &mut StdRng) -> GeneticResult<()> + Sync {
+        let islands = self.islands.iter_mut().zip(self.island_rngs.iter_mut());
+        if !self.parallel {
+            for (island, rng) in islands {
+                action(island, rng)?;
+            }
+            return Ok(());
+        }
+        let action = &action;
+        thread::scope(|scope| {
+            let handles: Vec<_> = islands
+                .map(|(island, rng)| scope.spawn(move || action(island, rng)))
+                .collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|panic| ::std::panic::resume_unwind(panic)))
+                .collect::<GeneticResult<Vec<()>>>()
+        })?;
+        Ok(())
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        let population: Vec<Individual<G>> = self.islands.iter()
+            .flat_map(|island| island.get_population().iter().cloned())
+            .collect();
+        let evaluations = self.islands.iter().map(|island| island.get_evaluations()).sum();
+        GenerationStats::from_population(self.islands[0].get_generation(), &population, evaluations)
+    }
+
+    fn migrate(&mut self) {
+        let island_count = self.islands.len();
+        let mut arrivals: Vec<Vec<Individual<G>>> = vec![vec!(); island_count];
+        for source in 0..island_count {
+            let migrants = self.select_migrants(source);
+            for destination in self.destinations(source) {
+                arrivals[destination].extend(migrants.iter().cloned());
+            }
+        }
+        for (destination, immigrants) in arrivals.into_iter().enumerate() {
+            self.settle(destination, immigrants);
+        }
+    }
+
+    fn select_migrants(&mut self, source: usize) -> Vec<Individual<G>> {
+        let population = self.islands[source].get_population();
+        let count = (self.migration_rate * population.len() as f64).round() as usize;
+        match self.migrant_selection {
+            MigrantSelection::Best => {
+                let mut ranked = population.to_vec();
+                sort_by_fitness(&mut ranked);
+                ranked.truncate(count);
+                ranked
+            },
+            MigrantSelection::Random => {
+                let rng = &mut self.migration_rng;
+                (0..count).map(|_| population[rng.gen_range(0, population.len())].clone()).collect()
+            }
+        }
+    }
+
+    fn destinations(&mut self, source: usize) -> Vec<usize> {
+        let island_count = self.islands.len();
+        if island_count == 1 {
+            return vec!();
+        }
+        match self.topology {
+            Topology::Ring => vec![(source + 1) % island_count],
+            Topology::FullyConnected => (0..island_count).filter(|&island| island != source).collect(),
+            Topology::Random => {
+                let offset = self.migration_rng.gen_range(1, island_count);
+                vec![(source + offset) % island_count]
+            },
+            Topology::Custom(ref destinations) => destinations[source].clone()
+        }
+    }
+
+    fn settle(&mut self, destination: usize, immigrants: Vec<Individual<G>>) {
+        let population = self.islands[destination].get_population_mutable();
+        let mut slots: Vec<usize> = (0..population.len()).collect();
+        match self.migrant_replacement {
+            MigrantReplacement::Worst => slots.sort_by(|&a, &b| population[a].get_fitness()
+                .partial_cmp(&population[b].get_fitness())
+                .unwrap_or(::std::cmp::Ordering::Equal)),
+            MigrantReplacement::Random => self.migration_rng.shuffle(&mut slots)
+        }
+        for (slot, immigrant) in slots.into_iter().zip(immigrants) {
+            population[slot] = immigrant;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::genetic_byte::GeneticByte;
+    use ::genetic_string::GeneticString;
+    use ::interfaces::fitness_function::TargetFitness;
+    use ::termination::MaxGenerations;
+
+    fn build_island_model(seed: usize) -> IslandModel<GeneticString<GeneticByte>, TargetFitness> {
+        let mut rng = StdRng::from_seed(&[seed][..]);
+        let islands = (0..3).map(|_| {
+            let population = (0..10).map(|_| GeneticString::new(5, &mut rng).unwrap()).collect();
+            GeneticAlgorithm::new(population, TargetFitness::new(300.5f64)).unwrap()
+        }).collect();
+        let mut island_model = IslandModel::new(islands, seed).unwrap();
+        island_model.set_migration_interval(2);
+        island_model.set_migration_rate(0.2).unwrap();
+        island_model
+    }
+
+    fn genomes(island_model: &IslandModel<GeneticString<GeneticByte>, TargetFitness>) -> Vec<GeneticString<GeneticByte>> {
+        island_model.get_islands().iter()
+            .flat_map(|island| island.get_population().iter().map(|individual| individual.get_genome().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_run_with_or_without_threads() {
+        let mut threaded = build_island_model(42);
+        let mut sequential = build_island_model(42);
+        sequential.set_parallel(false);
+        let threaded_stats = threaded.run(&mut MaxGenerations::new(6)).unwrap();
+        let sequential_stats = sequential.run(&mut MaxGenerations::new(6)).unwrap();
+        assert_eq!(threaded_stats, sequential_stats);
+        assert_eq!(genomes(&threaded), genomes(&sequential));
+    }
+
+    #[test]
+    fn ring_migration_sends_the_best_to_the_next_island() {
+        let mut island_model = build_island_model(9);
+        island_model.set_parallel(false);
+        island_model.get_statistics().unwrap();
+        island_model.set_migration_rate(0.1).unwrap();
+        let best_of_first = island_model.select_migrants(0).remove(0);
+        island_model.migrate();
+        assert!(island_model.get_islands()[1].get_population().contains(&best_of_first));
+    }
+
+    #[test]
+    fn custom_topology_must_name_existing_islands() {
+        let mut island_model = build_island_model(1);
+        assert!(island_model.set_topology(Topology::Custom(vec![vec![1], vec![2], vec![3]])).is_err());
+        assert!(island_model.set_topology(Topology::Custom(vec![vec![1, 2], vec![], vec![0]])).is_ok());
+    }
+}
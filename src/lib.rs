@@ -1,5 +1,5 @@
-/// This library will hold a series of highly generic trait definitions and utility methods
-/// implemented on those traits to allow the rapid development of genetic utilities and libraries.
+//! This library will hold a series of highly generic trait definitions and utility methods
+//! implemented on those traits to allow the rapid development of genetic utilities and libraries.
 
 extern crate rand;
 extern crate probability;
@@ -9,6 +9,34 @@ pub mod mutable;
 pub mod error;
 pub mod genetic_string;
 pub mod genetic_byte;
+pub mod fitness_cache;
+pub mod individual;
+pub mod statistics;
+pub mod genetic_algorithm;
+pub mod termination;
+pub mod diversity;
+pub mod replacement;
+pub mod niching;
+pub mod island_model;
+pub mod bit_string;
+pub mod real_vector;
+pub mod permutation;
+pub mod prefix_tree;
+pub mod gp_tree;
+pub mod typed_gp;
+pub mod linear_gp;
+pub mod grammatical_evolution;
+pub mod cgp;
+pub mod neat;
+pub mod int_vector;
+pub mod differential_evolution;
+pub mod cma_es;
+pub mod estimation_of_distribution;
+pub mod local_search;
+pub mod single_solution;
+pub mod particle_swarm;
+pub mod map_elites;
+pub mod novelty_search;
 
 // Use statements
 use error::GeneticError;
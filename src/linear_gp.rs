@@ -0,0 +1,502 @@
+//! Linear genetic programming.
+//!
+//! A program is a flat sequence of register instructions such as `r1 = r2 * x0`, run from top to
+//! bottom by a small register machine. Like a `GeneticString`, the genome is a vector, but every
+//! instruction stands on its own, so crossover and mutation work on whole instructions without
+//! breaking the structure of the program.
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+const DEFAULT_MINIMUM_LENGTH: usize = 1;
+const DEFAULT_MAXIMUM_LENGTH: usize = 200;
+const DEFAULT_MACRO_MUTATION_PROBABILITY: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LinearOperation {
+    Add,
+    Subtract,
+    Multiply,
+    /// Protected division, which gives 1 when dividing by zero.
+    Divide
+}
+
+impl LinearOperation {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        match *self {
+            LinearOperation::Add => a + b,
+            LinearOperation::Subtract => a - b,
+            LinearOperation::Multiply => a * b,
+            LinearOperation::Divide => if b == 0f64 { 1f64 } else { a / b }
+        }
+    }
+
+    fn get_symbol(&self) -> char {
+        match *self {
+            LinearOperation::Add => '+',
+            LinearOperation::Subtract => '-',
+            LinearOperation::Multiply => '*',
+            LinearOperation::Divide => '/'
+        }
+    }
+}
+
+/// Where an instruction reads a value from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Register(usize),
+    Input(usize),
+    /// An index into the constants of the register machine.
+    Constant(usize)
+}
+
+/// An instruction of the form `destination = first operation second`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    destination: usize,
+    operation: LinearOperation,
+    first: Operand,
+    second: Operand
+}
+
+impl Instruction {
+    pub fn new(destination: usize, operation: LinearOperation, first: Operand, second: Operand) -> Instruction {
+        Instruction { destination, operation, first, second }
+    }
+
+    pub fn get_destination(&self) -> usize {
+        self.destination
+    }
+
+    pub fn get_operation(&self) -> LinearOperation {
+        self.operation
+    }
+
+    pub fn get_operands(&self) -> (Operand, Operand) {
+        (self.first, self.second)
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "r{} = {} {} {}", self.destination, OperandDisplay(self.first),
+               self.operation.get_symbol(), OperandDisplay(self.second))
+    }
+}
+
+struct OperandDisplay(Operand);
+
+impl Display for OperandDisplay {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            Operand::Register(index) => write!(f, "r{}", index),
+            Operand::Input(index) => write!(f, "x{}", index),
+            Operand::Constant(index) => write!(f, "c{}", index)
+        }
+    }
+}
+
+/// The interpreter programs run on: how many registers and inputs there are, which constants
+/// and operations instructions may use, and which register holds the result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisterMachine {
+    registers: usize,
+    inputs: usize,
+    constants: Vec<f64>,
+    operations: Vec<LinearOperation>,
+    output_register: usize
+}
+
+impl RegisterMachine {
+    /// Creates a register machine whose result is read from register 0.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The register machine if successful.</li>
+    /// <li>InvalidParameterError if there are no registers or no operations.</li>
+    /// </ul>
+    pub fn new(registers: usize, inputs: usize, constants: Vec<f64>,
+               operations: Vec<LinearOperation>) -> GeneticResult<RegisterMachine> {
+        if registers == 0 || operations.is_empty() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(RegisterMachine { registers, inputs, constants, operations, output_register: 0 })
+    }
+
+    pub fn get_registers(&self) -> usize {
+        self.registers
+    }
+
+    pub fn get_inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn get_constants(&self) -> &[f64] {
+        &self.constants
+    }
+
+    pub fn get_operations(&self) -> &[LinearOperation] {
+        &self.operations
+    }
+
+    pub fn get_output_register(&self) -> usize {
+        self.output_register
+    }
+
+    pub fn set_output_register(&mut self, output_register: usize) -> GeneticResult<()> {
+        if output_register >= self.registers {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.output_register = output_register;
+        Ok(())
+    }
+
+    pub fn random_instruction(&self, rng: &mut StdRng) -> Instruction {
+        Instruction {
+            destination: rng.gen_range(0, self.registers),
+            operation: self.operations[rng.gen_range(0, self.operations.len())],
+            first: self.random_operand(rng),
+            second: self.random_operand(rng)
+        }
+    }
+
+    fn random_operand(&self, rng: &mut StdRng) -> Operand {
+        let index = rng.gen_range(0, self.registers + self.inputs + self.constants.len());
+        if index < self.registers {
+            Operand::Register(index)
+        } else if index < self.registers + self.inputs {
+            Operand::Input(index - self.registers)
+        } else {
+            Operand::Constant(index - self.registers - self.inputs)
+        }
+    }
+
+    fn is_valid(&self, instruction: &Instruction) -> bool {
+        let valid_operand = |operand| match operand {
+            Operand::Register(index) => index < self.registers,
+            Operand::Input(index) => index < self.inputs,
+            Operand::Constant(index) => index < self.constants.len()
+        };
+        instruction.destination < self.registers
+            && self.operations.contains(&instruction.operation)
+            && valid_operand(instruction.first)
+            && valid_operand(instruction.second)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinearProgram {
+    instructions: Vec<Instruction>,
+    machine: Arc<RegisterMachine>,
+    minimum_length: usize,
+    maximum_length: usize,
+    macro_mutation_probability: f64
+}
+
+impl GeneticCollection for LinearProgram {
+    type Child = LinearProgram;
+
+    /// Breeds a child by linear crossover followed by mutation.
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.linear_crossover(mate, rng)?;
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.instructions.len()
+    }
+}
+
+impl Mutable for LinearProgram {
+    /// Applies either a macro mutation, which inserts or deletes an instruction, or a micro
+    /// mutation, which changes part of one instruction.
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        if rng.gen::<f64>() < self.macro_mutation_probability {
+            self.macro_mutation(rng);
+        } else {
+            self.micro_mutation(rng);
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for LinearProgram {
+    fn eq(&self, other: &LinearProgram) -> bool {
+        self.instructions == other.instructions
+    }
+}
+
+impl Eq for LinearProgram {}
+
+impl Hash for LinearProgram {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.instructions.hash(state);
+    }
+}
+
+impl Display for LinearProgram {
+    /// Writes one instruction per line.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+impl LinearProgram {
+    /// Creates a program of random instructions. Programs are kept between 1 and 200
+    /// instructions long until configured otherwise.
+    pub fn new(length: usize, machine: Arc<RegisterMachine>, rng: &mut StdRng) -> LinearProgram {
+        let instructions = (0..length).map(|_| machine.random_instruction(rng)).collect();
+        LinearProgram::with_instructions(instructions, machine)
+    }
+
+    /// # Returns
+    /// <ul>
+    /// <li>The program if successful.</li>
+    /// <li>InvalidParameterError if an instruction uses a register, input, constant or
+    /// operation the machine doesn't have.</li>
+    /// </ul>
+    pub fn from_instructions(instructions: Vec<Instruction>, machine: Arc<RegisterMachine>) -> GeneticResult<LinearProgram> {
+        if instructions.iter().all(|instruction| machine.is_valid(instruction)) {
+            Ok(LinearProgram::with_instructions(instructions, machine))
+        } else {
+            Err(GeneticError::InvalidParameterError)
+        }
+    }
+
+    pub fn get_instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn get_machine(&self) -> &Arc<RegisterMachine> {
+        &self.machine
+    }
+
+    /// Limits the number of instructions crossover and mutation may leave a program with.
+    pub fn set_length_limits(&mut self, minimum_length: usize, maximum_length: usize) -> GeneticResult<()> {
+        if minimum_length > maximum_length {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.minimum_length = minimum_length;
+        self.maximum_length = maximum_length;
+        Ok(())
+    }
+
+    /// Sets the chance that a mutation inserts or deletes an instruction instead of changing
+    /// one.
+    pub fn set_macro_mutation_probability(&mut self, probability: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&probability) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.macro_mutation_probability = probability;
+        Ok(())
+    }
+
+    /// Runs the program with every register starting at zero.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The value left in the output register if successful.</li>
+    /// <li>InvalidParameterError if the number of inputs doesn't match the machine.</li>
+    /// </ul>
+    pub fn execute(&self, inputs: &[f64]) -> GeneticResult<f64> {
+        if inputs.len() != self.machine.inputs {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mut registers = vec![0f64; self.machine.registers];
+        for instruction in &self.instructions {
+            let read = |operand| match operand {
+                Operand::Register(index) => registers[index],
+                Operand::Input(index) => inputs[index],
+                Operand::Constant(index) => self.machine.constants[index]
+            };
+            let value = instruction.operation.apply(read(instruction.first), read(instruction.second));
+            registers[instruction.destination] = value;
+        }
+        Ok(registers[self.machine.output_register])
+    }
+
+    /// Finds the structurally effective instructions: those whose result can reach the output
+    /// register. Every other instruction is an intron and can be removed without changing what
+    /// the program computes.
+    ///
+    /// # Returns
+    /// The indices of the effective instructions, in program order.
+    pub fn effective_instructions(&self) -> Vec<usize> {
+        let mut effective_registers = HashSet::new();
+        effective_registers.insert(self.machine.output_register);
+        let mut effective = vec!();
+        for (index, instruction) in self.instructions.iter().enumerate().rev() {
+            if effective_registers.remove(&instruction.destination) {
+                for &operand in &[instruction.first, instruction.second] {
+                    if let Operand::Register(register) = operand {
+                        effective_registers.insert(register);
+                    }
+                }
+                effective.push(index);
+            }
+        }
+        effective.reverse();
+        effective
+    }
+
+    /// A copy of the program holding only its effective instructions.
+    pub fn remove_introns(&self) -> LinearProgram {
+        let mut program = self.clone();
+        program.instructions = self.effective_instructions().into_iter()
+            .map(|index| self.instructions[index])
+            .collect();
+        program
+    }
+
+    /// Two point crossover between programs of different lengths: a random segment of self is
+    /// replaced by a random segment of the mate. Segment lengths are drawn so the child stays
+    /// within the length limits, leaving self unchanged when that is impossible.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The child program, running on the machine of self.</li>
+    /// <li>InvalidParameterError if the mate runs on another machine that can't execute all of
+    /// its instructions.</li>
+    /// </ul>
+    pub fn linear_crossover(&self, mate: &LinearProgram,
+                            rng: &mut StdRng) -> GeneticResult<LinearProgram> {
+        if !Arc::ptr_eq(&self.machine, &mate.machine)
+            && !mate.instructions.iter().all(|instruction| self.machine.is_valid(instruction)) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let start = rng.gen_range(0, self.instructions.len() + 1);
+        let removed = rng.gen_range(0, self.instructions.len() - start + 1);
+        let kept = self.instructions.len() - removed;
+        let smallest = self.minimum_length.saturating_sub(kept);
+        let largest = self.maximum_length.saturating_sub(kept).min(mate.instructions.len());
+        if smallest > largest {
+            return Ok(self.clone());
+        }
+        let inserted = rng.gen_range(smallest, largest + 1);
+        let donor_start = rng.gen_range(0, mate.instructions.len() - inserted + 1);
+        let mut child = self.clone();
+        child.instructions.splice(start..start + removed,
+                                  mate.instructions[donor_start..donor_start + inserted].iter().cloned());
+        Ok(child)
+    }
+
+    /// Inserts a random instruction or deletes one, as the length limits allow.
+    pub fn macro_mutation(&mut self, rng: &mut StdRng) {
+        let length = self.instructions.len();
+        let can_insert = length < self.maximum_length;
+        let can_delete = length > self.minimum_length && length > 0;
+        if can_insert && (!can_delete || rng.gen()) {
+            let instruction = self.machine.random_instruction(rng);
+            self.instructions.insert(rng.gen_range(0, length + 1), instruction);
+        } else if can_delete {
+            self.instructions.remove(rng.gen_range(0, length));
+        }
+    }
+
+    /// Changes the destination, the operation or one of the operands of a random instruction.
+    pub fn micro_mutation(&mut self, rng: &mut StdRng) {
+        if self.instructions.is_empty() {
+            return;
+        }
+        let index = rng.gen_range(0, self.instructions.len());
+        let machine = self.machine.clone();
+        let instruction = &mut self.instructions[index];
+        match rng.gen_range(0, 4) {
+            0 => instruction.destination = rng.gen_range(0, machine.registers),
+            1 => instruction.operation = machine.operations[rng.gen_range(0, machine.operations.len())],
+            2 => instruction.first = machine.random_operand(rng),
+            _ => instruction.second = machine.random_operand(rng)
+        }
+    }
+
+    fn with_instructions(instructions: Vec<Instruction>, machine: Arc<RegisterMachine>) -> LinearProgram {
+        LinearProgram {
+            instructions,
+            machine,
+            minimum_length: DEFAULT_MINIMUM_LENGTH,
+            maximum_length: DEFAULT_MAXIMUM_LENGTH,
+            macro_mutation_probability: DEFAULT_MACRO_MUTATION_PROBABILITY
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn machine() -> Arc<RegisterMachine> {
+        let operations = vec![LinearOperation::Add, LinearOperation::Subtract,
+                              LinearOperation::Multiply, LinearOperation::Divide];
+        Arc::new(RegisterMachine::new(3, 2, vec![2f64, 0.5f64], operations).unwrap())
+    }
+
+    #[test]
+    fn program_runs_instructions_in_order_and_reads_the_output_register() {
+        let instructions = vec![
+            Instruction::new(1, LinearOperation::Multiply, Operand::Input(0), Operand::Constant(0)),
+            Instruction::new(0, LinearOperation::Add, Operand::Register(1), Operand::Input(1)),
+            Instruction::new(2, LinearOperation::Divide, Operand::Register(0), Operand::Register(2))
+        ];
+        let program = LinearProgram::from_instructions(instructions, machine()).unwrap();
+        assert_eq!(11f64, program.execute(&[3f64, 5f64]).unwrap());
+        assert_eq!("r1 = x0 * c0\nr0 = r1 + x1\nr2 = r0 / r2", program.to_string());
+        assert!(program.execute(&[3f64]).is_err());
+        let invalid = vec![Instruction::new(3, LinearOperation::Add, Operand::Input(0), Operand::Input(0))];
+        assert!(LinearProgram::from_instructions(invalid, machine()).is_err());
+    }
+
+    #[test]
+    fn introns_are_detected_and_removing_them_keeps_the_result() {
+        let instructions = vec![
+            Instruction::new(2, LinearOperation::Add, Operand::Input(0), Operand::Input(0)),
+            Instruction::new(1, LinearOperation::Multiply, Operand::Input(1), Operand::Constant(1)),
+            // Overwritten before it is read.
+            Instruction::new(2, LinearOperation::Subtract, Operand::Register(1), Operand::Constant(0)),
+            Instruction::new(0, LinearOperation::Add, Operand::Register(1), Operand::Register(2)),
+            // Writes a register the output never reads.
+            Instruction::new(1, LinearOperation::Add, Operand::Register(0), Operand::Register(0))
+        ];
+        let program = LinearProgram::from_instructions(instructions, machine()).unwrap();
+        assert_eq!(vec![1, 2, 3], program.effective_instructions());
+        let effective = program.remove_introns();
+        assert_eq!(3, effective.get_size());
+        assert_eq!(program.execute(&[4f64, 6f64]).unwrap(), effective.execute(&[4f64, 6f64]).unwrap());
+    }
+
+    #[test]
+    fn crossover_and_mutation_keep_programs_valid_and_within_length_limits() {
+        let mut rng = StdRng::from_seed(&[20usize][..]);
+        let mut first = LinearProgram::new(8, machine(), &mut rng);
+        let second = LinearProgram::new(15, machine(), &mut rng);
+        first.set_length_limits(4, 12).unwrap();
+        for _ in 0..100 {
+            let child = first.breed(&second, &mut rng).unwrap();
+            assert!(child.get_size() >= 4 && child.get_size() <= 12);
+            assert!(LinearProgram::from_instructions(child.get_instructions().to_vec(), machine()).is_ok());
+            first = child;
+        }
+        let operations = vec![LinearOperation::Add, LinearOperation::Multiply];
+        let larger = Arc::new(RegisterMachine::new(6, 4, vec![1f64, 2f64, 3f64], operations).unwrap());
+        let foreign = LinearProgram::new(30, larger, &mut rng);
+        assert!(first.breed(&foreign, &mut rng).is_err());
+        let twin = LinearProgram::new(10, machine(), &mut rng);
+        assert!(first.breed(&twin, &mut rng).is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+//! Local searches over the neighbourhood defined by `Mutable`: the neighbours of a genome are
+//! the genomes one call of `mutate` away from it. Neighbours whose evaluation fails are never
+//! moved to. The neighbour sampling, climbing step and Metropolis acceptance are public so the
+//! single solution optimizers share them.
+
+use std::collections::VecDeque;
+
+use rand::{Rng, StdRng};
+
+use ::individual::Individual;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::interfaces::local_search::LocalSearch;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// What a genetic algorithm does with the result of a local search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LearningMode {
+    /// The improved genome takes the place of the original.
+    Lamarckian,
+    /// The original genome is kept with its own fitness, but is selected as a parent by the
+    /// fitness of the improved one.
+    Baldwinian
+}
+
+/// Which improving neighbour hill climbing moves to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Improvement {
+    /// The first neighbour found that is fitter than the current genome.
+    First,
+    /// The fittest of all the neighbours sampled, if it is fitter than the current genome.
+    Best
+}
+
+/// Repeatedly moves to a fitter neighbour until none of the sampled neighbours is fitter or
+/// the step limit is reached.
+#[derive(Clone, Copy, Debug)]
+pub struct HillClimbing {
+    improvement: Improvement,
+    neighbours: usize,
+    maximum_steps: usize
+}
+
+impl HillClimbing {
+    /// # Parameters
+    /// <ul>
+    /// <li>improvement - which improving neighbour to move to</li>
+    /// <li>neighbours - how many neighbours are sampled at each step</li>
+    /// <li>maximum_steps - the most moves made</li>
+    /// </ul>
+    pub fn new(improvement: Improvement, neighbours: usize, maximum_steps: usize) -> HillClimbing {
+        HillClimbing { improvement, neighbours: neighbours.max(1), maximum_steps }
+    }
+}
+
+impl<G> LocalSearch<G> for HillClimbing where G: Mutable + Clone {
+    fn search(&mut self, start: Individual<G>, fitness_function: &dyn FitnessFunction<G>, rng: &mut StdRng)
+              -> GeneticResult<(Individual<G>, usize)> {
+        let mut current = start;
+        let mut evaluations = 0;
+        for _ in 0..self.maximum_steps {
+            let (chosen, sampled) = climb(&current, self.improvement, self.neighbours, fitness_function,
+                                          f64::NEG_INFINITY, rng)?;
+            evaluations += sampled;
+            match chosen {
+                Some(chosen) => current = chosen,
+                None => break
+            }
+        }
+        Ok((current, evaluations))
+    }
+}
+
+/// Simulated annealing with geometric cooling: a fitter neighbour is always accepted and a
+/// less fit one with a probability of e^(difference / temperature). The fittest genome
+/// visited is returned.
+#[derive(Clone, Copy, Debug)]
+pub struct AnnealingSearch {
+    steps: usize,
+    initial_temperature: f64,
+    cooling_factor: f64
+}
+
+impl AnnealingSearch {
+    /// # Parameters
+    /// <ul>
+    /// <li>steps - how many neighbours are tried</li>
+    /// <li>initial_temperature - the temperature of the first step</li>
+    /// <li>cooling_factor - what the temperature is multiplied by after every step</li>
+    /// </ul>
+    pub fn new(steps: usize, initial_temperature: f64, cooling_factor: f64) -> AnnealingSearch {
+        AnnealingSearch { steps, initial_temperature, cooling_factor }
+    }
+}
+
+impl<G> LocalSearch<G> for AnnealingSearch where G: Mutable + Clone {
+    fn search(&mut self, start: Individual<G>, fitness_function: &dyn FitnessFunction<G>, rng: &mut StdRng)
+              -> GeneticResult<(Individual<G>, usize)> {
+        let mut best = start.clone();
+        let mut current = start;
+        let mut temperature = self.initial_temperature;
+        for _ in 0..self.steps {
+            let neighbour = neighbour(current.get_genome(), fitness_function, f64::NEG_INFINITY, rng)?;
+            if metropolis(neighbour.get_fitness() - current.get_fitness(), temperature, rng) {
+                current = neighbour;
+                if current.get_fitness() > best.get_fitness() {
+                    best = current.clone();
+                }
+            }
+            temperature *= self.cooling_factor;
+        }
+        Ok((best, self.steps))
+    }
+}
+
+/// Tabu search: every step moves to the fittest sampled neighbour, even a less fit one,
+/// unless it was visited within the last `tenure` steps. A tabu neighbour is still accepted
+/// if it is fitter than any genome found so far. The fittest genome visited is returned.
+#[derive(Clone, Copy, Debug)]
+pub struct TabuSearch {
+    neighbours: usize,
+    steps: usize,
+    tenure: usize
+}
+
+impl TabuSearch {
+    /// # Parameters
+    /// <ul>
+    /// <li>neighbours - how many neighbours are sampled at each step</li>
+    /// <li>steps - how many moves are made</li>
+    /// <li>tenure - how many of the most recently visited genomes are tabu</li>
+    /// </ul>
+    pub fn new(neighbours: usize, steps: usize, tenure: usize) -> TabuSearch {
+        TabuSearch { neighbours: neighbours.max(1), steps, tenure }
+    }
+}
+
+impl<G> LocalSearch<G> for TabuSearch where G: Mutable + Clone + Eq {
+    fn search(&mut self, start: Individual<G>, fitness_function: &dyn FitnessFunction<G>, rng: &mut StdRng)
+              -> GeneticResult<(Individual<G>, usize)> {
+        let mut best = start.clone();
+        let mut current = start;
+        let mut tabu: VecDeque<G> = VecDeque::with_capacity(self.tenure + 1);
+        let mut evaluations = 0;
+        for _ in 0..self.steps {
+            let mut chosen: Option<Individual<G>> = None;
+            for _ in 0..self.neighbours {
+                let neighbour = neighbour(current.get_genome(), fitness_function, f64::NEG_INFINITY, rng)?;
+                evaluations += 1;
+                let allowed = neighbour.get_fitness() > best.get_fitness()
+                    || (neighbour.get_genome() != current.get_genome() && !tabu.contains(neighbour.get_genome()));
+                let fitter = match chosen {
+                    Some(ref chosen) => neighbour.get_fitness() > chosen.get_fitness(),
+                    None => neighbour.get_fitness() > f64::NEG_INFINITY
+                };
+                if allowed && fitter {
+                    chosen = Some(neighbour);
+                }
+            }
+            let chosen = match chosen {
+                Some(chosen) => chosen,
+                None => break
+            };
+            tabu.push_back(current.into_genome());
+            if tabu.len() > self.tenure {
+                tabu.pop_front();
+            }
+            current = chosen;
+            if current.get_fitness() > best.get_fitness() {
+                best = current.clone();
+            }
+        }
+        Ok((best, evaluations))
+    }
+}
+
+/// Mutates a copy of the genome and evaluates it.
+///
+/// # Parameters
+/// <ul>
+/// <li>genome - the genome whose neighbour is sampled</li>
+/// <li>fitness_function - scores the neighbour</li>
+/// <li>invalid_fitness - the fitness of a neighbour whose evaluation fails</li>
+/// <li>rng - the random number generator</li>
+/// </ul>
+pub fn neighbour<G>(genome: &G, fitness_function: &dyn FitnessFunction<G>, invalid_fitness: f64, rng: &mut StdRng)
+                    -> GeneticResult<Individual<G>> where G: Mutable + Clone {
+    let mut neighbour = genome.clone();
+    neighbour.mutate(rng)?;
+    let fitness = fitness_function.evaluate(&neighbour).unwrap_or(invalid_fitness);
+    Ok(Individual::new(neighbour, fitness))
+}
+
+/// One step of hill climbing: samples up to the given number of neighbours and picks one
+/// that is strictly fitter than the current genome.
+///
+/// # Returns
+/// The neighbour to move to, or None if no sampled neighbour is fitter, together with the
+/// number of neighbours evaluated.
+pub fn climb<G>(current: &Individual<G>, improvement: Improvement, neighbours: usize,
+                fitness_function: &dyn FitnessFunction<G>, invalid_fitness: f64, rng: &mut StdRng)
+                -> GeneticResult<(Option<Individual<G>>, usize)> where G: Mutable + Clone {
+    let mut chosen: Option<Individual<G>> = None;
+    let mut evaluations = 0;
+    for _ in 0..neighbours {
+        let neighbour = neighbour(current.get_genome(), fitness_function, invalid_fitness, rng)?;
+        evaluations += 1;
+        let threshold = match chosen {
+            Some(ref chosen) => chosen.get_fitness(),
+            None => current.get_fitness()
+        };
+        if neighbour.get_fitness() > threshold {
+            chosen = Some(neighbour);
+            if improvement == Improvement::First {
+                break;
+            }
+        }
+    }
+    Ok((chosen, evaluations))
+}
+
+/// The Metropolis criterion: a move that doesn't lower the fitness is always accepted and one
+/// that does with a probability of e^(difference / temperature), never at a temperature of 0.
+pub fn metropolis(difference: f64, temperature: f64, rng: &mut StdRng) -> bool {
+    difference >= 0f64 || (temperature > 0f64 && rng.gen::<f64>() < (difference / temperature).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::bit_string::BitString;
+
+    fn one_max(bit_string: &BitString) -> GeneticResult<f64> {
+        Ok(bit_string.count_ones() as f64)
+    }
+
+    fn start() -> Individual<BitString> {
+        Individual::new(BitString::new(20), 0f64)
+    }
+
+    #[test]
+    fn hill_climbing_reaches_the_top_of_one_max() {
+        for (seed, &improvement) in [Improvement::First, Improvement::Best].iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[90 + seed][..]);
+            let mut hill_climbing = HillClimbing::new(improvement, 200, 1000);
+            let (best, evaluations) = hill_climbing.search(start(), &one_max, &mut rng).unwrap();
+            assert_eq!(20f64, best.get_fitness());
+            assert_eq!(20, best.get_genome().count_ones());
+            assert!(evaluations > 0);
+        }
+    }
+
+    #[test]
+    fn first_improvement_stops_sampling_once_a_neighbour_is_fitter() {
+        let mut rng = StdRng::from_seed(&[92usize][..]);
+        let (_, first) = HillClimbing::new(Improvement::First, 50, 1).search(start(), &one_max, &mut rng).unwrap();
+        let (_, best) = HillClimbing::new(Improvement::Best, 50, 1).search(start(), &one_max, &mut rng).unwrap();
+        assert!(first < 50);
+        assert_eq!(50, best);
+    }
+
+    #[test]
+    fn annealing_and_tabu_search_improve_on_their_start_and_return_the_best_visited() {
+        let mut rng = StdRng::from_seed(&[93usize][..]);
+        let (annealed, evaluations) = AnnealingSearch::new(500, 2f64, 0.99).search(start(), &one_max, &mut rng).unwrap();
+        assert_eq!(500, evaluations);
+        assert!(annealed.get_fitness() >= 15f64);
+        assert_eq!(annealed.get_fitness(), one_max(annealed.get_genome()).unwrap());
+        let (tabu, _) = TabuSearch::new(10, 100, 5).search(start(), &one_max, &mut rng).unwrap();
+        assert_eq!(20f64, tabu.get_fitness());
+    }
+}
@@ -0,0 +1,610 @@
+//! MAP-Elites, a quality-diversity algorithm: the behaviour space is divided into cells and
+//! each cell keeps the fittest genome found whose behaviour falls into it. New candidates are
+//! mutated copies of randomly chosen elites, so the archive fills with genomes that are good
+//! at many different things rather than a population converging on one of them.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::hash::Hash;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::individual::Individual;
+use ::interfaces::behaviour_descriptor::BehaviourDescriptor;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+/// How the behaviour space is divided into cells.
+enum Cells {
+    /// A regular grid with the given number of cells along each dimension.
+    Grid(Vec<usize>),
+    /// The Voronoi cells of the given centroids.
+    Centroidal(Vec<Vec<f64>>)
+}
+
+/// A division of a box of behaviour descriptors into cells.
+pub struct Tessellation {
+    bounds: Vec<(f64, f64)>,
+    cells: Cells
+}
+
+impl Tessellation {
+    /// Divides the bounds into a regular grid. Descriptors outside the bounds fall into the
+    /// nearest cell on the edge of the grid.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>bounds - the lower and upper bound of each descriptor</li>
+    /// <li>cells_per_dimension - how many cells each descriptor is divided into</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tessellation if successful.</li>
+    /// <li>CollectionEmptyError if there are no bounds.</li>
+    /// <li>LengthMismatchError if there isn't one number of cells per bound.</li>
+    /// <li>InvalidParameterError if a bound is empty or not finite, or a dimension has no
+    /// cells.</li>
+    /// </ul>
+    pub fn grid(bounds: Vec<(f64, f64)>, cells_per_dimension: Vec<usize>) -> GeneticResult<Tessellation> {
+        check_bounds(&bounds)?;
+        if cells_per_dimension.len() != bounds.len() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        if cells_per_dimension.contains(&0) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(Tessellation { bounds, cells: Cells::Grid(cells_per_dimension) })
+    }
+
+    /// Divides the bounds into the Voronoi cells of centroids placed by k-means over points
+    /// drawn uniformly from the bounds, as in CVT-MAP-Elites. Unlike a grid, the number of
+    /// cells doesn't grow exponentially with the number of descriptors.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>bounds - the lower and upper bound of each descriptor</li>
+    /// <li>cells - the number of cells</li>
+    /// <li>samples - how many uniform points the centroids are fitted to</li>
+    /// <li>iterations - the most rounds of k-means made</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tessellation if successful.</li>
+    /// <li>CollectionEmptyError if there are no bounds.</li>
+    /// <li>InvalidParameterError if a bound is empty or not finite, there are no cells or
+    /// fewer samples than cells.</li>
+    /// </ul>
+    pub fn centroidal(bounds: Vec<(f64, f64)>, cells: usize, samples: usize, iterations: usize, rng: &mut StdRng)
+                      -> GeneticResult<Tessellation> {
+        check_bounds(&bounds)?;
+        if cells == 0 || samples < cells {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let points: Vec<Vec<f64>> = (0..samples)
+            .map(|_| bounds.iter().map(|&(lower, upper)| lower + (upper - lower) * rng.gen::<f64>()).collect())
+            .collect();
+        let mut centroids: Vec<Vec<f64>> = points[..cells].to_vec();
+        let mut assignment = vec![usize::MAX; samples];
+        for _ in 0..iterations {
+            let mut changed = false;
+            for (point, assigned) in points.iter().zip(assignment.iter_mut()) {
+                let nearest = nearest(&centroids, point);
+                if nearest != *assigned {
+                    *assigned = nearest;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+            let mut sums = vec![vec![0f64; bounds.len()]; cells];
+            let mut counts = vec![0usize; cells];
+            for (point, &assigned) in points.iter().zip(&assignment) {
+                counts[assigned] += 1;
+                for (sum, value) in sums[assigned].iter_mut().zip(point) {
+                    *sum += value;
+                }
+            }
+            for ((centroid, sum), &count) in centroids.iter_mut().zip(sums).zip(&counts) {
+                if count > 0 {
+                    *centroid = sum.into_iter().map(|sum| sum / count as f64).collect();
+                }
+            }
+        }
+        Ok(Tessellation { bounds, cells: Cells::Centroidal(centroids) })
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    pub fn get_cell_count(&self) -> usize {
+        match self.cells {
+            Cells::Grid(ref cells_per_dimension) => cells_per_dimension.iter().product(),
+            Cells::Centroidal(ref centroids) => centroids.len()
+        }
+    }
+
+    /// The point that represents a cell: the centre of a grid cell or the centroid of a
+    /// Voronoi cell.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The point if successful.</li>
+    /// <li>InvalidParameterError if there is no such cell.</li>
+    /// </ul>
+    pub fn get_centre(&self, cell: usize) -> GeneticResult<Vec<f64>> {
+        if cell >= self.get_cell_count() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        match self.cells {
+            Cells::Grid(ref cells_per_dimension) => {
+                let mut remainder = cell;
+                Ok(self.bounds.iter().zip(cells_per_dimension).map(|(&(lower, upper), &count)| {
+                    let index = remainder % count;
+                    remainder /= count;
+                    lower + (upper - lower) * (index as f64 + 0.5) / count as f64
+                }).collect())
+            }
+            Cells::Centroidal(ref centroids) => Ok(centroids[cell].clone())
+        }
+    }
+
+    /// Finds the cell a behaviour descriptor falls into.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The index of the cell if successful.</li>
+    /// <li>LengthMismatchError if the descriptor doesn't have one value per bound.</li>
+    /// <li>InvalidParameterError if a value is NaN.</li>
+    /// </ul>
+    pub fn get_cell(&self, descriptor: &[f64]) -> GeneticResult<usize> {
+        if descriptor.len() != self.bounds.len() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        if descriptor.iter().any(|value| value.is_nan()) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        match self.cells {
+            Cells::Grid(ref cells_per_dimension) => {
+                let mut cell = 0;
+                let mut stride = 1;
+                for ((&(lower, upper), &count), &value) in self.bounds.iter().zip(cells_per_dimension).zip(descriptor) {
+                    let position = if upper > lower { (value - lower) / (upper - lower) } else { 0f64 };
+                    let index = ((position * count as f64).floor().max(0f64) as usize).min(count - 1);
+                    cell += index * stride;
+                    stride *= count;
+                }
+                Ok(cell)
+            }
+            Cells::Centroidal(ref centroids) => Ok(nearest(centroids, descriptor))
+        }
+    }
+}
+
+/// The fittest genome found for a cell, together with its behaviour.
+#[derive(Clone, Debug)]
+pub struct Elite<G> {
+    individual: Individual<G>,
+    descriptor: Vec<f64>
+}
+
+impl<G> Elite<G> {
+    pub fn get_genome(&self) -> &G {
+        self.individual.get_genome()
+    }
+
+    pub fn get_fitness(&self) -> f64 {
+        self.individual.get_fitness()
+    }
+
+    pub fn get_descriptor(&self) -> &[f64] {
+        &self.descriptor
+    }
+}
+
+/// A MAP-Elites archive, which is filled from an initial population and then by mutating
+/// random elites. Candidates whose fitness or behaviour can't be evaluated are discarded, so
+/// the invalid fitness set through `Optimizer::set_invalid_fitness` is never used.
+pub struct MapElites<G, F, D> {
+    archive: Vec<Option<Elite<G>>>,
+    occupied: Vec<usize>,
+    initial_population: Vec<G>,
+    tessellation: Tessellation,
+    fitness_function: F,
+    descriptor: D,
+    batch_size: usize,
+    crossover_probability: f64,
+    generation: usize,
+    evaluations: usize,
+    evaluated: bool,
+    progress: Progress<G>
+}
+
+impl<G, F, D> MapElites<G, F, D>
+    where G: GeneticCollection<Child = G> + Clone + Hash + Eq, F: FitnessFunction<G>, D: BehaviourDescriptor<G> {
+    /// Creates an archive that is filled with the initial population when the first
+    /// generation is requested. Each generation produces as many candidates as the initial
+    /// population holds, all of them by mutation, until configured otherwise.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>initial_population - the genomes the archive is seeded with</li>
+    /// <li>tessellation - divides the behaviour space into cells</li>
+    /// <li>fitness_function - scores a genome, higher is fitter</li>
+    /// <li>descriptor - places a genome in the behaviour space</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The archive if successful.</li>
+    /// <li>CollectionEmptyError if the initial population is empty.</li>
+    /// </ul>
+    pub fn new(initial_population: Vec<G>, tessellation: Tessellation, fitness_function: F, descriptor: D)
+               -> GeneticResult<MapElites<G, F, D>> {
+        if initial_population.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        Ok(MapElites {
+            archive: (0..tessellation.get_cell_count()).map(|_| None).collect(),
+            occupied: vec![],
+            batch_size: initial_population.len(),
+            initial_population,
+            tessellation,
+            fitness_function,
+            descriptor,
+            crossover_probability: 0f64,
+            generation: 0,
+            evaluations: 0,
+            evaluated: false,
+            progress: Progress::new(0f64)
+        })
+    }
+
+    /// Sets how many candidates are produced each generation.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the batch size is 0.</li>
+    /// </ul>
+    pub fn set_batch_size(&mut self, batch_size: usize) -> GeneticResult<()> {
+        if batch_size == 0 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.batch_size = batch_size;
+        Ok(())
+    }
+
+    /// Sets the probability that a candidate is bred from two random elites instead of
+    /// mutated from one. Defaults to 0.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the probability isn't between 0 and 1.</li>
+    /// </ul>
+    pub fn set_crossover_probability(&mut self, crossover_probability: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&crossover_probability) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.crossover_probability = crossover_probability;
+        Ok(())
+    }
+
+    pub fn get_tessellation(&self) -> &Tessellation {
+        &self.tessellation
+    }
+
+    /// The elite of every cell, indexed by cell, or None for cells that are still empty.
+    pub fn get_archive(&self) -> &[Option<Elite<G>>] {
+        &self.archive
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// The fraction of the cells that hold an elite.
+    pub fn get_coverage(&self) -> f64 {
+        self.occupied.len() as f64 / self.archive.len() as f64
+    }
+
+    /// The QD-score: the sum of the fitness of every elite. It rewards both filling more
+    /// cells and improving the elites, provided the fitness function never goes below 0.
+    pub fn get_qd_score(&self) -> f64 {
+        self.elites().map(|elite| elite.get_fitness()).sum()
+    }
+
+    /// Evaluates a genome and makes it the elite of its cell if the cell is empty or it is
+    /// fitter than the current elite.
+    ///
+    /// # Returns
+    /// true if the genome was archived.
+    fn insert(&mut self, genome: G) -> bool {
+        self.evaluations += 1;
+        let fitness = match self.fitness_function.evaluate(&genome) {
+            Ok(fitness) if !fitness.is_nan() => fitness,
+            _ => return false
+        };
+        let descriptor = match self.descriptor.describe(&genome) {
+            Ok(descriptor) => descriptor,
+            Err(_) => return false
+        };
+        let cell = match self.tessellation.get_cell(&descriptor) {
+            Ok(cell) => cell,
+            Err(_) => return false
+        };
+        let replaces = match self.archive[cell] {
+            Some(ref elite) => fitness > elite.get_fitness(),
+            None => {
+                self.occupied.push(cell);
+                true
+            }
+        };
+        if replaces {
+            self.archive[cell] = Some(Elite { individual: Individual::new(genome, fitness), descriptor });
+        }
+        replaces
+    }
+
+    fn random_elite(&self, rng: &mut StdRng) -> &G {
+        let cell = self.occupied[rng.gen_range(0, self.occupied.len())];
+        match self.archive[cell] {
+            Some(ref elite) => elite.get_genome(),
+            None => unreachable!("occupied cells always hold an elite")
+        }
+    }
+
+    fn elites(&self) -> impl Iterator<Item = &Elite<G>> {
+        self.archive.iter().filter_map(|elite| elite.as_ref())
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        let fitness: Vec<f64> = self.elites().map(|elite| elite.get_fitness()).collect();
+        let mean_length = self.elites().map(|elite| elite.get_genome().get_size() as f64).sum::<f64>()
+            / fitness.len() as f64;
+        let unique_genomes = self.elites().map(|elite| elite.get_genome()).collect::<HashSet<_>>().len();
+        GenerationStats::new(self.generation, &fitness, mean_length, unique_genomes, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        let elites = self.archive.iter().filter_map(|elite| elite.as_ref())
+            .map(|elite| (elite.get_genome(), elite.get_fitness()));
+        self.progress.update_best(elites, stats);
+    }
+}
+
+impl<G, F, D> Optimizer<G> for MapElites<G, F, D>
+    where G: GeneticCollection<Child = G> + Clone + Hash + Eq, F: FitnessFunction<G>, D: BehaviourDescriptor<G> {
+    /// Seeds the archive if that hasn't happened yet and summarises the elites.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if !self.evaluated {
+            self.evaluated = true;
+            let initial_population = self.initial_population.split_off(0);
+            for genome in initial_population {
+                self.insert(genome);
+            }
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Produces a batch of candidates from random elites and tries to archive each of them.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The statistics of the elites after the batch if successful.</li>
+    /// <li>CollectionEmptyError if no genome could be archived to start from.</li>
+    /// <li>Any error from mutating or breeding the genomes.</li>
+    /// </ul>
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.get_statistics(rng)?;
+        if self.occupied.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        let mut candidates = Vec::with_capacity(self.batch_size);
+        for _ in 0..self.batch_size {
+            let parent = self.random_elite(rng);
+            let candidate = if rng.gen::<f64>() < self.crossover_probability {
+                parent.breed(self.random_elite(rng), rng)?
+            } else {
+                let mut candidate = parent.clone();
+                candidate.mutate(rng)?;
+                candidate
+            };
+            candidates.push(candidate);
+        }
+        for candidate in candidates {
+            self.insert(candidate);
+        }
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<G> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<G> {
+        &mut self.progress
+    }
+}
+
+impl<G, F, D> MapElites<G, F, D> where G: Display {
+    /// Writes the archive as CSV, one row per elite in order of cell: the cell, its fitness,
+    /// each value of its behaviour descriptor and the genome as it is displayed, quoted.
+    pub fn export(&self) -> String {
+        let dimensions = self.tessellation.get_bounds().len();
+        let mut csv = String::from("cell,fitness");
+        for dimension in 0..dimensions {
+            csv.push_str(&format!(",descriptor_{}", dimension));
+        }
+        csv.push_str(",genome\n");
+        for (cell, elite) in self.archive.iter().enumerate() {
+            if let Some(ref elite) = *elite {
+                csv.push_str(&format!("{},{}", cell, elite.get_fitness()));
+                for value in elite.get_descriptor() {
+                    csv.push_str(&format!(",{}", value));
+                }
+                csv.push_str(&format!(",\"{}\"\n", elite.get_genome().to_string().replace('"', "\"\"")));
+            }
+        }
+        csv
+    }
+}
+
+fn check_bounds(bounds: &[(f64, f64)]) -> GeneticResult<()> {
+    if bounds.is_empty() {
+        return Err(GeneticError::CollectionEmptyError);
+    }
+    if !bounds.iter().all(|&(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper) {
+        return Err(GeneticError::InvalidParameterError);
+    }
+    Ok(())
+}
+
+/// The index of the centroid closest to the point.
+fn nearest(centroids: &[Vec<f64>], point: &[f64]) -> usize {
+    let squared_distance = |centroid: &Vec<f64>| centroid.iter().zip(point)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f64>();
+    let mut nearest = 0;
+    let mut nearest_distance = f64::INFINITY;
+    for (index, centroid) in centroids.iter().enumerate() {
+        let distance = squared_distance(centroid);
+        if distance < nearest_distance {
+            nearest = index;
+            nearest_distance = distance;
+        }
+    }
+    nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::bit_string::BitString;
+    use ::genetic_byte::GeneticByte;
+    use ::genetic_string::GeneticString;
+    use ::interfaces::fitness_function::TargetFitness;
+    use ::interfaces::genetic_collection_component::GeneticCollectionComponent;
+    use ::termination::MaxGenerations;
+
+    type Expression = GeneticString<GeneticByte>;
+
+    fn length_and_operator_mix(expression: &Expression) -> GeneticResult<Vec<f64>> {
+        let operators: Vec<u8> = expression.get_vector_immutable().iter()
+            .filter_map(|component| component.get_operator().ok())
+            .collect();
+        let multiplicative = operators.iter().filter(|&&operator| operator == b'*' || operator == b'/').count();
+        let mix = if operators.is_empty() { 0f64 } else { multiplicative as f64 / operators.len() as f64 };
+        Ok(vec![expression.get_size() as f64, mix])
+    }
+
+    fn ones_in_each_half(bit_string: &BitString) -> GeneticResult<Vec<f64>> {
+        let bits = bit_string.to_bools();
+        let (first, second) = bits.split_at(bits.len() / 2);
+        let ones = |half: &[bool]| half.iter().filter(|&&bit| bit).count() as f64;
+        Ok(vec![ones(first), ones(second)])
+    }
+
+    fn alternating(bit_string: &BitString) -> GeneticResult<f64> {
+        let bits = bit_string.to_bools();
+        Ok(bits.windows(2).filter(|pair| pair[0] != pair[1]).count() as f64)
+    }
+
+    #[test]
+    fn tessellations_assign_descriptors_to_cells() {
+        let grid = Tessellation::grid(vec![(0f64, 1f64), (0f64, 10f64)], vec![4, 2]).unwrap();
+        assert_eq!(8, grid.get_cell_count());
+        assert_eq!(0, grid.get_cell(&[0f64, 0f64]).unwrap());
+        assert_eq!(1, grid.get_cell(&[0.3, 4f64]).unwrap());
+        assert_eq!(7, grid.get_cell(&[1f64, 10f64]).unwrap());
+        assert_eq!(4, grid.get_cell(&[-5f64, 50f64]).unwrap());
+        assert_eq!(vec![0.375, 7.5], grid.get_centre(5).unwrap());
+        assert!(grid.get_cell(&[0.5]).is_err());
+        assert!(Tessellation::grid(vec![(0f64, 1f64)], vec![0]).is_err());
+
+        let mut rng = StdRng::from_seed(&[130usize][..]);
+        let centroidal = Tessellation::centroidal(vec![(0f64, 1f64), (0f64, 1f64)], 16, 2000, 50, &mut rng).unwrap();
+        assert_eq!(16, centroidal.get_cell_count());
+        for cell in 0..16 {
+            let centre = centroidal.get_centre(cell).unwrap();
+            assert!(centre.iter().all(|value| (0f64..=1f64).contains(value)));
+            assert_eq!(cell, centroidal.get_cell(&centre).unwrap());
+        }
+        assert!(Tessellation::centroidal(vec![(0f64, 1f64)], 10, 5, 10, &mut rng).is_err());
+    }
+
+    #[test]
+    fn map_elites_spreads_genetic_strings_over_lengths_and_operator_mixes() {
+        let mut rng = StdRng::from_seed(&[131usize][..]);
+        let population: Vec<Expression> = (0..20).map(|_| GeneticString::new(7, &mut rng).unwrap()).collect();
+        let tessellation = Tessellation::grid(vec![(1f64, 21f64), (0f64, 1f64)], vec![10, 5]).unwrap();
+        let mut map_elites = MapElites::new(population, tessellation, TargetFitness::new(100f64), length_and_operator_mix)
+            .unwrap();
+        map_elites.set_crossover_probability(0.5).unwrap();
+        let initial_coverage = {
+            map_elites.get_statistics(&mut rng).unwrap();
+            map_elites.get_coverage()
+        };
+        let stats = map_elites.run(&mut MaxGenerations::new(100), &mut rng).unwrap();
+        assert_eq!(100, stats.get_generation());
+        assert_eq!(20 * 101, map_elites.get_evaluations());
+        assert!(map_elites.get_coverage() > initial_coverage);
+        let mut qd_score = 0f64;
+        for (cell, elite) in map_elites.get_archive().iter().enumerate() {
+            if let Some(ref elite) = *elite {
+                assert_eq!(cell, map_elites.get_tessellation().get_cell(elite.get_descriptor()).unwrap());
+                assert_eq!(length_and_operator_mix(elite.get_genome()).unwrap(), elite.get_descriptor());
+                qd_score += elite.get_fitness();
+            }
+        }
+        assert_eq!(qd_score, map_elites.get_qd_score());
+        assert_eq!(stats.get_best_fitness(), map_elites.get_best().unwrap().get_fitness());
+    }
+
+    #[test]
+    fn cvt_map_elites_fills_its_cells_and_exports_the_archive() {
+        let mut rng = StdRng::from_seed(&[132usize][..]);
+        let population: Vec<BitString> = (0..10).map(|_| BitString::new_random(16, &mut rng)).collect();
+        let tessellation = Tessellation::centroidal(vec![(0f64, 8f64), (0f64, 8f64)], 20, 1000, 30, &mut rng).unwrap();
+        let mut map_elites = MapElites::new(population, tessellation, alternating, ones_in_each_half).unwrap();
+        map_elites.set_batch_size(50).unwrap();
+        assert!(map_elites.set_batch_size(0).is_err());
+        assert!(map_elites.set_crossover_probability(1.5).is_err());
+        map_elites.run(&mut MaxGenerations::new(60), &mut rng).unwrap();
+        assert!(map_elites.get_coverage() >= 0.9);
+
+        let csv = map_elites.export();
+        let mut lines = csv.lines();
+        assert_eq!(Some("cell,fitness,descriptor_0,descriptor_1,genome"), lines.next());
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!((map_elites.get_coverage() * 20f64).round() as usize, rows.len());
+        for row in rows {
+            let fields: Vec<&str> = row.split(',').collect();
+            let cell: usize = fields[0].parse().unwrap();
+            let elite = map_elites.get_archive()[cell].as_ref().unwrap();
+            assert_eq!(elite.get_fitness(), fields[1].parse::<f64>().unwrap());
+            assert_eq!(format!("\"{}\"", elite.get_genome()), fields[4]);
+        }
+    }
+}
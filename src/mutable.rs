@@ -8,5 +8,5 @@ pub trait Mutable {
     ///
     /// # Returns
     /// Mutation error if an error occurs
-    fn mutate(&mut self, &mut StdRng)-> GeneticResult<()>;
+    fn mutate(&mut self, rng: &mut StdRng)-> GeneticResult<()>;
 }
\ No newline at end of file
@@ -0,0 +1,958 @@
+//! Neuroevolution of augmenting topologies (NEAT).
+//!
+//! A genome is a list of node genes and connection genes. Every structural change is given a
+//! global innovation number by the `InnovationHistory` shared through the `NeatConfig`, so
+//! genes with the same origin line up in crossover and in the compatibility distance that
+//! divides the population into species. Networks start minimal, with every input connected to
+//! every output, and grow through add-node and add-connection mutations.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use rand::distributions::normal::StandardNormal;
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::individual::Individual;
+use ::interfaces::distance_metric::DistanceMetric;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::interfaces::selection_fitness::SelectionFitness;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// How often an add-connection mutation looks for a pair of nodes that can be connected.
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+/// How far the speciation threshold moves each generation when aiming at a species count.
+const THRESHOLD_STEP: f64 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Activation {
+    /// The steepened sigmoid of the original NEAT, `1 / (1 + e^(-4.9x))`.
+    Sigmoid,
+    Tanh,
+    Relu,
+    Identity
+}
+
+impl Activation {
+    pub fn apply(&self, x: f64) -> f64 {
+        match *self {
+            Activation::Sigmoid => 1f64 / (1f64 + (-4.9f64 * x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0f64),
+            Activation::Identity => x
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Input,
+    /// Always outputs 1.
+    Bias,
+    Output,
+    Hidden
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeGene {
+    id: usize,
+    kind: NodeKind,
+    activation: Activation
+}
+
+impl NodeGene {
+    pub fn new(id: usize, kind: NodeKind, activation: Activation) -> NodeGene {
+        NodeGene { id, kind, activation }
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    pub fn get_activation(&self) -> Activation {
+        self.activation
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectionGene {
+    innovation: usize,
+    input: usize,
+    output: usize,
+    weight: f64,
+    enabled: bool
+}
+
+impl ConnectionGene {
+    pub fn new(innovation: usize, input: usize, output: usize, weight: f64, enabled: bool) -> ConnectionGene {
+        ConnectionGene { innovation, input, output, weight, enabled }
+    }
+
+    pub fn get_innovation(&self) -> usize {
+        self.innovation
+    }
+
+    /// The ids of the nodes the connection leads from and to.
+    pub fn get_nodes(&self) -> (usize, usize) {
+        (self.input, self.output)
+    }
+
+    pub fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl PartialEq for ConnectionGene {
+    fn eq(&self, other: &ConnectionGene) -> bool {
+        self.innovation == other.innovation && self.input == other.input && self.output == other.output
+            && self.weight.to_bits() == other.weight.to_bits() && self.enabled == other.enabled
+    }
+}
+
+impl Eq for ConnectionGene {}
+
+impl Hash for ConnectionGene {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.innovation.hash(state);
+        self.input.hash(state);
+        self.output.hash(state);
+        self.weight.to_bits().hash(state);
+        self.enabled.hash(state);
+    }
+}
+
+/// Hands out innovation numbers and node ids, giving the same structural change the same
+/// numbers wherever in the population it happens.
+#[derive(Debug)]
+pub struct InnovationHistory {
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>,
+    next_innovation: usize,
+    next_node: usize
+}
+
+impl InnovationHistory {
+    fn new(next_node: usize) -> InnovationHistory {
+        InnovationHistory { connections: HashMap::new(), splits: HashMap::new(), next_innovation: 0, next_node }
+    }
+
+    /// The innovation number of the connection between two nodes.
+    pub fn connection_innovation(&mut self, input: usize, output: usize) -> usize {
+        let next_innovation = &mut self.next_innovation;
+        *self.connections.entry((input, output)).or_insert_with(|| {
+            *next_innovation += 1;
+            *next_innovation - 1
+        })
+    }
+
+    /// The id of the node that splits the connection with the given innovation number.
+    pub fn split_node(&mut self, innovation: usize) -> usize {
+        let next_node = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node += 1;
+            *next_node - 1
+        })
+    }
+
+    fn new_node(&mut self) -> usize {
+        self.next_node += 1;
+        self.next_node - 1
+    }
+
+    pub fn get_innovation_count(&self) -> usize {
+        self.next_innovation
+    }
+}
+
+/// The settings and innovation history shared by a population of genomes.
+#[derive(Debug)]
+pub struct NeatConfig {
+    inputs: usize,
+    outputs: usize,
+    activation: Activation,
+    weight_mutation_rate: f64,
+    weight_perturbation_probability: f64,
+    weight_perturbation_power: f64,
+    add_connection_rate: f64,
+    add_node_rate: f64,
+    disabled_inheritance_probability: f64,
+    allow_recurrent: bool,
+    excess_coefficient: f64,
+    disjoint_coefficient: f64,
+    weight_coefficient: f64,
+    history: Mutex<InnovationHistory>
+}
+
+impl NeatConfig {
+    /// Creates a configuration with the settings of the original NEAT: weights mutate in 80%
+    /// of offspring, nodes are added to 3% and connections to 5%, and networks are
+    /// feed-forward with sigmoid activations.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The configuration if successful.</li>
+    /// <li>InvalidParameterError if there are no outputs.</li>
+    /// </ul>
+    pub fn new(inputs: usize, outputs: usize) -> GeneticResult<NeatConfig> {
+        if outputs == 0 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(NeatConfig {
+            inputs,
+            outputs,
+            activation: Activation::Sigmoid,
+            weight_mutation_rate: 0.8f64,
+            weight_perturbation_probability: 0.9f64,
+            weight_perturbation_power: 0.5f64,
+            add_connection_rate: 0.05f64,
+            add_node_rate: 0.03f64,
+            disabled_inheritance_probability: 0.75f64,
+            allow_recurrent: false,
+            excess_coefficient: 1f64,
+            disjoint_coefficient: 1f64,
+            weight_coefficient: 0.4f64,
+            // Inputs, then the bias, then the outputs.
+            history: Mutex::new(InnovationHistory::new(inputs + 1 + outputs))
+        })
+    }
+
+    pub fn get_inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn get_outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Sets the activation of new hidden and output nodes.
+    pub fn set_activation(&mut self, activation: Activation) {
+        self.activation = activation;
+    }
+
+    /// # Parameters
+    /// <ul>
+    /// <li>rate - the chance that an offspring has its weights mutated</li>
+    /// <li>perturbation_probability - the chance that a weight is nudged rather than
+    /// replaced</li>
+    /// <li>power - the standard deviation of a nudge</li>
+    /// </ul>
+    pub fn set_weight_mutation(&mut self, rate: f64, perturbation_probability: f64, power: f64) -> GeneticResult<()> {
+        check_probability(rate)?;
+        check_probability(perturbation_probability)?;
+        self.weight_mutation_rate = rate;
+        self.weight_perturbation_probability = perturbation_probability;
+        self.weight_perturbation_power = power;
+        Ok(())
+    }
+
+    pub fn set_structural_mutation_rates(&mut self, add_connection_rate: f64, add_node_rate: f64) -> GeneticResult<()> {
+        check_probability(add_connection_rate)?;
+        check_probability(add_node_rate)?;
+        self.add_connection_rate = add_connection_rate;
+        self.add_node_rate = add_node_rate;
+        Ok(())
+    }
+
+    /// Sets the chance that a gene disabled in either parent is disabled in the child.
+    pub fn set_disabled_inheritance_probability(&mut self, probability: f64) -> GeneticResult<()> {
+        check_probability(probability)?;
+        self.disabled_inheritance_probability = probability;
+        Ok(())
+    }
+
+    /// Lets add-connection mutations create cycles, which only `RecurrentNetwork` can run.
+    pub fn set_allow_recurrent(&mut self, allow_recurrent: bool) {
+        self.allow_recurrent = allow_recurrent;
+    }
+
+    /// Sets the weights of excess genes, disjoint genes and the mean weight difference of
+    /// matching genes in the compatibility distance.
+    pub fn set_compatibility_coefficients(&mut self, excess: f64, disjoint: f64, weight: f64) {
+        self.excess_coefficient = excess;
+        self.disjoint_coefficient = disjoint;
+        self.weight_coefficient = weight;
+    }
+
+    pub fn get_history(&self) -> &Mutex<InnovationHistory> {
+        &self.history
+    }
+
+    fn history(&self) -> ::std::sync::MutexGuard<'_, InnovationHistory> {
+        // The history stays consistent even if another thread panicked while holding it.
+        self.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn check_probability(probability: f64) -> GeneticResult<()> {
+    if (0f64..=1f64).contains(&probability) {
+        Ok(())
+    } else {
+        Err(GeneticError::InvalidParameterError)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NeatGenome {
+    nodes: Vec<NodeGene>,
+    connections: Vec<ConnectionGene>,
+    config: Arc<NeatConfig>
+}
+
+impl GeneticCollection for NeatGenome {
+    type Child = NeatGenome;
+
+    /// Breeds a child by crossover, treating self as the fitter parent, followed by mutation.
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.crossover(mate, rng);
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.connections.len()
+    }
+}
+
+impl Mutable for NeatGenome {
+    /// Mutates the weights, adds a connection and adds a node, each with its own rate.
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        if rng.gen::<f64>() < self.config.weight_mutation_rate {
+            self.mutate_weights(rng);
+        }
+        if rng.gen::<f64>() < self.config.add_connection_rate {
+            self.add_connection(rng);
+        }
+        if rng.gen::<f64>() < self.config.add_node_rate {
+            self.add_node(rng);
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for NeatGenome {
+    fn eq(&self, other: &NeatGenome) -> bool {
+        self.nodes == other.nodes && self.connections == other.connections
+    }
+}
+
+impl Eq for NeatGenome {}
+
+impl Hash for NeatGenome {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nodes.hash(state);
+        self.connections.hash(state);
+    }
+}
+
+impl Display for NeatGenome {
+    /// Writes one connection per line, e.g. `[3] 0 -> 4 (0.5)`, marking disabled ones.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, connection) in self.connections.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "[{}] {} -> {} ({})", connection.innovation, connection.input, connection.output,
+                   connection.weight)?;
+            if !connection.enabled {
+                write!(f, " disabled")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl NeatGenome {
+    /// Creates a minimal genome, with the inputs and the bias connected to every output by
+    /// random weights drawn from a standard normal distribution.
+    pub fn new(config: Arc<NeatConfig>, rng: &mut StdRng) -> NeatGenome {
+        let (inputs, outputs) = (config.inputs, config.outputs);
+        let mut nodes: Vec<NodeGene> = (0..inputs)
+            .map(|id| NodeGene::new(id, NodeKind::Input, Activation::Identity))
+            .collect();
+        nodes.push(NodeGene::new(inputs, NodeKind::Bias, Activation::Identity));
+        nodes.extend((inputs + 1..inputs + 1 + outputs).map(|id| NodeGene::new(id, NodeKind::Output, config.activation)));
+        let mut connections = vec!();
+        for output in inputs + 1..inputs + 1 + outputs {
+            for input in 0..inputs + 1 {
+                let StandardNormal(weight) = rng.gen();
+                let innovation = config.history().connection_innovation(input, output);
+                connections.push(ConnectionGene::new(innovation, input, output, weight, true));
+            }
+        }
+        connections.sort_by_key(|connection| connection.innovation);
+        NeatGenome { nodes, connections, config }
+    }
+
+    /// Creates a genome from its genes.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The genome if successful.</li>
+    /// <li>InvalidParameterError if the input, bias and output nodes don't match the
+    /// configuration, node ids repeat, a connection uses an unknown node or leads into an
+    /// input or the bias, or two connections share an innovation number.</li>
+    /// </ul>
+    pub fn from_genes(mut nodes: Vec<NodeGene>, mut connections: Vec<ConnectionGene>,
+                      config: Arc<NeatConfig>) -> GeneticResult<NeatGenome> {
+        nodes.sort_by_key(|node| node.id);
+        connections.sort_by_key(|connection| connection.innovation);
+        let count = |kind| nodes.iter().filter(|node| node.kind == kind).count();
+        let kinds: HashMap<usize, NodeKind> = nodes.iter().map(|node| (node.id, node.kind)).collect();
+        let valid = count(NodeKind::Input) == config.inputs && count(NodeKind::Bias) == 1
+            && count(NodeKind::Output) == config.outputs && kinds.len() == nodes.len()
+            && connections.windows(2).all(|pair| pair[0].innovation != pair[1].innovation)
+            && connections.iter().all(|connection| kinds.contains_key(&connection.input)
+                && matches!(kinds.get(&connection.output), Some(&NodeKind::Output) | Some(&NodeKind::Hidden)));
+        if !valid {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(NeatGenome { nodes, connections, config })
+    }
+
+    pub fn get_nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    /// The connection genes, ordered by innovation number.
+    pub fn get_connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    pub fn get_config(&self) -> &Arc<NeatConfig> {
+        &self.config
+    }
+
+    /// Nudges every weight with gaussian noise or, now and then, replaces it outright.
+    pub fn mutate_weights(&mut self, rng: &mut StdRng) {
+        for connection in &mut self.connections {
+            let StandardNormal(noise) = rng.gen();
+            if rng.gen::<f64>() < self.config.weight_perturbation_probability {
+                connection.weight += noise * self.config.weight_perturbation_power;
+            } else {
+                connection.weight = noise;
+            }
+        }
+    }
+
+    /// Connects two unconnected nodes with a random weight. Unless recurrent networks are
+    /// allowed, connections that would close a cycle are never added. Nothing changes if no
+    /// suitable pair of nodes turns up.
+    pub fn add_connection(&mut self, rng: &mut StdRng) {
+        let existing: HashSet<(usize, usize)> = self.connections.iter()
+            .map(|connection| (connection.input, connection.output))
+            .collect();
+        let targets: Vec<usize> = self.nodes.iter()
+            .filter(|node| node.kind == NodeKind::Output || node.kind == NodeKind::Hidden)
+            .map(|node| node.id)
+            .collect();
+        for _ in 0..ADD_CONNECTION_ATTEMPTS {
+            let input = self.nodes[rng.gen_range(0, self.nodes.len())].id;
+            let output = targets[rng.gen_range(0, targets.len())];
+            if existing.contains(&(input, output))
+                || (!self.config.allow_recurrent && (input == output || self.reaches(output, input))) {
+                continue;
+            }
+            let StandardNormal(weight) = rng.gen();
+            let innovation = self.config.history().connection_innovation(input, output);
+            self.insert_connection(ConnectionGene::new(innovation, input, output, weight, true));
+            return;
+        }
+    }
+
+    /// Splits a random enabled connection in two with a new hidden node. The connection into
+    /// the node gets weight 1 and the one out of it the old weight, so the network behaves
+    /// much as before.
+    pub fn add_node(&mut self, rng: &mut StdRng) {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let index = enabled[rng.gen_range(0, enabled.len())];
+        self.connections[index].enabled = false;
+        let split = self.connections[index];
+        let (node, first, second) = {
+            let mut history = self.config.history();
+            let mut node = history.split_node(split.innovation);
+            if self.nodes.iter().any(|existing| existing.id == node) {
+                // This genome already split the connection once before.
+                node = history.new_node();
+            }
+            (node, history.connection_innovation(split.input, node), history.connection_innovation(node, split.output))
+        };
+        let position = self.nodes.iter().position(|existing| existing.id > node).unwrap_or(self.nodes.len());
+        self.nodes.insert(position, NodeGene::new(node, NodeKind::Hidden, self.config.activation));
+        self.insert_connection(ConnectionGene::new(first, split.input, node, 1f64, true));
+        self.insert_connection(ConnectionGene::new(second, node, split.output, split.weight, true));
+    }
+
+    /// Lines the connection genes of both parents up by innovation number. Matching genes are
+    /// inherited from either parent at random, while disjoint and excess genes come from self,
+    /// which is taken to be the fitter parent. A gene disabled in either parent is likely to
+    /// stay disabled.
+    pub fn crossover(&self, mate: &NeatGenome, rng: &mut StdRng) -> NeatGenome {
+        let mate_genes: HashMap<usize, &ConnectionGene> = mate.connections.iter()
+            .map(|connection| (connection.innovation, connection))
+            .collect();
+        let mut child = self.clone();
+        for gene in &mut child.connections {
+            if let Some(other) = mate_genes.get(&gene.innovation) {
+                let either_disabled = !gene.enabled || !other.enabled;
+                if rng.gen() {
+                    gene.weight = other.weight;
+                }
+                gene.enabled = !either_disabled || rng.gen::<f64>() >= self.config.disabled_inheritance_probability;
+            }
+        }
+        child
+    }
+
+    /// The NEAT compatibility distance `c1 * E / N + c2 * D / N + c3 * W`, where E and D count
+    /// the excess and disjoint genes, N is the number of genes of the larger genome and W is
+    /// the mean weight difference of the matching genes.
+    pub fn compatibility_distance(&self, other: &NeatGenome) -> f64 {
+        let own_last = self.connections.last().map_or(0, |connection| connection.innovation);
+        let other_last = other.connections.last().map_or(0, |connection| connection.innovation);
+        let (mut own, mut theirs) = (self.connections.iter().peekable(), other.connections.iter().peekable());
+        let (mut excess, mut disjoint, mut matching, mut weight_difference) = (0f64, 0f64, 0f64, 0f64);
+        loop {
+            let (a, b) = match (own.peek(), theirs.peek()) {
+                (None, None) => break,
+                (a, b) => (a.map(|gene| gene.innovation), b.map(|gene| gene.innovation))
+            };
+            let (take_own, take_theirs) = match (a, b) {
+                (Some(a), Some(b)) if a == b => (true, true),
+                (Some(a), Some(b)) => (a < b, b < a),
+                (Some(_), None) => (true, false),
+                _ => (false, true)
+            };
+            if take_own && take_theirs {
+                let (a, b) = (own.next().map_or(0f64, |gene| gene.weight), theirs.next().map_or(0f64, |gene| gene.weight));
+                matching += 1f64;
+                weight_difference += (a - b).abs();
+            } else if take_own {
+                let innovation = own.next().map_or(0, |gene| gene.innovation);
+                if innovation > other_last { excess += 1f64 } else { disjoint += 1f64 }
+            } else {
+                let innovation = theirs.next().map_or(0, |gene| gene.innovation);
+                if innovation > own_last { excess += 1f64 } else { disjoint += 1f64 }
+            }
+        }
+        let genes = self.connections.len().max(other.connections.len()).max(1) as f64;
+        let mean_difference = if matching > 0f64 { weight_difference / matching } else { 0f64 };
+        self.config.excess_coefficient * excess / genes + self.config.disjoint_coefficient * disjoint / genes
+            + self.config.weight_coefficient * mean_difference
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let position = self.connections.iter()
+            .position(|existing| existing.innovation > connection.innovation)
+            .unwrap_or(self.connections.len());
+        self.connections.insert(position, connection);
+    }
+
+    /// Whether a path of connections, enabled or not, leads from one node to the other.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = vec![from];
+        while let Some(node) = pending.pop() {
+            if node == to {
+                return true;
+            }
+            if visited.insert(node) {
+                pending.extend(self.connections.iter()
+                    .filter(|connection| connection.input == node)
+                    .map(|connection| connection.output));
+            }
+        }
+        false
+    }
+}
+
+/// The compatibility distance as a `DistanceMetric`, e.g. for `niching::FitnessSharing`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompatibilityDistance;
+
+impl DistanceMetric<NeatGenome> for CompatibilityDistance {
+    fn distance(&self, a: &NeatGenome, b: &NeatGenome) -> GeneticResult<f64> {
+        Ok(a.compatibility_distance(b))
+    }
+}
+
+/// The incoming connections of a node, as indices into the node values and weights.
+type Inputs = Vec<(usize, f64)>;
+
+/// Maps the genome's nodes to value indices and gathers the enabled incoming connections of
+/// every node.
+fn wire(genome: &NeatGenome) -> (HashMap<usize, usize>, Vec<Inputs>) {
+    let indices: HashMap<usize, usize> = genome.nodes.iter().enumerate().map(|(index, node)| (node.id, index)).collect();
+    let mut incoming = vec![vec!(); genome.nodes.len()];
+    for connection in genome.connections.iter().filter(|connection| connection.enabled) {
+        incoming[indices[&connection.output]].push((indices[&connection.input], connection.weight));
+    }
+    (indices, incoming)
+}
+
+fn nodes_of_kind(genome: &NeatGenome, kind: NodeKind) -> Vec<usize> {
+    (0..genome.nodes.len()).filter(|&index| genome.nodes[index].kind == kind).collect()
+}
+
+/// Evaluates a genome without cycles in a single pass, in topological order.
+#[derive(Clone, Debug)]
+pub struct FeedForwardNetwork {
+    node_count: usize,
+    inputs: Vec<usize>,
+    bias: Vec<usize>,
+    outputs: Vec<usize>,
+    /// Every hidden and output node in an order where its inputs come first.
+    order: Vec<(usize, Activation, Inputs)>
+}
+
+impl FeedForwardNetwork {
+    /// # Returns
+    /// <ul>
+    /// <li>The network if successful.</li>
+    /// <li>InvalidParameterError if the enabled connections form a cycle.</li>
+    /// </ul>
+    pub fn new(genome: &NeatGenome) -> GeneticResult<FeedForwardNetwork> {
+        let (_, incoming) = wire(genome);
+        let mut done: Vec<bool> = genome.nodes.iter()
+            .map(|node| node.kind == NodeKind::Input || node.kind == NodeKind::Bias)
+            .collect();
+        let mut order = vec!();
+        let mut remaining: Vec<usize> = (0..done.len()).filter(|&index| !done[index]).collect();
+        while !remaining.is_empty() {
+            let (ready, blocked): (Vec<usize>, Vec<usize>) = remaining.into_iter()
+                .partition(|&index| incoming[index].iter().all(|&(source, _)| done[source]));
+            if ready.is_empty() {
+                return Err(GeneticError::InvalidParameterError);
+            }
+            for index in ready {
+                done[index] = true;
+                order.push((index, genome.nodes[index].activation, incoming[index].clone()));
+            }
+            remaining = blocked;
+        }
+        Ok(FeedForwardNetwork {
+            node_count: genome.nodes.len(),
+            inputs: nodes_of_kind(genome, NodeKind::Input),
+            bias: nodes_of_kind(genome, NodeKind::Bias),
+            outputs: nodes_of_kind(genome, NodeKind::Output),
+            order
+        })
+    }
+
+    /// # Returns
+    /// <ul>
+    /// <li>The value of every output if successful.</li>
+    /// <li>InvalidParameterError if the number of inputs doesn't match the network.</li>
+    /// </ul>
+    pub fn activate(&self, inputs: &[f64]) -> GeneticResult<Vec<f64>> {
+        if inputs.len() != self.inputs.len() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mut values = vec![0f64; self.node_count];
+        for (&node, &input) in self.inputs.iter().zip(inputs) {
+            values[node] = input;
+        }
+        for &bias in &self.bias {
+            values[bias] = 1f64;
+        }
+        for &(node, activation, ref sources) in &self.order {
+            let sum: f64 = sources.iter().map(|&(source, weight)| values[source] * weight).sum();
+            values[node] = activation.apply(sum);
+        }
+        Ok(self.outputs.iter().map(|&output| values[output]).collect())
+    }
+}
+
+/// Evaluates any genome, cycles included, one step at a time: each activation updates every
+/// node at once from the values of the previous step, so a signal needs as many steps as the
+/// network is deep to reach the outputs.
+#[derive(Clone, Debug)]
+pub struct RecurrentNetwork {
+    inputs: Vec<usize>,
+    bias: Vec<usize>,
+    outputs: Vec<usize>,
+    activations: Vec<Activation>,
+    incoming: Vec<Inputs>,
+    values: Vec<f64>
+}
+
+impl RecurrentNetwork {
+    pub fn new(genome: &NeatGenome) -> RecurrentNetwork {
+        let (_, incoming) = wire(genome);
+        RecurrentNetwork {
+            inputs: nodes_of_kind(genome, NodeKind::Input),
+            bias: nodes_of_kind(genome, NodeKind::Bias),
+            outputs: nodes_of_kind(genome, NodeKind::Output),
+            activations: genome.nodes.iter().map(|node| node.activation).collect(),
+            values: vec![0f64; genome.nodes.len()],
+            incoming
+        }
+    }
+
+    /// Advances the network by one step.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The value of every output after the step if successful.</li>
+    /// <li>InvalidParameterError if the number of inputs doesn't match the network.</li>
+    /// </ul>
+    pub fn activate(&mut self, inputs: &[f64]) -> GeneticResult<Vec<f64>> {
+        if inputs.len() != self.inputs.len() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        for (&node, &input) in self.inputs.iter().zip(inputs) {
+            self.values[node] = input;
+        }
+        for &bias in &self.bias {
+            self.values[bias] = 1f64;
+        }
+        let next: Vec<f64> = (0..self.values.len()).map(|node| {
+            if self.incoming[node].is_empty() && (self.inputs.contains(&node) || self.bias.contains(&node)) {
+                self.values[node]
+            } else {
+                let sum: f64 = self.incoming[node].iter().map(|&(source, weight)| self.values[source] * weight).sum();
+                self.activations[node].apply(sum)
+            }
+        }).collect();
+        self.values = next;
+        Ok(self.outputs.iter().map(|&output| self.values[output]).collect())
+    }
+
+    /// Clears the state left by earlier steps.
+    pub fn reset(&mut self) {
+        for value in &mut self.values {
+            *value = 0f64;
+        }
+    }
+}
+
+/// Divides the population into species by compatibility distance and shares fitness within
+/// each species, so new structure has time to optimise its weights before it has to compete
+/// with the whole population. Fitness must be non-negative.
+#[derive(Clone, Debug)]
+pub struct Speciation {
+    threshold: f64,
+    target_species: Option<usize>,
+    representatives: Vec<NeatGenome>,
+    species: Vec<Vec<usize>>
+}
+
+impl Speciation {
+    /// # Parameters
+    /// threshold - genomes closer than this to a species' representative join that species
+    pub fn new(threshold: f64) -> Speciation {
+        Speciation { threshold, target_species: None, representatives: vec!(), species: vec!() }
+    }
+
+    /// Makes the threshold rise or fall each generation until there are about the given
+    /// number of species.
+    pub fn set_target_species(&mut self, target_species: usize) {
+        self.target_species = Some(target_species);
+    }
+
+    pub fn get_threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// The members of each species, as indices into the population last scored.
+    pub fn get_species(&self) -> &[Vec<usize>] {
+        &self.species
+    }
+}
+
+impl SelectionFitness<NeatGenome> for Speciation {
+    fn selection_fitness(&mut self, population: &[Individual<NeatGenome>]) -> GeneticResult<Vec<f64>> {
+        let mut species: Vec<Vec<usize>> = vec![vec!(); self.representatives.len()];
+        for (index, individual) in population.iter().enumerate() {
+            let genome = individual.get_genome();
+            match self.representatives.iter().position(|representative| genome.compatibility_distance(representative) < self.threshold) {
+                Some(existing) => species[existing].push(index),
+                None => {
+                    self.representatives.push(genome.clone());
+                    species.push(vec![index]);
+                }
+            }
+        }
+        species.retain(|members| !members.is_empty());
+        // Each surviving species is represented by its first member in the next generation.
+        self.representatives = species.iter().map(|members| population[members[0]].get_genome().clone()).collect();
+        if let Some(target) = self.target_species {
+            if species.len() < target {
+                self.threshold = (self.threshold - THRESHOLD_STEP).max(THRESHOLD_STEP);
+            } else if species.len() > target {
+                self.threshold += THRESHOLD_STEP;
+            }
+        }
+        let mut scores = vec![0f64; population.len()];
+        for members in &species {
+            for &member in members {
+                scores[member] = population[member].get_fitness() / members.len() as f64;
+            }
+        }
+        self.species = species;
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::genetic_algorithm::GeneticAlgorithm;
+    use ::interfaces::optimizer::Optimizer;
+    use ::interfaces::replacement::Replacement;
+
+    fn config(inputs: usize, outputs: usize) -> Arc<NeatConfig> {
+        Arc::new(NeatConfig::new(inputs, outputs).unwrap())
+    }
+
+    #[test]
+    fn feed_forward_network_computes_hand_built_genome() {
+        let config = config(2, 1);
+        // Inputs 0 and 1, bias 2, output 3, hidden 4.
+        let nodes = vec![NodeGene::new(0, NodeKind::Input, Activation::Identity),
+                         NodeGene::new(1, NodeKind::Input, Activation::Identity),
+                         NodeGene::new(2, NodeKind::Bias, Activation::Identity),
+                         NodeGene::new(3, NodeKind::Output, Activation::Identity),
+                         NodeGene::new(4, NodeKind::Hidden, Activation::Relu)];
+        let connections = vec![ConnectionGene::new(0, 0, 4, 1f64, true),
+                               ConnectionGene::new(1, 1, 4, -1f64, true),
+                               ConnectionGene::new(2, 4, 3, 2f64, true),
+                               ConnectionGene::new(3, 2, 3, 0.5f64, true),
+                               ConnectionGene::new(4, 0, 3, 100f64, false)];
+        let genome = NeatGenome::from_genes(nodes, connections, config).unwrap();
+        let network = FeedForwardNetwork::new(&genome).unwrap();
+        assert_eq!(vec![6.5f64], network.activate(&[5f64, 2f64]).unwrap());
+        assert_eq!(vec![0.5f64], network.activate(&[2f64, 5f64]).unwrap());
+        let mut recurrent = RecurrentNetwork::new(&genome);
+        recurrent.activate(&[5f64, 2f64]).unwrap();
+        // The hidden node needs a second step to reach the output.
+        assert_eq!(vec![6.5f64], recurrent.activate(&[5f64, 2f64]).unwrap());
+    }
+
+    #[test]
+    fn the_same_split_gets_the_same_innovation_numbers_in_different_genomes() {
+        let mut rng = StdRng::from_seed(&[25usize][..]);
+        let config = config(1, 1);
+        let mut first = NeatGenome::new(config.clone(), &mut rng);
+        let mut second = NeatGenome::new(config, &mut rng);
+        // Leave the input to output connection as the only one to split.
+        first.connections[1].enabled = false;
+        second.connections[1].enabled = false;
+        let weight = first.get_connections()[0].get_weight();
+        first.add_node(&mut rng);
+        second.add_node(&mut rng);
+        let innovations = |genome: &NeatGenome| genome.get_connections().iter()
+            .map(|gene| (gene.get_innovation(), gene.get_nodes()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(0, (0, 2)), (1, (1, 2)), (2, (0, 3)), (3, (3, 2))], innovations(&first));
+        assert_eq!(innovations(&first), innovations(&second));
+        assert!(!first.get_connections()[0].is_enabled());
+        assert_eq!(1f64, first.get_connections()[2].get_weight());
+        assert_eq!(weight, first.get_connections()[3].get_weight());
+    }
+
+    #[test]
+    fn crossover_takes_structure_from_the_fitter_parent() {
+        let mut rng = StdRng::from_seed(&[26usize][..]);
+        let config = config(2, 1);
+        let simple = NeatGenome::new(config.clone(), &mut rng);
+        let mut complex = simple.clone();
+        complex.add_node(&mut rng);
+        complex.add_connection(&mut rng);
+        complex.mutate_weights(&mut rng);
+        let child = simple.crossover(&complex, &mut rng);
+        assert_eq!(simple.get_nodes(), child.get_nodes());
+        assert_eq!(simple.get_size(), child.get_size());
+        for (gene, parent_gene) in child.get_connections().iter().zip(simple.get_connections()) {
+            let other = complex.get_connections().iter().find(|other| other.get_innovation() == gene.get_innovation()).unwrap();
+            assert!(gene.get_weight() == parent_gene.get_weight() || gene.get_weight() == other.get_weight());
+        }
+        let child = complex.crossover(&simple, &mut rng);
+        assert_eq!(complex.get_nodes(), child.get_nodes());
+        assert!(FeedForwardNetwork::new(&child).is_ok());
+        assert!(simple.compatibility_distance(&complex) > 0f64);
+    }
+
+    #[test]
+    fn speciation_groups_similar_genomes_and_shares_their_fitness() {
+        let mut rng = StdRng::from_seed(&[27usize][..]);
+        let config = config(2, 1);
+        let base = NeatGenome::new(config, &mut rng);
+        let mut distant = base.clone();
+        for _ in 0..5 {
+            distant.add_node(&mut rng);
+        }
+        let population = vec![Individual::new(base.clone(), 3f64), Individual::new(base.clone(), 6f64),
+                              Individual::new(distant, 4f64)];
+        let mut speciation = Speciation::new(0.5f64);
+        let scores = speciation.selection_fitness(&population).unwrap();
+        assert_eq!(vec![vec![0, 1], vec![2]], speciation.get_species().to_vec());
+        assert_eq!(vec![1.5f64, 3f64, 4f64], scores);
+    }
+
+    #[test]
+    fn add_connection_never_closes_a_cycle_in_feed_forward_mode() {
+        let mut rng = StdRng::from_seed(&[28usize][..]);
+        let mut genome = NeatGenome::new(config(3, 2), &mut rng);
+        for _ in 0..30 {
+            genome.add_node(&mut rng);
+            genome.add_connection(&mut rng);
+            assert!(FeedForwardNetwork::new(&genome).is_ok());
+        }
+        assert!(NeatGenome::from_genes(genome.get_nodes().to_vec(), genome.get_connections().to_vec(),
+                                       genome.get_config().clone()).is_ok());
+    }
+
+    struct ParentCheck {
+        violations: Arc<Mutex<usize>>
+    }
+
+    impl Replacement<NeatGenome> for ParentCheck {
+        fn replace(&mut self, population: Vec<Individual<NeatGenome>>, offspring: Vec<Individual<NeatGenome>>,
+                   parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<NeatGenome>>> {
+            for (child, &(father, mother)) in offspring.iter().zip(parents) {
+                let fitter = population[father].get_fitness().max(population[mother].get_fitness());
+                if child.get_fitness() < fitter {
+                    *self.violations.lock().unwrap() += 1;
+                }
+            }
+            Ok(offspring)
+        }
+    }
+
+    #[test]
+    fn the_engine_breeds_neat_genomes_with_the_fitter_parent_as_self() {
+        let mut rng = StdRng::from_seed(&[29usize][..]);
+        let config = config(2, 1);
+        let population = (0..20).map(|_| NeatGenome::new(config.clone(), &mut rng)).collect();
+        // Crossover keeps every node of the fitter parent and mutation only adds nodes, so a
+        // child is never smaller than its fitter parent when the engine passes that one as self.
+        let node_count = |genome: &NeatGenome| Ok(genome.get_nodes().len() as f64);
+        let mut algorithm = GeneticAlgorithm::new(population, node_count).unwrap();
+        let violations = Arc::new(Mutex::new(0));
+        algorithm.set_replacement(Box::new(ParentCheck { violations: violations.clone() }));
+        let initial = algorithm.get_statistics(&mut rng).unwrap();
+        let mut last = initial.clone();
+        for _ in 0..15 {
+            last = algorithm.next_generation(&mut rng).unwrap();
+        }
+        assert_eq!(0, *violations.lock().unwrap());
+        assert!(last.get_best_fitness() > initial.get_best_fitness());
+    }
+}
@@ -0,0 +1,236 @@
+//! Niching operators that keep a population spread over several optima instead of letting
+//! it collapse onto one. Every operator works with any distance metric.
+//!
+//! Fitness sharing and clearing change the scores parents are selected by, while
+//! deterministic crowding and restricted tournament selection decide which offspring
+//! replace which members of the population.
+
+use rand::{Rng, StdRng};
+
+use ::individual::Individual;
+use ::interfaces::distance_metric::DistanceMetric;
+use ::interfaces::replacement::Replacement;
+use ::interfaces::selection_fitness::SelectionFitness;
+use ::GeneticResult;
+
+/// Divides the fitness of each individual by the number of individuals crowding its niche,
+/// so that no single niche can take over the population. Fitness must be non-negative.
+pub struct FitnessSharing<D> {
+    metric: D,
+    sharing_radius: f64,
+    alpha: f64
+}
+
+impl<D> FitnessSharing<D> {
+    /// # Parameters
+    /// <ul>
+    /// <li>metric - measures the distance between two genomes</li>
+    /// <li>sharing_radius - genomes closer than this share their fitness</li>
+    /// <li>alpha - shapes the sharing function, 1 gives a triangular sharing function</li>
+    /// </ul>
+    pub fn new(metric: D, sharing_radius: f64, alpha: f64) -> FitnessSharing<D> {
+        FitnessSharing { metric, sharing_radius, alpha }
+    }
+
+    fn share(&self, distance: f64) -> f64 {
+        if distance < self.sharing_radius {
+            1f64 - (distance / self.sharing_radius).powf(self.alpha)
+        } else {
+            0f64
+        }
+    }
+}
+
+impl<G, D> SelectionFitness<G> for FitnessSharing<D> where D: DistanceMetric<G> {
+    fn selection_fitness(&mut self, population: &[Individual<G>]) -> GeneticResult<Vec<f64>> {
+        let mut niche_counts = vec![0f64; population.len()];
+        for (i, a) in population.iter().enumerate() {
+            // An individual is always in its own niche.
+            niche_counts[i] += 1f64;
+            for (j, b) in population.iter().enumerate().skip(i + 1) {
+                let sharing = self.share(self.metric.distance(a.get_genome(), b.get_genome())?);
+                niche_counts[i] += sharing;
+                niche_counts[j] += sharing;
+            }
+        }
+        Ok(population.iter()
+            .zip(niche_counts)
+            .map(|(individual, niche_count)| individual.get_fitness() / niche_count)
+            .collect())
+    }
+}
+
+/// Lets only the fittest few individuals of each niche keep their fitness; everyone else in
+/// the niche is cleared to a fitness of 0.
+pub struct Clearing<D> {
+    metric: D,
+    clearing_radius: f64,
+    capacity: usize
+}
+
+impl<D> Clearing<D> {
+    /// # Parameters
+    /// <ul>
+    /// <li>metric - measures the distance between two genomes</li>
+    /// <li>clearing_radius - genomes closer than this to a niche winner belong to its niche</li>
+    /// <li>capacity - how many individuals of each niche keep their fitness</li>
+    /// </ul>
+    pub fn new(metric: D, clearing_radius: f64, capacity: usize) -> Clearing<D> {
+        Clearing { metric, clearing_radius, capacity }
+    }
+}
+
+impl<G, D> SelectionFitness<G> for Clearing<D> where D: DistanceMetric<G> {
+    fn selection_fitness(&mut self, population: &[Individual<G>]) -> GeneticResult<Vec<f64>> {
+        let mut ranked: Vec<usize> = (0..population.len()).collect();
+        ranked.sort_by(|&a, &b| population[b].get_fitness().partial_cmp(&population[a].get_fitness())
+            .unwrap_or(::std::cmp::Ordering::Equal));
+        let mut scores: Vec<f64> = population.iter().map(|individual| individual.get_fitness()).collect();
+        let mut cleared = vec![false; population.len()];
+        for (rank, &winner) in ranked.iter().enumerate() {
+            if cleared[winner] {
+                continue;
+            }
+            let mut niche_members = 1;
+            for &other in &ranked[rank + 1..] {
+                if cleared[other] {
+                    continue;
+                }
+                let distance = self.metric.distance(population[winner].get_genome(), population[other].get_genome())?;
+                if distance < self.clearing_radius {
+                    if niche_members < self.capacity {
+                        niche_members += 1;
+                    } else {
+                        cleared[other] = true;
+                        scores[other] = 0f64;
+                    }
+                }
+            }
+        }
+        Ok(scores)
+    }
+}
+
+/// Each offspring competes with whichever of its two parents it is closest to and takes its
+/// place if it is fitter.
+pub struct DeterministicCrowding<D> {
+    metric: D
+}
+
+impl<D> DeterministicCrowding<D> {
+    pub fn new(metric: D) -> DeterministicCrowding<D> {
+        DeterministicCrowding { metric }
+    }
+}
+
+impl<G, D> Replacement<G> for DeterministicCrowding<D> where D: DistanceMetric<G> {
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        for (child, &(father, mother)) in offspring.into_iter().zip(parents) {
+            let father_distance = self.metric.distance(child.get_genome(), population[father].get_genome())?;
+            let mother_distance = self.metric.distance(child.get_genome(), population[mother].get_genome())?;
+            let rival = if father_distance <= mother_distance { father } else { mother };
+            if child.get_fitness() > population[rival].get_fitness() {
+                population[rival] = child;
+            }
+        }
+        Ok(population)
+    }
+}
+
+/// Each offspring is compared with a random sample of the population and replaces the most
+/// similar member of the sample if it is fitter.
+pub struct RestrictedTournament<D> {
+    metric: D,
+    window_size: usize
+}
+
+impl<D> RestrictedTournament<D> {
+    /// # Parameters
+    /// <ul>
+    /// <li>metric - measures the distance between two genomes</li>
+    /// <li>window_size - how many members of the population each offspring is compared with</li>
+    /// </ul>
+    pub fn new(metric: D, window_size: usize) -> RestrictedTournament<D> {
+        RestrictedTournament { metric, window_size: window_size.max(1) }
+    }
+}
+
+impl<G, D> Replacement<G> for RestrictedTournament<D> where D: DistanceMetric<G> {
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        if population.is_empty() {
+            return Ok(population);
+        }
+        for child in offspring {
+            let mut closest = rng.gen_range(0, population.len());
+            let mut closest_distance = self.metric.distance(child.get_genome(), population[closest].get_genome())?;
+            for _ in 1..self.window_size {
+                let candidate = rng.gen_range(0, population.len());
+                let distance = self.metric.distance(child.get_genome(), population[candidate].get_genome())?;
+                if distance < closest_distance {
+                    closest = candidate;
+                    closest_distance = distance;
+                }
+            }
+            if child.get_fitness() > population[closest].get_fitness() {
+                population[closest] = child;
+            }
+        }
+        Ok(population)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn distance(a: &f64, b: &f64) -> GeneticResult<f64> {
+        Ok((a - b).abs())
+    }
+
+    fn individuals(genomes_and_fitness: &[(f64, f64)]) -> Vec<Individual<f64>> {
+        genomes_and_fitness.iter().map(|&(genome, fitness)| Individual::new(genome, fitness)).collect()
+    }
+
+    #[test]
+    fn crowded_niche_shares_its_fitness_and_lone_individual_keeps_its_own() {
+        let population = individuals(&[(0f64, 6f64), (0f64, 6f64), (0f64, 6f64), (10f64, 4f64)]);
+        let mut sharing = FitnessSharing::new(distance, 1f64, 1f64);
+        let scores = sharing.selection_fitness(&population).unwrap();
+        assert_eq!(vec![2f64, 2f64, 2f64, 4f64], scores);
+    }
+
+    #[test]
+    fn clearing_keeps_only_the_niche_winners() {
+        let population = individuals(&[(0f64, 5f64), (0.5f64, 7f64), (10f64, 1f64), (10.2f64, 2f64)]);
+        let mut clearing = Clearing::new(distance, 1f64, 1);
+        let scores = clearing.selection_fitness(&population).unwrap();
+        assert_eq!(vec![0f64, 7f64, 0f64, 2f64], scores);
+    }
+
+    #[test]
+    fn offspring_replaces_only_its_closest_parent_when_fitter() {
+        let mut rng = StdRng::from_seed(&[1usize][..]);
+        let population = individuals(&[(0f64, 1f64), (10f64, 1f64)]);
+        // The first child is closest to the second parent but less fit, the second is
+        // closest to the first parent and fitter.
+        let offspring = individuals(&[(8f64, 0.5f64), (1f64, 3f64)]);
+        let mut crowding = DeterministicCrowding::new(distance);
+        let next = crowding.replace(population, offspring, &[(0, 1), (0, 1)], &mut rng).unwrap();
+        assert_eq!(1f64, *next[0].get_genome());
+        assert_eq!(10f64, *next[1].get_genome());
+    }
+
+    #[test]
+    fn restricted_tournament_never_lowers_the_fitness_of_a_slot() {
+        let mut rng = StdRng::from_seed(&[2usize][..]);
+        let population = individuals(&[(0f64, 3f64), (5f64, 3f64), (10f64, 3f64)]);
+        let offspring = individuals(&[(4.5f64, 1f64), (10.5f64, 8f64)]);
+        let mut tournament = RestrictedTournament::new(distance, 3);
+        let next = tournament.replace(population, offspring, &[], &mut rng).unwrap();
+        assert_eq!(3, next.len());
+        assert!(next.iter().all(|individual| individual.get_fitness() >= 3f64));
+    }
+}
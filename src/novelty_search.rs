@@ -0,0 +1,292 @@
+//! Novelty search, which rewards genomes for behaving differently from those seen before
+//! instead of for being fit. On deceptive problems, where the fitness gradient leads away
+//! from the optimum, searching for novelty can find what objective search cannot.
+//!
+//! `NoveltySearch` replaces fitness in the scores parents are selected by, so it plugs into
+//! `GeneticAlgorithm::set_selection_fitness`. The fitness reported in the statistics is left
+//! untouched, and so is replacement: the engine still picks survivors and elites by their
+//! objective fitness, so the default `Generational(1)` keeps the fittest genome found while
+//! novelty only decides who breeds.
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use ::error::GeneticError;
+use ::individual::Individual;
+use ::interfaces::behaviour_descriptor::BehaviourDescriptor;
+use ::interfaces::selection_fitness::SelectionFitness;
+use ::GeneticResult;
+
+/// Which behaviours are added to the archive of past behaviours after each generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveInsertion {
+    /// Every behaviour whose novelty is above the threshold.
+    Threshold { threshold: f64 },
+    /// Each behaviour with the given probability, regardless of its novelty.
+    Random { probability: f64 }
+}
+
+/// Scores each individual by the mean Euclidean distance from its behaviour to the k nearest
+/// behaviours among the rest of the population and the archive.
+pub struct NoveltySearch<D> {
+    descriptor: D,
+    neighbours: usize,
+    insertion: ArchiveInsertion,
+    fitness_weight: f64,
+    archive: Vec<Vec<f64>>,
+    archive_capacity: Option<usize>,
+    rng: StdRng
+}
+
+impl<D> NoveltySearch<D> {
+    /// Creates a novelty search with an empty, unbounded archive that ignores fitness until a
+    /// fitness weight is set.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>descriptor - characterises the behaviour of a genome</li>
+    /// <li>neighbours - k, how many of the nearest behaviours the novelty is averaged over</li>
+    /// <li>insertion - which behaviours are archived</li>
+    /// <li>rng - seeds the generator used for random archive insertion</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The novelty search if successful.</li>
+    /// <li>InvalidParameterError if k is 0, the threshold is negative or the probability
+    /// isn't between 0 and 1.</li>
+    /// </ul>
+    pub fn new(descriptor: D, neighbours: usize, insertion: ArchiveInsertion, rng: &mut StdRng)
+               -> GeneticResult<NoveltySearch<D>> {
+        let valid = match insertion {
+            ArchiveInsertion::Threshold { threshold } => threshold >= 0f64,
+            ArchiveInsertion::Random { probability } => (0f64..=1f64).contains(&probability)
+        };
+        if neighbours == 0 || !valid {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(NoveltySearch {
+            descriptor,
+            neighbours,
+            insertion,
+            fitness_weight: 0f64,
+            archive: vec![],
+            archive_capacity: None,
+            rng: StdRng::from_seed(&[rng.gen::<usize>()][..])
+        })
+    }
+
+    /// Sets how much fitness counts next to novelty. With a weight w, each individual is
+    /// scored (1 - w) * novelty + w * fitness, both rescaled to lie between 0 and 1 over the
+    /// population. 0 is pure novelty search and is the default; 1 is pure objective search.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the weight isn't between 0 and 1.</li>
+    /// </ul>
+    pub fn set_fitness_weight(&mut self, fitness_weight: f64) -> GeneticResult<()> {
+        if !(0f64..=1f64).contains(&fitness_weight) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.fitness_weight = fitness_weight;
+        Ok(())
+    }
+
+    /// Limits the archive to the given number of behaviours, evicting the oldest once it is
+    /// full. Without a limit the archive only grows, and each generation compares every
+    /// behaviour against more of it than the last.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the capacity is 0.</li>
+    /// </ul>
+    pub fn set_archive_capacity(&mut self, capacity: usize) -> GeneticResult<()> {
+        if capacity == 0 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.archive_capacity = Some(capacity);
+        self.evict();
+        Ok(())
+    }
+
+    /// The behaviours archived so far, oldest first.
+    pub fn get_archive(&self) -> &[Vec<f64>] {
+        &self.archive
+    }
+
+    /// The novelty of a behaviour relative to the population, excluding the behaviour at the
+    /// given index, and the archive. 0 if there is nothing to compare it to.
+    fn novelty(&self, index: usize, behaviours: &[Vec<f64>]) -> f64 {
+        let mut distances: Vec<f64> = behaviours.iter().enumerate()
+            .filter(|&(other, _)| other != index)
+            .map(|(_, other)| other)
+            .chain(&self.archive)
+            .map(|other| euclidean_distance(&behaviours[index], other))
+            .collect();
+        if distances.is_empty() {
+            return 0f64;
+        }
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+        distances.truncate(self.neighbours);
+        distances.iter().sum::<f64>() / distances.len() as f64
+    }
+
+    /// Drops the oldest behaviours until the archive fits its capacity.
+    fn evict(&mut self) {
+        if let Some(capacity) = self.archive_capacity {
+            let excess = self.archive.len().saturating_sub(capacity);
+            self.archive.drain(..excess);
+        }
+    }
+}
+
+impl<G, D> SelectionFitness<G> for NoveltySearch<D> where D: BehaviourDescriptor<G> {
+    fn selection_fitness(&mut self, population: &[Individual<G>]) -> GeneticResult<Vec<f64>> {
+        let behaviours = population.iter()
+            .map(|individual| self.descriptor.describe(individual.get_genome()))
+            .collect::<GeneticResult<Vec<Vec<f64>>>>()?;
+        if behaviours.windows(2).any(|pair| pair[0].len() != pair[1].len())
+            || self.archive.first().is_some_and(|archived| behaviours.iter().any(|behaviour| behaviour.len() != archived.len())) {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        let novelty: Vec<f64> = (0..behaviours.len()).map(|index| self.novelty(index, &behaviours)).collect();
+        for (behaviour, &novelty) in behaviours.into_iter().zip(&novelty) {
+            let archived = match self.insertion {
+                ArchiveInsertion::Threshold { threshold } => novelty > threshold,
+                ArchiveInsertion::Random { probability } => self.rng.gen::<f64>() < probability
+            };
+            if archived {
+                self.archive.push(behaviour);
+            }
+        }
+        self.evict();
+        if self.fitness_weight == 0f64 {
+            return Ok(novelty);
+        }
+        let fitness: Vec<f64> = population.iter().map(|individual| individual.get_fitness()).collect();
+        Ok(rescale(&novelty).into_iter()
+            .zip(rescale(&fitness))
+            .map(|(novelty, fitness)| (1f64 - self.fitness_weight) * novelty + self.fitness_weight * fitness)
+            .collect())
+    }
+}
+
+fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt()
+}
+
+/// Maps the values linearly onto [0, 1], or onto 0 if they are all equal.
+fn rescale(values: &[f64]) -> Vec<f64> {
+    let lowest = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let highest = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    values.iter()
+        .map(|value| if highest > lowest { (value - lowest) / (highest - lowest) } else { 0f64 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::bit_string::BitString;
+    use ::genetic_algorithm::GeneticAlgorithm;
+    use ::interfaces::optimizer::Optimizer;
+    use ::termination::{any, Any, FitnessThreshold, MaxGenerations};
+
+    fn ones(bit_string: &BitString) -> GeneticResult<Vec<f64>> {
+        Ok(vec![bit_string.count_ones() as f64])
+    }
+
+    fn population(ones: &[usize]) -> Vec<Individual<BitString>> {
+        ones.iter()
+            .map(|&count| {
+                let bits: Vec<bool> = (0..10).map(|index| index < count).collect();
+                Individual::new(BitString::from_bools(&bits), count as f64)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn novelty_is_the_mean_distance_to_the_nearest_behaviours_including_the_archive() {
+        let mut rng = StdRng::from_seed(&[140usize][..]);
+        let mut novelty_search = NoveltySearch::new(ones, 2, ArchiveInsertion::Threshold { threshold: 3f64 }, &mut rng)
+            .unwrap();
+        let scores = novelty_search.selection_fitness(&population(&[0, 1, 2, 10])).unwrap();
+        assert_eq!(vec![1.5, 1f64, 1.5, 8.5], scores);
+        assert_eq!(&[vec![10f64]][..], novelty_search.get_archive());
+
+        // The archived behaviour is now the nearest neighbour of the lone 9.
+        let scores = novelty_search.selection_fitness(&population(&[0, 1, 2, 9])).unwrap();
+        assert_eq!(vec![1.5, 1f64, 1.5, 4f64], scores);
+        assert_eq!(2, novelty_search.get_archive().len());
+    }
+
+    #[test]
+    fn random_insertion_ignores_novelty_and_the_hybrid_blends_in_fitness() {
+        let mut rng = StdRng::from_seed(&[141usize][..]);
+        let mut always = NoveltySearch::new(ones, 3, ArchiveInsertion::Random { probability: 1f64 }, &mut rng).unwrap();
+        let mut never = NoveltySearch::new(ones, 3, ArchiveInsertion::Random { probability: 0f64 }, &mut rng).unwrap();
+        always.selection_fitness(&population(&[3, 3, 3])).unwrap();
+        never.selection_fitness(&population(&[0, 5, 10])).unwrap();
+        assert_eq!(3, always.get_archive().len());
+        assert!(never.get_archive().is_empty());
+        assert!(NoveltySearch::new(ones, 0, ArchiveInsertion::Random { probability: 0.5 }, &mut rng).is_err());
+        assert!(NoveltySearch::new(ones, 3, ArchiveInsertion::Random { probability: 2f64 }, &mut rng).is_err());
+
+        // Novelty favours the outlier at 0, fitness the individual at 6.
+        let individuals = population(&[0, 4, 5, 6]);
+        assert!(never.set_fitness_weight(1.5).is_err());
+        never.set_fitness_weight(1f64).unwrap();
+        assert_eq!(vec![0f64, 4f64 / 6f64, 5f64 / 6f64, 1f64], never.selection_fitness(&individuals).unwrap());
+        never.set_fitness_weight(0.5).unwrap();
+        let hybrid = never.selection_fitness(&individuals).unwrap();
+        never.set_fitness_weight(0f64).unwrap();
+        let novelty = never.selection_fitness(&individuals).unwrap();
+        assert!(novelty[0] > novelty[3]);
+        assert!(hybrid[0] > hybrid[1] && hybrid[3] > hybrid[1]);
+    }
+
+    #[test]
+    fn bounded_archive_keeps_only_the_newest_behaviours() {
+        let mut rng = StdRng::from_seed(&[142usize][..]);
+        let mut novelty_search = NoveltySearch::new(ones, 2, ArchiveInsertion::Random { probability: 1f64 }, &mut rng)
+            .unwrap();
+        novelty_search.selection_fitness(&population(&[0, 1, 2])).unwrap();
+        assert_eq!(3, novelty_search.get_archive().len());
+        assert!(novelty_search.set_archive_capacity(0).is_err());
+        novelty_search.set_archive_capacity(4).unwrap();
+        for generation in 3..8 {
+            novelty_search.selection_fitness(&population(&[generation, generation + 1])).unwrap();
+            assert!(novelty_search.get_archive().len() <= 4);
+        }
+        assert_eq!(&[vec![6f64], vec![7f64], vec![7f64], vec![8f64]][..], novelty_search.get_archive());
+        novelty_search.set_archive_capacity(1).unwrap();
+        assert_eq!(&[vec![8f64]][..], novelty_search.get_archive());
+    }
+
+    /// Every zero is worth a point, but all ones is worth more than anything else.
+    fn trap(bit_string: &BitString) -> GeneticResult<f64> {
+        Ok(match bit_string.count_ones() {
+            12 => 40f64,
+            ones => (12 - ones) as f64
+        })
+    }
+
+    fn termination() -> Any {
+        any(vec![Box::new(MaxGenerations::new(300)), Box::new(FitnessThreshold::new(40f64))])
+    }
+
+    #[test]
+    fn novelty_search_in_the_engine_escapes_a_deceptive_trap_that_fitness_alone_leads_away_from() {
+        let mut rng = StdRng::from_seed(&[19usize][..]);
+        let population: Vec<BitString> = (0..30).map(|_| BitString::new(12)).collect();
+        let mut objective = GeneticAlgorithm::new(population.clone(), trap).unwrap();
+        assert!(objective.run(&mut termination(), &mut rng).unwrap().get_best_fitness() < 40f64);
+        let mut genetic_algorithm = GeneticAlgorithm::new(population, trap).unwrap();
+        let insertion = ArchiveInsertion::Random { probability: 0.02 };
+        genetic_algorithm.set_selection_fitness(Box::new(NoveltySearch::new(ones, 5, insertion, &mut rng).unwrap()));
+        let stats = genetic_algorithm.run(&mut termination(), &mut rng).unwrap();
+        assert_eq!(40f64, stats.get_best_fitness());
+        assert_eq!(40f64, genetic_algorithm.get_best().unwrap().get_fitness());
+    }
+}
@@ -0,0 +1,342 @@
+//! Particle swarm optimization, in which particles fly through a box of real vectors, each
+//! pulled towards the best position it has found and the best position found by its
+//! neighbours. As everywhere in the crate, higher fitness is better.
+
+use std::cmp::Ordering;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::real_vector::BoundHandling;
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+const DEFAULT_INERTIA_WEIGHT: f64 = 0.7298;
+const DEFAULT_ACCELERATION: f64 = 1.49618;
+const DEFAULT_MAXIMUM_VELOCITY: f64 = 1f64;
+
+/// Which particles share their best positions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwarmTopology {
+    /// Every particle follows the best position of the whole swarm (gbest).
+    Global,
+    /// The particles form a ring and each follows the best position among itself and the
+    /// given number of particles on either side (lbest).
+    Ring { neighbours: usize }
+}
+
+/// How the velocity of a particle is updated. Both pull a particle towards its own best
+/// position with the cognitive coefficient and towards that of its neighbourhood with the
+/// social coefficient, each scaled by a fresh uniform random number per dimension.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VelocityUpdate {
+    /// The previous velocity is multiplied by the inertia weight.
+    Inertia { weight: f64, cognitive: f64, social: f64 },
+    /// The whole new velocity is multiplied by Clerc's constriction factor, which requires
+    /// the two coefficients to add up to more than 4.
+    Constriction { cognitive: f64, social: f64 }
+}
+
+/// A particle swarm maximising a fitness function over a box of real vectors. Positions whose
+/// evaluation fails get a fitness of negative infinity, so no particle is pulled towards them.
+pub struct ParticleSwarm<F> {
+    positions: Vec<Vec<f64>>,
+    velocities: Vec<Vec<f64>>,
+    fitness: Vec<f64>,
+    personal_best: Vec<(Vec<f64>, f64)>,
+    bounds: Vec<(f64, f64)>,
+    fitness_function: F,
+    topology: SwarmTopology,
+    update: VelocityUpdate,
+    maximum_velocity: f64,
+    bound_handling: BoundHandling,
+    generation: usize,
+    evaluations: usize,
+    evaluated: bool,
+    progress: Progress<Vec<f64>>
+}
+
+impl<F> ParticleSwarm<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Creates a swarm spread uniformly over the bounds. It uses the global topology, an
+    /// inertia weight of 0.7298 with both coefficients 1.49618, velocities clamped to the
+    /// width of the bounds and clamping at the bounds until configured otherwise. The swarm
+    /// is evaluated when the first generation is requested.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>swarm_size - the number of particles</li>
+    /// <li>bounds - the lower and upper bound of each dimension</li>
+    /// <li>fitness_function - scores a position, higher is fitter</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The swarm if successful.</li>
+    /// <li>CollectionEmptyError if there are no particles or no bounds.</li>
+    /// <li>InvalidParameterError if a bound is empty or not finite.</li>
+    /// </ul>
+    pub fn new(swarm_size: usize, bounds: Vec<(f64, f64)>, fitness_function: F, rng: &mut StdRng)
+               -> GeneticResult<ParticleSwarm<F>> {
+        if swarm_size == 0 || bounds.is_empty() {
+            return Err(GeneticError::CollectionEmptyError);
+        }
+        if !bounds.iter().all(|&(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mut positions = Vec::with_capacity(swarm_size);
+        let mut velocities = Vec::with_capacity(swarm_size);
+        for _ in 0..swarm_size {
+            let position: Vec<f64> = bounds.iter().map(|&(lower, upper)| uniform(lower, upper, rng)).collect();
+            let velocity = bounds.iter().zip(&position)
+                .map(|(&(lower, upper), value)| (uniform(lower, upper, rng) - value) / 2f64)
+                .collect();
+            positions.push(position);
+            velocities.push(velocity);
+        }
+        Ok(ParticleSwarm {
+            personal_best: positions.iter().map(|position| (position.clone(), f64::NEG_INFINITY)).collect(),
+            positions,
+            velocities,
+            fitness: vec![0f64; swarm_size],
+            bounds,
+            fitness_function,
+            topology: SwarmTopology::Global,
+            update: VelocityUpdate::Inertia {
+                weight: DEFAULT_INERTIA_WEIGHT,
+                cognitive: DEFAULT_ACCELERATION,
+                social: DEFAULT_ACCELERATION
+            },
+            maximum_velocity: DEFAULT_MAXIMUM_VELOCITY,
+            bound_handling: BoundHandling::Clamp,
+            generation: 0,
+            evaluations: 0,
+            evaluated: false,
+            progress: Progress::new(f64::NEG_INFINITY)
+        })
+    }
+
+    pub fn set_topology(&mut self, topology: SwarmTopology) {
+        self.topology = topology;
+    }
+
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if a coefficient is negative, or the coefficients of
+    /// constriction don't add up to more than 4.</li>
+    /// </ul>
+    pub fn set_velocity_update(&mut self, update: VelocityUpdate) -> GeneticResult<()> {
+        let valid = match update {
+            VelocityUpdate::Inertia { weight, cognitive, social } => weight >= 0f64 && cognitive >= 0f64 && social >= 0f64,
+            VelocityUpdate::Constriction { cognitive, social } => cognitive >= 0f64 && social >= 0f64 && cognitive + social > 4f64
+        };
+        if !valid {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.update = update;
+        Ok(())
+    }
+
+    /// Sets the fastest a particle may move in each dimension, as a fraction of the width of
+    /// the bounds in that dimension. Defaults to 1.
+    pub fn set_maximum_velocity(&mut self, fraction: f64) {
+        self.maximum_velocity = fraction.max(0f64);
+    }
+
+    /// Sets how particles that leave the bounds are brought back. Clamping also stops the
+    /// particle in that dimension, reflecting reverses its velocity there and resampling
+    /// stops it at a uniformly drawn position. Defaults to clamping.
+    pub fn set_bound_handling(&mut self, bound_handling: BoundHandling) {
+        self.bound_handling = bound_handling;
+    }
+
+    pub fn get_positions(&self) -> &[Vec<f64>] {
+        &self.positions
+    }
+
+    pub fn get_velocities(&self) -> &[Vec<f64>] {
+        &self.velocities
+    }
+
+    /// The best position each particle has found and its fitness.
+    pub fn get_personal_best(&self) -> &[(Vec<f64>, f64)] {
+        &self.personal_best
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// The particle whose best position the given particle is pulled towards.
+    fn neighbourhood_best(&self, particle: usize) -> usize {
+        let size = self.positions.len();
+        let fitter = |&a: &usize, &b: &usize| self.personal_best[a].1.partial_cmp(&self.personal_best[b].1)
+            .unwrap_or(Ordering::Equal);
+        let best = match self.topology {
+            SwarmTopology::Global => (0..size).max_by(fitter),
+            SwarmTopology::Ring { neighbours } => {
+                let neighbours = neighbours.min(size / 2);
+                (0..=2 * neighbours).map(|offset| (particle + size + offset - neighbours) % size).max_by(fitter)
+            }
+        };
+        best.unwrap_or(particle)
+    }
+
+    fn evaluate_positions(&mut self) {
+        for particle in 0..self.positions.len() {
+            self.evaluations += 1;
+            let fitness = (self.fitness_function)(&self.positions[particle])
+                .unwrap_or(self.progress.get_invalid_fitness());
+            self.fitness[particle] = fitness;
+            if fitness > self.personal_best[particle].1 {
+                self.personal_best[particle] = (self.positions[particle].clone(), fitness);
+            }
+        }
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        GenerationStats::from_real_vectors(self.generation, &self.positions, &self.fitness, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        self.progress.update_best(self.positions.iter().zip(self.fitness.iter().cloned()), stats);
+    }
+}
+
+impl<F> Optimizer<Vec<f64>> for ParticleSwarm<F> where F: Fn(&[f64]) -> GeneticResult<f64> {
+    /// Evaluates the swarm if that hasn't happened yet and summarises it.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if !self.evaluated {
+            self.evaluated = true;
+            self.evaluate_positions();
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Moves every particle once and evaluates its new position.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.get_statistics(rng)?;
+        let guides: Vec<usize> = (0..self.positions.len()).map(|particle| self.neighbourhood_best(particle)).collect();
+        let (inertia, cognitive, social, constriction) = match self.update {
+            VelocityUpdate::Inertia { weight, cognitive, social } => (weight, cognitive, social, 1f64),
+            VelocityUpdate::Constriction { cognitive, social } => {
+                let phi = cognitive + social;
+                (1f64, cognitive, social, 2f64 / (2f64 - phi - (phi * phi - 4f64 * phi).sqrt()).abs())
+            }
+        };
+        for (particle, &guide) in guides.iter().enumerate() {
+            for dimension in 0..self.bounds.len() {
+                let (lower, upper) = self.bounds[dimension];
+                let position = self.positions[particle][dimension];
+                let own_pull = cognitive * rng.gen::<f64>() * (self.personal_best[particle].0[dimension] - position);
+                let social_pull = social * rng.gen::<f64>() * (self.personal_best[guide].0[dimension] - position);
+                let limit = self.maximum_velocity * (upper - lower);
+                let mut velocity = (constriction * (inertia * self.velocities[particle][dimension] + own_pull + social_pull))
+                    .clamp(-limit, limit);
+                let mut moved = position + velocity;
+                if !(lower..=upper).contains(&moved) {
+                    match self.bound_handling {
+                        BoundHandling::Clamp => {
+                            moved = moved.clamp(lower, upper);
+                            velocity = 0f64;
+                        }
+                        BoundHandling::Reflect => {
+                            moved = if moved < lower { 2f64 * lower - moved } else { 2f64 * upper - moved }.clamp(lower, upper);
+                            velocity = -velocity;
+                        }
+                        BoundHandling::Resample => {
+                            moved = uniform(lower, upper, rng);
+                            velocity = 0f64;
+                        }
+                    }
+                }
+                self.positions[particle][dimension] = moved;
+                self.velocities[particle][dimension] = velocity;
+            }
+        }
+        self.evaluate_positions();
+        self.generation += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<Vec<f64>> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<Vec<f64>> {
+        &mut self.progress
+    }
+}
+
+fn uniform(lower: f64, upper: f64, rng: &mut StdRng) -> f64 {
+    lower + (upper - lower) * rng.gen::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use ::termination::MaxGenerations;
+
+    fn sphere(position: &[f64]) -> GeneticResult<f64> {
+        Ok(-position.iter().map(|value| value * value).sum::<f64>())
+    }
+
+    #[test]
+    fn every_topology_and_velocity_update_closes_in_on_the_optimum() {
+        let topologies = [SwarmTopology::Global, SwarmTopology::Ring { neighbours: 1 }];
+        let updates = [VelocityUpdate::Inertia { weight: 0.7298, cognitive: 1.49618, social: 1.49618 },
+                       VelocityUpdate::Constriction { cognitive: 2.05, social: 2.05 }];
+        for (seed, (&topology, &update)) in topologies.iter()
+            .flat_map(|topology| updates.iter().map(move |update| (topology, update)))
+            .enumerate() {
+            let mut rng = StdRng::from_seed(&[110 + seed][..]);
+            let mut swarm = ParticleSwarm::new(20, vec![(-5f64, 5f64); 5], sphere, &mut rng).unwrap();
+            swarm.set_topology(topology);
+            swarm.set_velocity_update(update).unwrap();
+            let stats = swarm.run(&mut MaxGenerations::new(300), &mut rng).unwrap();
+            let best = swarm.get_best().unwrap().get_fitness();
+            assert!(best > -1e-6, "{:?} {:?} reached {}", topology, update, best);
+            assert_eq!(20 * 301, stats.get_evaluations());
+        }
+    }
+
+    #[test]
+    fn particles_stay_within_the_bounds_and_the_velocity_limit() {
+        let shifted_sphere = |position: &[f64]| Ok(-position.iter().map(|value| (value - 8f64).powi(2)).sum::<f64>());
+        for (seed, &bound_handling) in [BoundHandling::Clamp, BoundHandling::Reflect, BoundHandling::Resample].iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[120 + seed][..]);
+            let mut swarm = ParticleSwarm::new(10, vec![(-5f64, 5f64); 3], shifted_sphere, &mut rng).unwrap();
+            swarm.set_bound_handling(bound_handling);
+            swarm.set_maximum_velocity(0.2);
+            for _ in 0..100 {
+                swarm.next_generation(&mut rng).unwrap();
+                assert!(swarm.get_positions().iter().flatten().all(|value| (-5f64..=5f64).contains(value)));
+                assert!(swarm.get_velocities().iter().flatten().all(|value| value.abs() <= 2f64));
+            }
+            assert!(swarm.get_best().unwrap().get_genome().iter().all(|value| *value > 4.9), "{:?}", bound_handling);
+        }
+    }
+
+    #[test]
+    fn invalid_configurations_are_rejected() {
+        let mut rng = StdRng::from_seed(&[130usize][..]);
+        assert!(ParticleSwarm::new(0, vec![(0f64, 1f64)], sphere, &mut rng).is_err());
+        assert!(ParticleSwarm::new(5, vec![(1f64, 0f64)], sphere, &mut rng).is_err());
+        let mut swarm = ParticleSwarm::new(5, vec![(0f64, 1f64)], sphere, &mut rng).unwrap();
+        assert!(swarm.set_velocity_update(VelocityUpdate::Constriction { cognitive: 1f64, social: 1f64 }).is_err());
+        assert!(swarm.set_velocity_update(VelocityUpdate::Inertia { weight: -1f64, cognitive: 1f64, social: 1f64 }).is_err());
+    }
+}
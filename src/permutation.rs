@@ -0,0 +1,373 @@
+//! An ordering of the numbers 0 to n - 1, for scheduling and routing problems. Every mutation
+//! and crossover produces a valid permutation.
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// The move a permutation makes when mutated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermutationMutation {
+    /// Swaps two elements.
+    Swap,
+    /// Moves one element to another position.
+    Insert,
+    /// Reverses a segment.
+    Inversion,
+    /// Shuffles a segment.
+    Scramble
+}
+
+/// The order preserving crossover used when breeding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PermutationCrossover {
+    /// Partially mapped crossover (PMX).
+    PartiallyMapped,
+    /// Order crossover (OX).
+    Order,
+    /// Cycle crossover (CX).
+    Cycle,
+    /// Edge recombination crossover (ERX).
+    EdgeRecombination
+}
+
+#[derive(Clone, Debug)]
+pub struct Permutation {
+    order: Vec<usize>,
+    mutation: PermutationMutation,
+    crossover: PermutationCrossover
+}
+
+impl GeneticCollection for Permutation {
+    type Child = Permutation;
+
+    /// Breeds a valid child permutation and then mutates it.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>The child permutation if successful.</li>
+    ///     <li>LengthMismatchError if the parents have different lengths.</li>
+    /// </ul>
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = match self.crossover {
+            PermutationCrossover::PartiallyMapped => self.partially_mapped_crossover(mate, rng)?,
+            PermutationCrossover::Order => self.order_crossover(mate, rng)?,
+            PermutationCrossover::Cycle => self.cycle_crossover(mate)?,
+            PermutationCrossover::EdgeRecombination => self.edge_recombination_crossover(mate, rng)?
+        };
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.order.len()
+    }
+}
+
+impl Mutable for Permutation {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        match self.mutation {
+            PermutationMutation::Swap => self.swap_mutation(rng),
+            PermutationMutation::Insert => self.insert_mutation(rng),
+            PermutationMutation::Inversion => self.inversion_mutation(rng),
+            PermutationMutation::Scramble => self.scramble_mutation(rng)
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Permutation {
+    fn eq(&self, other: &Permutation) -> bool {
+        self.order == other.order
+    }
+}
+
+impl Eq for Permutation {}
+
+impl Hash for Permutation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.order.hash(state);
+    }
+}
+
+impl Display for Permutation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let elements: Vec<String> = self.order.iter().map(|element| element.to_string()).collect();
+        write!(f, "{}", elements.join(" "))
+    }
+}
+
+impl Permutation {
+    /// Creates a random permutation of the numbers 0 to length - 1. It uses swap mutation and
+    /// order crossover until configured otherwise.
+    pub fn new(length: usize, rng: &mut StdRng) -> Permutation {
+        let mut permutation = Permutation::identity(length);
+        rng.shuffle(&mut permutation.order);
+        permutation
+    }
+
+    /// Creates the permutation 0, 1, ..., length - 1.
+    pub fn identity(length: usize) -> Permutation {
+        Permutation {
+            order: (0..length).collect(),
+            mutation: PermutationMutation::Swap,
+            crossover: PermutationCrossover::Order
+        }
+    }
+
+    /// Creates a permutation with the given order.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The permutation if successful.</li>
+    /// <li>InvalidParameterError if the order doesn't contain each of 0 to its length - 1
+    /// exactly once.</li>
+    /// </ul>
+    pub fn from_order(order: Vec<usize>) -> GeneticResult<Permutation> {
+        let distinct: HashSet<usize> = order.iter().cloned().collect();
+        if distinct.len() != order.len() || order.iter().any(|&element| element >= order.len()) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        let mut permutation = Permutation::identity(0);
+        permutation.order = order;
+        Ok(permutation)
+    }
+
+    pub fn get_order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn set_mutation(&mut self, mutation: PermutationMutation) {
+        self.mutation = mutation;
+    }
+
+    pub fn set_crossover(&mut self, crossover: PermutationCrossover) {
+        self.crossover = crossover;
+    }
+
+    pub fn swap_mutation(&mut self, rng: &mut StdRng) {
+        if self.order.len() < 2 {
+            return;
+        }
+        let (first, second) = self.two_positions(rng);
+        self.order.swap(first, second);
+    }
+
+    pub fn insert_mutation(&mut self, rng: &mut StdRng) {
+        if self.order.len() < 2 {
+            return;
+        }
+        let from = rng.gen_range(0, self.order.len());
+        let element = self.order.remove(from);
+        let to = rng.gen_range(0, self.order.len() + 1);
+        self.order.insert(to, element);
+    }
+
+    pub fn inversion_mutation(&mut self, rng: &mut StdRng) {
+        let (start, end) = self.segment(rng);
+        self.order[start..end].reverse();
+    }
+
+    pub fn scramble_mutation(&mut self, rng: &mut StdRng) {
+        let (start, end) = self.segment(rng);
+        rng.shuffle(&mut self.order[start..end]);
+    }
+
+    /// Partially mapped crossover: copies a random segment of self and fills the rest from the
+    /// mate, following the mapping defined by the segment wherever an element would repeat.
+    pub fn partially_mapped_crossover(&self, mate: &Permutation, rng: &mut StdRng) -> GeneticResult<Permutation> {
+        self.check_length(mate)?;
+        let (start, end) = self.segment(rng);
+        let length = self.order.len();
+        // position_in_self[e] is where element e sits in self.
+        let mut position_in_self = vec![0; length];
+        for (index, &element) in self.order.iter().enumerate() {
+            position_in_self[element] = index;
+        }
+        let mut child = self.clone();
+        for index in (0..start).chain(end..length) {
+            let mut element = mate.order[index];
+            while (start..end).contains(&position_in_self[element]) {
+                element = mate.order[position_in_self[element]];
+            }
+            child.order[index] = element;
+        }
+        Ok(child)
+    }
+
+    /// Order crossover: copies a random segment of self and fills the rest with the missing
+    /// elements in the order they follow the segment in the mate.
+    pub fn order_crossover(&self, mate: &Permutation, rng: &mut StdRng) -> GeneticResult<Permutation> {
+        self.check_length(mate)?;
+        let (start, end) = self.segment(rng);
+        let length = self.order.len();
+        let mut used = vec![false; length];
+        for &element in &self.order[start..end] {
+            used[element] = true;
+        }
+        let mut remaining = (0..length)
+            .map(|offset| mate.order[(end + offset) % length])
+            .filter(|&element| !used[element]);
+        let mut child = self.clone();
+        for offset in 0..length - (end - start) {
+            let index = (end + offset) % length;
+            child.order[index] = remaining.next().ok_or(GeneticError::ComputationError)?;
+        }
+        Ok(child)
+    }
+
+    /// Cycle crossover: splits the positions into the cycles the two parents define and takes
+    /// alternate cycles from self and the mate, so every element keeps a parent's position.
+    pub fn cycle_crossover(&self, mate: &Permutation) -> GeneticResult<Permutation> {
+        self.check_length(mate)?;
+        let length = self.order.len();
+        let mut position_in_self = vec![0; length];
+        for (index, &element) in self.order.iter().enumerate() {
+            position_in_self[element] = index;
+        }
+        let mut child = self.clone();
+        let mut visited = vec![false; length];
+        let mut from_mate = false;
+        for start in 0..length {
+            if visited[start] {
+                continue;
+            }
+            let mut index = start;
+            while !visited[index] {
+                visited[index] = true;
+                if from_mate {
+                    child.order[index] = mate.order[index];
+                }
+                index = position_in_self[mate.order[index]];
+            }
+            from_mate = !from_mate;
+        }
+        Ok(child)
+    }
+
+    /// Edge recombination crossover: builds a child that mostly uses edges (adjacent pairs,
+    /// wrapping around) found in either parent, preferring neighbours with the fewest edges left.
+    pub fn edge_recombination_crossover(&self, mate: &Permutation, rng: &mut StdRng) -> GeneticResult<Permutation> {
+        self.check_length(mate)?;
+        let length = self.order.len();
+        let mut child = self.clone();
+        if length == 0 {
+            return Ok(child);
+        }
+        let mut edges: Vec<HashSet<usize>> = vec![HashSet::new(); length];
+        for parent in &[&self.order, &mate.order] {
+            for index in 0..length {
+                let element = parent[index];
+                edges[element].insert(parent[(index + 1) % length]);
+                edges[element].insert(parent[(index + length - 1) % length]);
+            }
+        }
+        for (element, neighbours) in edges.iter_mut().enumerate() {
+            neighbours.remove(&element);
+        }
+        let mut placed = vec![false; length];
+        let mut current = self.order[0];
+        for slot in 0..length {
+            child.order[slot] = current;
+            placed[current] = true;
+            for neighbours in &mut edges {
+                neighbours.remove(&current);
+            }
+            if slot + 1 == length {
+                break;
+            }
+            let mut candidates: Vec<usize> = edges[current].iter().cloned().collect();
+            candidates.sort();
+            current = if candidates.is_empty() {
+                let unplaced: Vec<usize> = (0..length).filter(|&element| !placed[element]).collect();
+                unplaced[rng.gen_range(0, unplaced.len())]
+            } else {
+                let fewest = candidates.iter().map(|&candidate| edges[candidate].len()).min().unwrap_or(0);
+                let best: Vec<usize> = candidates.into_iter()
+                    .filter(|&candidate| edges[candidate].len() == fewest)
+                    .collect();
+                best[rng.gen_range(0, best.len())]
+            };
+        }
+        Ok(child)
+    }
+
+    /// Two distinct random positions.
+    fn two_positions(&self, rng: &mut StdRng) -> (usize, usize) {
+        let first = rng.gen_range(0, self.order.len());
+        let second = (first + rng.gen_range(1, self.order.len())) % self.order.len();
+        (first, second)
+    }
+
+    /// A random segment given by its start and exclusive end.
+    fn segment(&self, rng: &mut StdRng) -> (usize, usize) {
+        let first = rng.gen_range(0, self.order.len() + 1);
+        let second = rng.gen_range(0, self.order.len() + 1);
+        (first.min(second), first.max(second))
+    }
+
+    fn check_length(&self, other: &Permutation) -> GeneticResult<()> {
+        if self.order.len() == other.order.len() {
+            Ok(())
+        } else {
+            Err(GeneticError::LengthMismatchError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn is_valid(permutation: &Permutation) -> bool {
+        Permutation::from_order(permutation.get_order().to_vec()).is_ok()
+    }
+
+    #[test]
+    fn every_mutation_keeps_a_valid_permutation() {
+        let mut rng = StdRng::from_seed(&[14usize][..]);
+        let mut permutation = Permutation::new(12, &mut rng);
+        for _ in 0..100 {
+            permutation.swap_mutation(&mut rng);
+            permutation.insert_mutation(&mut rng);
+            permutation.inversion_mutation(&mut rng);
+            permutation.scramble_mutation(&mut rng);
+            assert!(is_valid(&permutation));
+        }
+    }
+
+    #[test]
+    fn every_crossover_produces_a_valid_permutation() {
+        let mut rng = StdRng::from_seed(&[15usize][..]);
+        for _ in 0..100 {
+            let first = Permutation::new(10, &mut rng);
+            let second = Permutation::new(10, &mut rng);
+            assert!(is_valid(&first.partially_mapped_crossover(&second, &mut rng).unwrap()));
+            assert!(is_valid(&first.order_crossover(&second, &mut rng).unwrap()));
+            assert!(is_valid(&first.cycle_crossover(&second).unwrap()));
+            assert!(is_valid(&first.edge_recombination_crossover(&second, &mut rng).unwrap()));
+        }
+    }
+
+    #[test]
+    fn cycle_crossover_alternates_cycles_between_parents() {
+        let first = Permutation::from_order(vec![0, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let second = Permutation::from_order(vec![7, 4, 1, 0, 2, 5, 3, 6]).unwrap();
+        let child = first.cycle_crossover(&second).unwrap();
+        assert_eq!(&[0, 4, 1, 3, 2, 5, 6, 7], child.get_order());
+    }
+
+    #[test]
+    fn invalid_orders_are_rejected() {
+        assert!(Permutation::from_order(vec![0, 0, 1]).is_err());
+        assert!(Permutation::from_order(vec![0, 3, 1]).is_err());
+    }
+}
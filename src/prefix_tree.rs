@@ -0,0 +1,187 @@
+//! Helpers shared by the genetic programming genomes that store a tree as its nodes in prefix
+//! order, such as `GpTree` and `TypedGpTree`. Every subtree is then a contiguous slice, so
+//! crossover and mutation come down to splicing slices. The helpers learn the arity of a node
+//! from a closure, so they work with any kind of node.
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::GeneticResult;
+
+/// Koza's customary limit on the depth of a tree.
+pub const DEFAULT_MAXIMUM_DEPTH: usize = 17;
+/// The deepest subtree grown by subtree mutation.
+pub const MUTATION_DEPTH: usize = 4;
+/// How often a crossover is retried when its child would be too deep.
+pub const CROSSOVER_ATTEMPTS: usize = 5;
+/// The chance that crossover picks a function node rather than a terminal.
+pub const FUNCTION_CROSSOVER_PROBABILITY: f64 = 0.9;
+
+/// The exclusive end of the subtree rooted at the given index, or None if the nodes run out.
+pub fn subtree_end<N, A>(nodes: &[N], root: usize, arity: &A) -> Option<usize> where A: Fn(&N) -> usize {
+    let mut expected = 1usize;
+    let mut index = root;
+    while expected > 0 {
+        expected = expected - 1 + arity(nodes.get(index)?);
+        index += 1;
+    }
+    Some(index)
+}
+
+/// The depth of the subtree rooted at the given index, where a lone terminal has depth 0.
+pub fn subtree_depth<N, A>(nodes: &[N], root: usize, arity: &A) -> usize where A: Fn(&N) -> usize {
+    let mut child = root + 1;
+    let mut deepest = None;
+    for _ in 0..arity(&nodes[root]) {
+        deepest = Some(deepest.unwrap_or(0).max(subtree_depth(nodes, child, arity)));
+        child = subtree_end(nodes, child, arity).unwrap_or(nodes.len());
+    }
+    deepest.map_or(0, |depth| depth + 1)
+}
+
+/// The depth at which the node at the given index sits.
+pub fn depth_of<N, A>(nodes: &[N], target: usize, arity: &A) -> usize where A: Fn(&N) -> usize {
+    // Each entry counts the children its node still expects.
+    let mut open: Vec<usize> = vec!();
+    for node in &nodes[..target] {
+        while let Some(&0) = open.last() {
+            open.pop();
+        }
+        if let Some(last) = open.last_mut() {
+            *last -= 1;
+        }
+        open.push(arity(node));
+    }
+    while let Some(&0) = open.last() {
+        open.pop();
+    }
+    open.len()
+}
+
+/// A copy of the nodes with the subtree rooted at the given index replaced.
+pub fn replace_subtree<N, A>(nodes: &[N], root: usize, replacement: &[N], arity: &A) -> Vec<N>
+    where N: Clone, A: Fn(&N) -> usize {
+    let end = subtree_end(nodes, root, arity).unwrap_or(nodes.len());
+    let mut replaced = nodes.to_vec();
+    replaced.splice(root..end, replacement.iter().cloned());
+    replaced
+}
+
+/// Picks a crossover point among the eligible nodes: a function node most of the time and a
+/// terminal otherwise, falling back to any eligible node if there is none of the kind wanted.
+///
+/// # Returns
+/// The index of the node, or None if no node is eligible.
+pub fn crossover_point<N, A, E>(nodes: &[N], eligible: E, arity: &A, rng: &mut StdRng) -> Option<usize>
+    where A: Fn(&N) -> usize, E: Fn(usize) -> bool {
+    let eligible: Vec<usize> = (0..nodes.len()).filter(|&index| eligible(index)).collect();
+    if eligible.is_empty() {
+        return None;
+    }
+    let want_function = rng.gen::<f64>() < FUNCTION_CROSSOVER_PROBABILITY;
+    let preferred: Vec<usize> = eligible.iter().cloned()
+        .filter(|&index| (arity(&nodes[index]) > 0) == want_function)
+        .collect();
+    let candidates = if preferred.is_empty() { &eligible } else { &preferred };
+    Some(candidates[rng.gen_range(0, candidates.len())])
+}
+
+/// Replaces a subtree of the nodes with a subtree of the mate's, retrying up to
+/// `CROSSOVER_ATTEMPTS` times while the child would be deeper than the maximum depth.
+///
+/// # Parameters
+/// <ul>
+/// <li>nodes - the tree receiving the subtree</li>
+/// <li>mate - the tree donating the subtree</li>
+/// <li>maximum_depth - the deepest the child may be</li>
+/// <li>arity - the arity of a node</li>
+/// <li>pick - picks the root of the replaced subtree and of the donated one, or None if this
+/// attempt found no pair that fits</li>
+/// </ul>
+///
+/// # Returns
+/// The child, or None if every attempt failed.
+pub fn subtree_crossover<N, A, P>(nodes: &[N], mate: &[N], maximum_depth: usize, arity: &A, mut pick: P)
+                                  -> Option<Vec<N>>
+    where N: Clone, A: Fn(&N) -> usize, P: FnMut() -> Option<(usize, usize)> {
+    for _ in 0..CROSSOVER_ATTEMPTS {
+        let (target, donor) = match pick() {
+            Some(points) => points,
+            None => continue
+        };
+        let donor_end = subtree_end(mate, donor, arity).unwrap_or(mate.len());
+        let child = replace_subtree(nodes, target, &mate[donor..donor_end], arity);
+        if subtree_depth(&child, 0, arity) <= maximum_depth {
+            return Some(child);
+        }
+    }
+    None
+}
+
+/// Ramped half-and-half initialisation: the population is spread evenly over the depths from
+/// the minimum to the maximum depth, and at each depth half the trees are built with the full
+/// method and half with the grow method.
+///
+/// # Parameters
+/// <ul>
+/// <li>population_size - the number of trees</li>
+/// <li>minimum_depth - the shallowest depth</li>
+/// <li>maximum_depth - the deepest depth</li>
+/// <li>build - builds a tree of the given depth, with the full method if the flag is set and
+/// the grow method otherwise</li>
+/// </ul>
+///
+/// # Returns
+/// <ul>
+/// <li>The trees if successful.</li>
+/// <li>InvalidParameterError if the minimum depth is greater than the maximum depth.</li>
+/// <li>The first error from building a tree.</li>
+/// </ul>
+pub fn ramped_half_and_half<T, B>(population_size: usize, minimum_depth: usize, maximum_depth: usize, mut build: B)
+                                  -> GeneticResult<Vec<T>> where B: FnMut(usize, bool) -> GeneticResult<T> {
+    if minimum_depth > maximum_depth {
+        return Err(GeneticError::InvalidParameterError);
+    }
+    let depths = maximum_depth - minimum_depth + 1;
+    (0..population_size)
+        .map(|index| build(minimum_depth + index % depths, (index / depths).is_multiple_of(2)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // Each node is its own arity: 2 0 1 0 is a binary function over a terminal and a unary
+    // function of a terminal.
+    fn arity(node: &usize) -> usize {
+        *node
+    }
+
+    #[test]
+    fn subtrees_are_measured_from_their_root() {
+        let nodes = [2, 0, 1, 0];
+        assert_eq!(Some(4), subtree_end(&nodes, 0, &arity));
+        assert_eq!(Some(2), subtree_end(&nodes, 1, &arity));
+        assert_eq!(None, subtree_end(&nodes[..3], 0, &arity));
+        assert_eq!(2, subtree_depth(&nodes, 0, &arity));
+        assert_eq!(vec![0, 1, 1, 2], (0..4).map(|index| depth_of(&nodes, index, &arity)).collect::<Vec<usize>>());
+        assert_eq!(vec![2, 0, 0], replace_subtree(&nodes, 2, &[0], &arity));
+    }
+
+    #[test]
+    fn crossover_gives_up_when_every_child_is_too_deep() {
+        let mut rng = StdRng::from_seed(&[36usize][..]);
+        let shallow = [2, 0, 0];
+        let deep = [1, 1, 1, 0];
+        assert_eq!(None, subtree_crossover(&shallow, &deep, 2, &arity, || Some((1, 0))));
+        let child = subtree_crossover(&shallow, &deep, 2, &arity, || Some((1, 2))).unwrap();
+        assert_eq!(vec![2, 1, 0, 0], child);
+        assert_eq!(Some(0), crossover_point(&deep, |index| index == 0, &arity, &mut rng));
+        assert_eq!(None, crossover_point(&deep, |_| false, &arity, &mut rng));
+        let trees = ramped_half_and_half(6, 1, 3, |depth, full| Ok((depth, full))).unwrap();
+        assert_eq!(vec![(1, true), (2, true), (3, true), (1, false), (2, false), (3, false)], trees);
+        assert!(ramped_half_and_half(6, 3, 1, |depth, full| Ok((depth, full))).is_err());
+    }
+}
@@ -0,0 +1,440 @@
+//! A vector of real numbers, each kept within its own bounds, for parameter tuning and other
+//! continuous problems.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use rand::distributions::normal::StandardNormal;
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::GeneticResult;
+
+/// The smallest step size self-adaptive mutation may shrink to.
+const MINIMUM_STEP_SIZE: f64 = 1e-10;
+const DEFAULT_DISTRIBUTION_INDEX: f64 = 20f64;
+
+/// How a value that leaves its bounds is brought back within them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundHandling {
+    /// Moves the value to the bound it crossed.
+    Clamp,
+    /// Mirrors the value back into the bounds at the bound it crossed.
+    Reflect,
+    /// Draws a new value uniformly from within the bounds.
+    Resample
+}
+
+/// How a vector perturbs its values when mutated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RealMutation {
+    /// Adds normally distributed noise with a fixed standard deviation to each value with a
+    /// probability of one over the dimension.
+    Gaussian { sigma: f64 },
+    /// Adds normally distributed noise to every value, with per dimension step sizes that are
+    /// themselves mutated log-normally before use.
+    SelfAdaptive,
+    /// Polynomial mutation with the given distribution index, applied to each value with a
+    /// probability of one over the dimension. Larger indices give smaller changes.
+    Polynomial { eta: f64 }
+}
+
+/// How two vectors combine their values when bred.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RealCrossover {
+    /// Blend crossover (BLX-α): each value is drawn from the range spanned by the parents,
+    /// widened by alpha times its length at both ends.
+    Blend { alpha: f64 },
+    /// Simulated binary crossover with the given distribution index.
+    SimulatedBinary { eta: f64 },
+    /// A random weighted average of the parents.
+    Arithmetic
+}
+
+#[derive(Clone, Debug)]
+pub struct RealVector {
+    values: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+    step_sizes: Vec<f64>,
+    mutation: RealMutation,
+    crossover: RealCrossover,
+    bound_handling: BoundHandling
+}
+
+impl GeneticCollection for RealVector {
+    type Child = RealVector;
+
+    /// Recombines the parents with the crossover of self, then mutates the child.
+    ///
+    /// # Returns
+    /// <ul>
+    ///     <li>The child vector if successful.</li>
+    ///     <li>LengthMismatchError if the parents have different dimensions.</li>
+    /// </ul>
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = match self.crossover {
+            RealCrossover::Blend { alpha } => self.blend_crossover(mate, alpha, rng)?,
+            RealCrossover::SimulatedBinary { eta } => self.simulated_binary_crossover(mate, eta, rng)?.0,
+            RealCrossover::Arithmetic => self.arithmetic_crossover(mate, rng)?
+        };
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl Mutable for RealVector {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        match self.mutation {
+            RealMutation::Gaussian { sigma } => self.gaussian_mutation(sigma, rng),
+            RealMutation::SelfAdaptive => self.self_adaptive_mutation(rng),
+            RealMutation::Polynomial { eta } => self.polynomial_mutation(eta, rng)
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for RealVector {
+    /// Vectors are equal when their values are bit for bit the same.
+    fn eq(&self, other: &RealVector) -> bool {
+        self.values.len() == other.values.len()
+            && self.values.iter().zip(&other.values).all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+impl Eq for RealVector {}
+
+impl Hash for RealVector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in &self.values {
+            value.to_bits().hash(state);
+        }
+    }
+}
+
+impl Display for RealVector {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|value| value.to_string()).collect();
+        write!(f, "[{}]", values.join(", "))
+    }
+}
+
+impl RealVector {
+    /// Creates a vector whose values are drawn uniformly from within their bounds. The vector
+    /// uses polynomial mutation and simulated binary crossover, both with a distribution index
+    /// of 20, and clamps values to their bounds until configured otherwise.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>bounds - the inclusive lower and upper bound of each dimension</li>
+    /// <li>rng - the random number generator</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The vector if successful.</li>
+    /// <li>InvalidParameterError if a lower bound is above its upper bound or a bound isn't
+    /// finite.</li>
+    /// </ul>
+    pub fn new(bounds: Vec<(f64, f64)>, rng: &mut StdRng) -> GeneticResult<RealVector> {
+        check_bounds(&bounds)?;
+        let values = bounds.iter().map(|&(lower, upper)| uniform(lower, upper, rng)).collect();
+        Ok(RealVector::with_values(values, bounds))
+    }
+
+    /// Creates a vector with the given values.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The vector if successful.</li>
+    /// <li>LengthMismatchError if there isn't one pair of bounds per value.</li>
+    /// <li>InvalidParameterError if the bounds are invalid or a value lies outside them.</li>
+    /// </ul>
+    pub fn from_values(values: Vec<f64>, bounds: Vec<(f64, f64)>) -> GeneticResult<RealVector> {
+        if values.len() != bounds.len() {
+            return Err(GeneticError::LengthMismatchError);
+        }
+        check_bounds(&bounds)?;
+        if values.iter().zip(&bounds).any(|(&value, &(lower, upper))| !(lower..=upper).contains(&value)) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        Ok(RealVector::with_values(values, bounds))
+    }
+
+    fn with_values(values: Vec<f64>, bounds: Vec<(f64, f64)>) -> RealVector {
+        // Self-adaptive step sizes start at a tenth of the width of each dimension.
+        let step_sizes = bounds.iter()
+            .map(|&(lower, upper)| ((upper - lower) / 10f64).max(MINIMUM_STEP_SIZE))
+            .collect();
+        RealVector {
+            values,
+            bounds,
+            step_sizes,
+            mutation: RealMutation::Polynomial { eta: DEFAULT_DISTRIBUTION_INDEX },
+            crossover: RealCrossover::SimulatedBinary { eta: DEFAULT_DISTRIBUTION_INDEX },
+            bound_handling: BoundHandling::Clamp
+        }
+    }
+
+    pub fn get_values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn get_bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    /// The per dimension step sizes used by self-adaptive mutation.
+    pub fn get_step_sizes(&self) -> &[f64] {
+        &self.step_sizes
+    }
+
+    pub fn set_mutation(&mut self, mutation: RealMutation) {
+        self.mutation = mutation;
+    }
+
+    pub fn set_crossover(&mut self, crossover: RealCrossover) {
+        self.crossover = crossover;
+    }
+
+    pub fn set_bound_handling(&mut self, bound_handling: BoundHandling) {
+        self.bound_handling = bound_handling;
+    }
+
+    /// Adds noise with the given standard deviation to each value with a probability of one
+    /// over the dimension.
+    pub fn gaussian_mutation(&mut self, sigma: f64, rng: &mut StdRng) {
+        let mutation_rate = 1f64 / self.values.len().max(1) as f64;
+        for index in 0..self.values.len() {
+            if rng.gen::<f64>() < mutation_rate {
+                let StandardNormal(noise) = rng.gen();
+                let value = self.values[index] + sigma * noise;
+                self.values[index] = self.bring_within_bounds(index, value, rng);
+            }
+        }
+    }
+
+    /// Mutates the step sizes log-normally, then adds noise scaled by the new step size to
+    /// every value.
+    pub fn self_adaptive_mutation(&mut self, rng: &mut StdRng) {
+        let dimension = self.values.len().max(1) as f64;
+        let global_learning_rate = 1f64 / (2f64 * dimension).sqrt();
+        let local_learning_rate = 1f64 / (2f64 * dimension.sqrt()).sqrt();
+        let StandardNormal(global_noise) = rng.gen();
+        for index in 0..self.values.len() {
+            let StandardNormal(local_noise) = rng.gen();
+            let step_size = self.step_sizes[index]
+                * (global_learning_rate * global_noise + local_learning_rate * local_noise).exp();
+            self.step_sizes[index] = step_size.max(MINIMUM_STEP_SIZE);
+            let StandardNormal(noise) = rng.gen();
+            let value = self.values[index] + self.step_sizes[index] * noise;
+            self.values[index] = self.bring_within_bounds(index, value, rng);
+        }
+    }
+
+    /// Deb's polynomial mutation, applied to each value with a probability of one over the
+    /// dimension.
+    pub fn polynomial_mutation(&mut self, eta: f64, rng: &mut StdRng) {
+        let mutation_rate = 1f64 / self.values.len().max(1) as f64;
+        let exponent = 1f64 / (eta + 1f64);
+        for index in 0..self.values.len() {
+            let (lower, upper) = self.bounds[index];
+            if upper == lower || rng.gen::<f64>() >= mutation_rate {
+                continue;
+            }
+            let width = upper - lower;
+            let value = self.values[index];
+            let random: f64 = rng.gen();
+            let perturbation = if random < 0.5 {
+                let distance_to_lower = 1f64 - (value - lower) / width;
+                let base = 2f64 * random + (1f64 - 2f64 * random) * distance_to_lower.powf(eta + 1f64);
+                base.powf(exponent) - 1f64
+            } else {
+                let distance_to_upper = 1f64 - (upper - value) / width;
+                let base = 2f64 * (1f64 - random) + 2f64 * (random - 0.5) * distance_to_upper.powf(eta + 1f64);
+                1f64 - base.powf(exponent)
+            };
+            self.values[index] = self.bring_within_bounds(index, value + perturbation * width, rng);
+        }
+    }
+
+    /// Blend crossover (BLX-α).
+    pub fn blend_crossover(&self, mate: &RealVector, alpha: f64, rng: &mut StdRng) -> GeneticResult<RealVector> {
+        self.check_dimension(mate)?;
+        let mut child = self.clone();
+        for index in 0..self.values.len() {
+            let low = self.values[index].min(mate.values[index]);
+            let high = self.values[index].max(mate.values[index]);
+            let extension = alpha * (high - low);
+            let value = uniform(low - extension, high + extension, rng);
+            child.values[index] = child.bring_within_bounds(index, value, rng);
+        }
+        child.average_step_sizes(mate);
+        Ok(child)
+    }
+
+    /// Simulated binary crossover (SBX).
+    ///
+    /// # Returns
+    /// Both children, whose mean equals the mean of the parents before bound handling.
+    pub fn simulated_binary_crossover(&self, mate: &RealVector, eta: f64, rng: &mut StdRng)
+                                      -> GeneticResult<(RealVector, RealVector)> {
+        self.check_dimension(mate)?;
+        let mut first = self.clone();
+        let mut second = mate.clone();
+        for index in 0..self.values.len() {
+            let random: f64 = rng.gen();
+            let spread = if random <= 0.5 {
+                (2f64 * random).powf(1f64 / (eta + 1f64))
+            } else {
+                (1f64 / (2f64 * (1f64 - random))).powf(1f64 / (eta + 1f64))
+            };
+            let (a, b) = (self.values[index], mate.values[index]);
+            let first_value = 0.5 * ((1f64 + spread) * a + (1f64 - spread) * b);
+            let second_value = 0.5 * ((1f64 - spread) * a + (1f64 + spread) * b);
+            first.values[index] = first.bring_within_bounds(index, first_value, rng);
+            second.values[index] = second.bring_within_bounds(index, second_value, rng);
+        }
+        first.average_step_sizes(mate);
+        second.average_step_sizes(self);
+        Ok((first, second))
+    }
+
+    /// Whole arithmetic crossover: the child is a weighted average of the parents with a
+    /// random weight.
+    pub fn arithmetic_crossover(&self, mate: &RealVector, rng: &mut StdRng) -> GeneticResult<RealVector> {
+        self.check_dimension(mate)?;
+        let weight: f64 = rng.gen();
+        let mut child = self.clone();
+        for index in 0..self.values.len() {
+            let value = weight * self.values[index] + (1f64 - weight) * mate.values[index];
+            child.values[index] = value.max(self.bounds[index].0).min(self.bounds[index].1);
+        }
+        child.average_step_sizes(mate);
+        Ok(child)
+    }
+
+    fn bring_within_bounds(&self, index: usize, value: f64, rng: &mut StdRng) -> f64 {
+        let (lower, upper) = self.bounds[index];
+        if (lower..=upper).contains(&value) {
+            return value;
+        }
+        match self.bound_handling {
+            BoundHandling::Clamp => value.max(lower).min(upper),
+            BoundHandling::Reflect => {
+                let width = upper - lower;
+                if width == 0f64 || !value.is_finite() {
+                    return value.max(lower).min(upper);
+                }
+                // Reflecting back and forth between the bounds repeats every two widths.
+                let offset = (value - lower).rem_euclid(2f64 * width);
+                if offset <= width { lower + offset } else { upper - (offset - width) }
+            },
+            BoundHandling::Resample => uniform(lower, upper, rng)
+        }
+    }
+
+    fn average_step_sizes(&mut self, mate: &RealVector) {
+        for (step_size, mate_step_size) in self.step_sizes.iter_mut().zip(&mate.step_sizes) {
+            *step_size = (*step_size + mate_step_size) / 2f64;
+        }
+    }
+
+    fn check_dimension(&self, other: &RealVector) -> GeneticResult<()> {
+        if self.values.len() == other.values.len() {
+            Ok(())
+        } else {
+            Err(GeneticError::LengthMismatchError)
+        }
+    }
+}
+
+fn check_bounds(bounds: &[(f64, f64)]) -> GeneticResult<()> {
+    if bounds.iter().all(|&(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper) {
+        Ok(())
+    } else {
+        Err(GeneticError::InvalidParameterError)
+    }
+}
+
+fn uniform(lower: f64, upper: f64, rng: &mut StdRng) -> f64 {
+    lower + (upper - lower) * rng.gen::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn within_bounds(vector: &RealVector) -> bool {
+        vector.get_values().iter().zip(vector.get_bounds())
+            .all(|(&value, &(lower, upper))| lower <= value && value <= upper)
+    }
+
+    #[test]
+    fn every_bound_handling_keeps_values_within_bounds() {
+        let mut rng = StdRng::from_seed(&[8usize][..]);
+        for &bound_handling in &[BoundHandling::Clamp, BoundHandling::Reflect, BoundHandling::Resample] {
+            let mut vector = RealVector::new(vec![(-1f64, 1f64); 5], &mut rng).unwrap();
+            vector.set_bound_handling(bound_handling);
+            for _ in 0..50 {
+                vector.gaussian_mutation(10f64, &mut rng);
+                vector.self_adaptive_mutation(&mut rng);
+                vector.polynomial_mutation(5f64, &mut rng);
+                assert!(within_bounds(&vector));
+            }
+        }
+    }
+
+    #[test]
+    fn reflection_mirrors_at_the_crossed_bound() {
+        let mut rng = StdRng::from_seed(&[9usize][..]);
+        let mut vector = RealVector::from_values(vec![0f64], vec![(0f64, 10f64)]).unwrap();
+        vector.set_bound_handling(BoundHandling::Reflect);
+        assert_eq!(8f64, vector.bring_within_bounds(0, 12f64, &mut rng));
+        assert_eq!(3f64, vector.bring_within_bounds(0, -3f64, &mut rng));
+    }
+
+    #[test]
+    fn simulated_binary_children_keep_the_parents_mean() {
+        let mut rng = StdRng::from_seed(&[10usize][..]);
+        let bounds = vec![(-100f64, 100f64); 3];
+        let first = RealVector::from_values(vec![1f64, 2f64, 3f64], bounds.clone()).unwrap();
+        let second = RealVector::from_values(vec![3f64, -2f64, 5f64], bounds).unwrap();
+        let (a, b) = first.simulated_binary_crossover(&second, 2f64, &mut rng).unwrap();
+        for index in 0..3 {
+            let parents = first.get_values()[index] + second.get_values()[index];
+            let children = a.get_values()[index] + b.get_values()[index];
+            assert!((parents - children).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn blend_and_arithmetic_children_lie_in_the_expected_ranges() {
+        let mut rng = StdRng::from_seed(&[11usize][..]);
+        let bounds = vec![(-100f64, 100f64); 2];
+        let first = RealVector::from_values(vec![0f64, 10f64], bounds.clone()).unwrap();
+        let second = RealVector::from_values(vec![10f64, 0f64], bounds).unwrap();
+        for _ in 0..20 {
+            let blend = first.blend_crossover(&second, 0.5, &mut rng).unwrap();
+            assert!(blend.get_values().iter().all(|&value| (-5f64..=15f64).contains(&value)));
+            let arithmetic = first.arithmetic_crossover(&second, &mut rng).unwrap();
+            assert!((arithmetic.get_values()[0] + arithmetic.get_values()[1] - 10f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn invalid_bounds_and_mismatched_dimensions_are_rejected() {
+        let mut rng = StdRng::from_seed(&[12usize][..]);
+        assert!(RealVector::new(vec![(1f64, 0f64)], &mut rng).is_err());
+        assert!(RealVector::from_values(vec![2f64], vec![(0f64, 1f64)]).is_err());
+        let one = RealVector::new(vec![(0f64, 1f64)], &mut rng).unwrap();
+        let two = RealVector::new(vec![(0f64, 1f64); 2], &mut rng).unwrap();
+        assert!(one.breed(&two, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Replacement schemes deciding which individuals make up the next population.
+
+use std::cmp::Ordering;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::individual::Individual;
+use ::interfaces::replacement::Replacement;
+use ::GeneticResult;
+
+/// Sorts a population from fittest to least fit.
+pub fn sort_by_fitness<G>(population: &mut [Individual<G>]) {
+    population.sort_by(|a, b| b.get_fitness().partial_cmp(&a.get_fitness()).unwrap_or(Ordering::Equal));
+}
+
+/// Replaces the whole population with its offspring, except for the fittest few which are
+/// carried over unchanged.
+#[derive(Clone, Copy, Debug)]
+pub struct Generational {
+    elite_count: usize
+}
+
+impl Generational {
+    /// # Parameters
+    /// elite_count - how many of the fittest individuals survive into the next population
+    pub fn new(elite_count: usize) -> Generational {
+        Generational { elite_count }
+    }
+}
+
+impl<G> Replacement<G> for Generational {
+    fn offspring_count(&self, population_size: usize) -> usize {
+        population_size - self.elite_count.min(population_size)
+    }
+
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        let population_size = population.len();
+        sort_by_fitness(&mut population);
+        population.truncate(self.elite_count);
+        population.extend(offspring);
+        population.truncate(population_size);
+        Ok(population)
+    }
+}
+
+/// The (1+λ) evolution strategy: λ offspring are bred from a single parent, which the fittest
+/// offspring replaces unless it is strictly worse. Letting equally fit offspring win allows
+/// neutral drift, which genomes with many inactive genes, such as CGP, rely on. A larger
+/// population collapses to its fittest individual.
+#[derive(Clone, Copy, Debug)]
+pub struct OnePlusLambda {
+    lambda: usize
+}
+
+impl OnePlusLambda {
+    /// # Parameters
+    /// lambda - how many offspring are bred each generation
+    pub fn new(lambda: usize) -> OnePlusLambda {
+        OnePlusLambda { lambda: lambda.max(1) }
+    }
+}
+
+impl<G> Replacement<G> for OnePlusLambda {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.lambda
+    }
+
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        sort_by_fitness(&mut population);
+        let mut parent = match population.into_iter().next() {
+            Some(parent) => parent,
+            None => return Ok(offspring)
+        };
+        for child in offspring {
+            if child.get_fitness() >= parent.get_fitness() {
+                parent = child;
+            }
+        }
+        Ok(vec![parent])
+    }
+}
+
+/// Which member of the population a steady-state child takes the place of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SteadyStateDeletion {
+    /// The least fit member.
+    Worst,
+    /// The member that has been in the population longest.
+    Oldest,
+    /// The least fit of the given number of members drawn at random.
+    TournamentLoser { size: usize }
+}
+
+/// Breeds only a few children each step, each of which takes the place of one member of the
+/// population. The population is kept in order of age, oldest first, so the children are
+/// appended to its end.
+#[derive(Clone, Copy, Debug)]
+pub struct SteadyState {
+    children: usize,
+    deletion: SteadyStateDeletion
+}
+
+impl SteadyState {
+    /// # Parameters
+    /// <ul>
+    /// <li>children - how many children are bred each step</li>
+    /// <li>deletion - which member of the population each child replaces</li>
+    /// </ul>
+    pub fn new(children: usize, deletion: SteadyStateDeletion) -> SteadyState {
+        SteadyState { children: children.max(1), deletion }
+    }
+}
+
+impl<G> Replacement<G> for SteadyState {
+    fn offspring_count(&self, population_size: usize) -> usize {
+        self.children.min(population_size)
+    }
+
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        for child in offspring {
+            if population.is_empty() {
+                population.push(child);
+                continue;
+            }
+            let doomed = match self.deletion {
+                SteadyStateDeletion::Worst => least_fit(&population, 0..population.len()),
+                SteadyStateDeletion::Oldest => 0,
+                SteadyStateDeletion::TournamentLoser { size } => {
+                    let contestants: Vec<usize> = (0..size.max(1))
+                        .map(|_| rng.gen_range(0, population.len()))
+                        .collect();
+                    least_fit(&population, contestants)
+                }
+            };
+            population.remove(doomed);
+            population.push(child);
+        }
+        Ok(population)
+    }
+}
+
+/// The (μ+λ) evolution strategy: λ offspring are bred from μ parents and the fittest μ of
+/// parents and offspring together survive.
+#[derive(Clone, Copy, Debug)]
+pub struct MuPlusLambda {
+    mu: usize,
+    lambda: usize
+}
+
+impl MuPlusLambda {
+    /// # Parameters
+    /// <ul>
+    /// <li>mu - how many individuals survive each generation</li>
+    /// <li>lambda - how many offspring are bred each generation</li>
+    /// </ul>
+    pub fn new(mu: usize, lambda: usize) -> MuPlusLambda {
+        MuPlusLambda { mu: mu.max(1), lambda: lambda.max(1) }
+    }
+}
+
+impl<G> Replacement<G> for MuPlusLambda {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.lambda
+    }
+
+    fn replace(&mut self, mut population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        population.extend(offspring);
+        sort_by_fitness(&mut population);
+        population.truncate(self.mu);
+        Ok(population)
+    }
+}
+
+/// The (μ,λ) evolution strategy: λ offspring are bred from μ parents and only the fittest μ
+/// offspring survive, so no individual lives longer than one generation. λ is at least μ.
+#[derive(Clone, Copy, Debug)]
+pub struct MuCommaLambda {
+    mu: usize,
+    lambda: usize
+}
+
+impl MuCommaLambda {
+    /// # Parameters
+    /// <ul>
+    /// <li>mu - how many individuals survive each generation</li>
+    /// <li>lambda - how many offspring are bred each generation, raised to mu if smaller</li>
+    /// </ul>
+    pub fn new(mu: usize, lambda: usize) -> MuCommaLambda {
+        let mu = mu.max(1);
+        MuCommaLambda { mu, lambda: lambda.max(mu) }
+    }
+}
+
+impl<G> Replacement<G> for MuCommaLambda {
+    fn offspring_count(&self, _population_size: usize) -> usize {
+        self.lambda
+    }
+
+    fn replace(&mut self, _population: Vec<Individual<G>>, mut offspring: Vec<Individual<G>>,
+               _parents: &[(usize, usize)], _rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        sort_by_fitness(&mut offspring);
+        offspring.truncate(self.mu);
+        Ok(offspring)
+    }
+}
+
+/// Applies a mutation step size to a genome.
+pub type StepSizeSetter<G> = fn(&mut G, f64);
+
+/// The (1+1) evolution strategy with Rechenberg's 1/5 success rule: the selection of a
+/// `OnePlusLambda` with a single offspring, plus step size adaptation. After every window of
+/// offspring the step size grows if more than a fifth of them were strictly fitter than their
+/// parent and shrinks if fewer were, and is then handed to the surviving genome through the
+/// setter.
+#[derive(Clone, Copy, Debug)]
+pub struct OnePlusOne<G> {
+    selection: OnePlusLambda,
+    step_size: f64,
+    setter: StepSizeSetter<G>,
+    window: usize,
+    factor: f64,
+    trials: usize,
+    successes: usize
+}
+
+impl<G> OnePlusOne<G> {
+    /// Creates the strategy with a window of 10 offspring and a shrinking factor of 0.817.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>step_size - the step size the genomes start with</li>
+    /// <li>setter - applies a new step size to a genome</li>
+    /// </ul>
+    pub fn new(step_size: f64, setter: StepSizeSetter<G>) -> OnePlusOne<G> {
+        OnePlusOne { selection: OnePlusLambda::new(1), step_size, setter, window: 10, factor: 0.817, trials: 0, successes: 0 }
+    }
+
+    pub fn get_step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    /// Gets the share of offspring that were fitter than their parent in the current window.
+    pub fn get_success_rate(&self) -> f64 {
+        if self.trials == 0 {
+            0f64
+        } else {
+            self.successes as f64 / self.trials as f64
+        }
+    }
+
+    /// Sets how many offspring are counted between step size adaptations.
+    pub fn set_window(&mut self, window: usize) {
+        self.window = window.max(1);
+    }
+
+    /// Sets the factor the step size is multiplied by when too few offspring succeed and
+    /// divided by when too many do.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>Ok if successful.</li>
+    /// <li>InvalidParameterError if the factor isn't strictly between 0 and 1.</li>
+    /// </ul>
+    pub fn set_factor(&mut self, factor: f64) -> GeneticResult<()> {
+        if factor <= 0f64 || factor >= 1f64 {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        self.factor = factor;
+        Ok(())
+    }
+}
+
+impl<G> Replacement<G> for OnePlusOne<G> {
+    fn offspring_count(&self, population_size: usize) -> usize {
+        Replacement::<G>::offspring_count(&self.selection, population_size)
+    }
+
+    fn replace(&mut self, population: Vec<Individual<G>>, offspring: Vec<Individual<G>>,
+               parents: &[(usize, usize)], rng: &mut StdRng) -> GeneticResult<Vec<Individual<G>>> {
+        if !population.is_empty() {
+            let parent_fitness = population.iter().map(|individual| individual.get_fitness()).fold(f64::NEG_INFINITY, f64::max);
+            self.trials += offspring.len();
+            self.successes += offspring.iter().filter(|child| child.get_fitness() > parent_fitness).count();
+        }
+        let survivors = self.selection.replace(population, offspring, parents, rng)?;
+        if self.trials >= self.window {
+            let success_rate = self.get_success_rate();
+            if success_rate > 0.2 {
+                self.step_size /= self.factor;
+            } else if success_rate < 0.2 {
+                self.step_size *= self.factor;
+            }
+            self.trials = 0;
+            self.successes = 0;
+        }
+        Ok(survivors.into_iter()
+            .map(|survivor| {
+                let fitness = survivor.get_fitness();
+                let mut genome = survivor.into_genome();
+                (self.setter)(&mut genome, self.step_size);
+                Individual::new(genome, fitness)
+            })
+            .collect())
+    }
+}
+
+/// Finds the least fit of the given members of the population.
+fn least_fit<G, I: IntoIterator<Item = usize>>(population: &[Individual<G>], candidates: I) -> usize {
+    candidates.into_iter()
+        .min_by(|&a, &b| population[a].get_fitness().partial_cmp(&population[b].get_fitness()).unwrap_or(Ordering::Equal))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn one_plus_lambda_keeps_the_parent_unless_an_offspring_is_as_fit() {
+        let mut rng = StdRng::from_seed(&[22usize][..]);
+        let mut replacement = OnePlusLambda::new(3);
+        assert_eq!(3, Replacement::<u8>::offspring_count(&replacement, 1));
+        let parent = vec![Individual::new(0u8, 2f64)];
+        let worse = vec![Individual::new(1u8, 1f64), Individual::new(2u8, 0f64)];
+        let next = replacement.replace(parent.clone(), worse, &[], &mut rng).unwrap();
+        assert_eq!(parent, next);
+        let neutral = vec![Individual::new(1u8, 1f64), Individual::new(2u8, 2f64)];
+        let next = replacement.replace(parent, neutral, &[], &mut rng).unwrap();
+        assert_eq!(vec![Individual::new(2u8, 2f64)], next);
+    }
+
+    #[test]
+    fn steady_state_children_replace_the_chosen_member_and_join_as_youngest() {
+        let mut rng = StdRng::from_seed(&[23usize][..]);
+        let population = vec![Individual::new(0u8, 3f64), Individual::new(1u8, 1f64), Individual::new(2u8, 2f64)];
+        let child = vec![Individual::new(9u8, 0f64)];
+        let mut worst = SteadyState::new(1, SteadyStateDeletion::Worst);
+        let next = worst.replace(population.clone(), child.clone(), &[], &mut rng).unwrap();
+        let genomes: Vec<u8> = next.iter().map(|individual| *individual.get_genome()).collect();
+        assert_eq!(vec![0, 2, 9], genomes);
+        let mut oldest = SteadyState::new(1, SteadyStateDeletion::Oldest);
+        let next = oldest.replace(population.clone(), child.clone(), &[], &mut rng).unwrap();
+        let genomes: Vec<u8> = next.iter().map(|individual| *individual.get_genome()).collect();
+        assert_eq!(vec![1, 2, 9], genomes);
+        let mut loser = SteadyState::new(1, SteadyStateDeletion::TournamentLoser { size: 30 });
+        let next = loser.replace(population, child, &[], &mut rng).unwrap();
+        let genomes: Vec<u8> = next.iter().map(|individual| *individual.get_genome()).collect();
+        assert_eq!(vec![0, 2, 9], genomes);
+    }
+
+    #[test]
+    fn plus_strategy_keeps_fit_parents_and_comma_strategy_discards_them() {
+        let mut rng = StdRng::from_seed(&[24usize][..]);
+        let parents = vec![Individual::new(0u8, 5f64), Individual::new(1u8, 1f64)];
+        let offspring = vec![Individual::new(2u8, 2f64), Individual::new(3u8, 4f64), Individual::new(4u8, 3f64)];
+        let mut plus = MuPlusLambda::new(2, 3);
+        assert_eq!(3, Replacement::<u8>::offspring_count(&plus, 2));
+        let next = plus.replace(parents.clone(), offspring.clone(), &[], &mut rng).unwrap();
+        assert_eq!(vec![Individual::new(0u8, 5f64), Individual::new(3u8, 4f64)], next);
+        let mut comma = MuCommaLambda::new(2, 3);
+        let next = comma.replace(parents, offspring, &[], &mut rng).unwrap();
+        assert_eq!(vec![Individual::new(3u8, 4f64), Individual::new(4u8, 3f64)], next);
+        assert_eq!(4, Replacement::<u8>::offspring_count(&MuCommaLambda::new(4, 2), 4));
+    }
+
+    #[test]
+    fn one_plus_one_widens_steps_after_many_successes_and_narrows_after_few() {
+        let mut rng = StdRng::from_seed(&[25usize][..]);
+        let mut strategy = OnePlusOne::new(1f64, |genome: &mut f64, step_size| *genome = step_size);
+        strategy.set_window(5);
+        let mut population = vec![Individual::new(1f64, 0f64)];
+        for generation in 1..6 {
+            let child = vec![Individual::new(0f64, generation as f64)];
+            population = strategy.replace(population, child, &[], &mut rng).unwrap();
+        }
+        assert!(strategy.get_step_size() > 1f64);
+        assert_eq!(strategy.get_step_size(), *population[0].get_genome());
+        let widened = strategy.get_step_size();
+        for _ in 0..5 {
+            let child = vec![Individual::new(0f64, -1f64)];
+            population = strategy.replace(population, child, &[], &mut rng).unwrap();
+        }
+        assert!(strategy.get_step_size() < widened);
+        assert_eq!(5f64, population[0].get_fitness());
+        assert!(strategy.set_factor(1.5).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! Optimizers that improve a single genome rather than a population: simulated annealing and
+//! random-restart hill climbing. Both work with any `Mutable + Clone` genome, moving to the
+//! genomes one call of `mutate` away, so a `GeneticString` can be optimized with its existing
+//! mutation.
+//!
+//! Each step tries one neighbour and is reported to the observers and the termination
+//! criterion as a generation whose population is the current genome. The length of the genome
+//! isn't known, so the mean length of the statistics is NaN. Genomes whose evaluation fails
+//! get a fitness of negative infinity, so they are never accepted over a genome that can be
+//! evaluated.
+
+use rand::StdRng;
+
+use ::individual::Individual;
+use ::interfaces::fitness_function::FitnessFunction;
+use ::interfaces::optimizer::{Optimizer, Progress};
+use ::local_search::{self, Improvement};
+use ::mutable::Mutable;
+use ::statistics::GenerationStats;
+use ::GeneticResult;
+
+/// The lower bound on the factor adaptive cooling multiplies the temperature by.
+const MINIMUM_ADAPTIVE_FACTOR: f64 = 0.5;
+
+/// How the temperature of simulated annealing falls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoolingSchedule {
+    /// The temperature is multiplied by the factor after every step.
+    Geometric { initial_temperature: f64, factor: f64 },
+    /// The decrement is subtracted from the temperature after every step, down to 0.
+    Linear { initial_temperature: f64, decrement: f64 },
+    /// After every window of steps the temperature T is multiplied by e^(-λT/σ), where σ is
+    /// the standard deviation of the fitness of the current genome over the window, so it
+    /// falls slowly while the fitness still varies a lot and quickly once it settles. The
+    /// temperature never falls by more than half at once.
+    Adaptive { initial_temperature: f64, lambda: f64, window: usize }
+}
+
+impl CoolingSchedule {
+    fn get_initial_temperature(&self) -> f64 {
+        match *self {
+            CoolingSchedule::Geometric { initial_temperature, .. }
+            | CoolingSchedule::Linear { initial_temperature, .. }
+            | CoolingSchedule::Adaptive { initial_temperature, .. } => initial_temperature
+        }
+    }
+}
+
+/// Simulated annealing: neighbours are accepted by the Metropolis criterion of
+/// `local_search::metropolis`.
+pub struct SimulatedAnnealing<G, F> {
+    current: Individual<G>,
+    fitness_function: F,
+    schedule: CoolingSchedule,
+    temperature: f64,
+    reheating: Option<(usize, f64)>,
+    steps_without_improvement: usize,
+    reheats: usize,
+    window_fitness: Vec<f64>,
+    evaluated: bool,
+    step: usize,
+    evaluations: usize,
+    progress: Progress<G>
+}
+
+impl<G, F> SimulatedAnnealing<G, F> where G: Mutable + Clone, F: FitnessFunction<G> {
+    /// Creates the optimizer. The initial genome is evaluated when the first step is taken.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>initial - the genome to start from</li>
+    /// <li>fitness_function - scores the genomes, higher is fitter</li>
+    /// <li>schedule - how the temperature falls</li>
+    /// </ul>
+    pub fn new(initial: G, fitness_function: F, schedule: CoolingSchedule) -> SimulatedAnnealing<G, F> {
+        SimulatedAnnealing {
+            current: Individual::new(initial, 0f64),
+            fitness_function,
+            schedule,
+            temperature: schedule.get_initial_temperature(),
+            reheating: None,
+            steps_without_improvement: 0,
+            reheats: 0,
+            window_fitness: vec![],
+            evaluated: false,
+            step: 0,
+            evaluations: 0,
+            progress: Progress::new(f64::NEG_INFINITY)
+        }
+    }
+
+    /// Reheats whenever the best genome hasn't improved for the given number of steps,
+    /// setting the temperature to the given fraction of the initial temperature. There is no
+    /// reheating by default.
+    pub fn set_reheating(&mut self, steps_without_improvement: usize, temperature_fraction: f64) {
+        self.reheating = Some((steps_without_improvement.max(1), temperature_fraction.max(0f64)));
+    }
+
+    pub fn get_current(&self) -> &Individual<G> {
+        &self.current
+    }
+
+    pub fn get_temperature(&self) -> f64 {
+        self.temperature
+    }
+
+    pub fn get_reheats(&self) -> usize {
+        self.reheats
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn cool(&mut self) {
+        match self.schedule {
+            CoolingSchedule::Geometric { factor, .. } => self.temperature *= factor,
+            CoolingSchedule::Linear { decrement, .. } => self.temperature = (self.temperature - decrement).max(0f64),
+            CoolingSchedule::Adaptive { lambda, window, .. } => {
+                self.window_fitness.push(self.current.get_fitness());
+                if self.window_fitness.len() >= window.max(2) {
+                    let count = self.window_fitness.len() as f64;
+                    let mean = self.window_fitness.iter().sum::<f64>() / count;
+                    let deviation = (self.window_fitness.iter().map(|fitness| (fitness - mean).powi(2)).sum::<f64>()
+                        / count).sqrt();
+                    let factor = if deviation > 0f64 { (-lambda * self.temperature / deviation).exp() } else { 0f64 };
+                    self.temperature *= factor.max(MINIMUM_ADAPTIVE_FACTOR);
+                    self.window_fitness.clear();
+                }
+            }
+        }
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        GenerationStats::new(self.step, &[self.current.get_fitness()], f64::NAN, 1, self.evaluations)
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) -> bool {
+        let current = (self.current.get_genome(), self.current.get_fitness());
+        self.progress.update_best(::std::iter::once(current), stats)
+    }
+}
+
+impl<G, F> Optimizer<G> for SimulatedAnnealing<G, F> where G: Mutable + Clone, F: FitnessFunction<G> {
+    /// Evaluates the initial genome if that hasn't happened yet, reporting it to the observers
+    /// as the first best genome, and summarises the current genome.
+    fn get_statistics(&mut self, _rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if !self.evaluated {
+            self.evaluated = true;
+            self.evaluations += 1;
+            let fitness = self.fitness_function.evaluate(self.current.get_genome())
+                .unwrap_or(self.progress.get_invalid_fitness());
+            self.current.set_fitness(fitness);
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Tries one neighbour of the current genome, then cools or reheats.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.get_statistics(rng)?;
+        let invalid_fitness = self.progress.get_invalid_fitness();
+        let neighbour = local_search::neighbour(self.current.get_genome(), &self.fitness_function, invalid_fitness, rng)?;
+        self.evaluations += 1;
+        if local_search::metropolis(neighbour.get_fitness() - self.current.get_fitness(), self.temperature, rng) {
+            self.current = neighbour;
+        }
+        self.step += 1;
+        let stats = self.summarise();
+        if self.update_best(&stats) {
+            self.steps_without_improvement = 0;
+        } else {
+            self.steps_without_improvement += 1;
+        }
+        self.cool();
+        if let Some((patience, fraction)) = self.reheating {
+            if self.steps_without_improvement >= patience {
+                self.temperature = self.schedule.get_initial_temperature() * fraction;
+                self.steps_without_improvement = 0;
+                self.window_fitness.clear();
+                self.reheats += 1;
+            }
+        }
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<G> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<G> {
+        &mut self.progress
+    }
+}
+
+/// Hill climbing that moves to a neighbour whenever it is fitter and starts again from a
+/// fresh genome once a number of neighbours in a row has failed to improve on the current
+/// one.
+pub struct RandomRestartHillClimbing<G, F, R> {
+    current: Option<Individual<G>>,
+    factory: R,
+    fitness_function: F,
+    patience: usize,
+    failures: usize,
+    restarts: usize,
+    step: usize,
+    evaluations: usize,
+    progress: Progress<G>
+}
+
+impl<G, F, R> RandomRestartHillClimbing<G, F, R>
+    where G: Mutable + Clone, F: FitnessFunction<G>, R: Fn(&mut StdRng) -> GeneticResult<G> {
+    /// # Parameters
+    /// <ul>
+    /// <li>factory - generates the genome of every climb, and may capture state such as the
+    /// length of the genomes</li>
+    /// <li>fitness_function - scores the genomes, higher is fitter</li>
+    /// <li>patience - how many neighbours in a row may fail to improve before restarting</li>
+    /// </ul>
+    pub fn new(factory: R, fitness_function: F, patience: usize) -> RandomRestartHillClimbing<G, F, R> {
+        RandomRestartHillClimbing {
+            current: None,
+            factory,
+            fitness_function,
+            patience: patience.max(1),
+            failures: 0,
+            restarts: 0,
+            step: 0,
+            evaluations: 0,
+            progress: Progress::new(f64::NEG_INFINITY)
+        }
+    }
+
+    /// The genome of the current climb, once the first one has been generated.
+    pub fn get_current(&self) -> Option<&Individual<G>> {
+        self.current.as_ref()
+    }
+
+    pub fn get_restarts(&self) -> usize {
+        self.restarts
+    }
+
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    fn start_climb(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        let genome = (self.factory)(rng)?;
+        let fitness = self.evaluate(&genome);
+        self.current = Some(Individual::new(genome, fitness));
+        self.failures = 0;
+        Ok(())
+    }
+
+    fn current_individual(&self) -> &Individual<G> {
+        self.current.as_ref().expect("a climb is started before every step")
+    }
+
+    fn update_best(&mut self, stats: &GenerationStats) {
+        let current = self.current.as_ref().map(|current| (current.get_genome(), current.get_fitness()));
+        self.progress.update_best(current, stats);
+    }
+
+    fn evaluate(&mut self, genome: &G) -> f64 {
+        self.evaluations += 1;
+        self.fitness_function.evaluate(genome).unwrap_or(self.progress.get_invalid_fitness())
+    }
+
+    fn summarise(&self) -> GenerationStats {
+        let fitness = self.current.as_ref().map(|current| current.get_fitness()).unwrap_or(f64::NAN);
+        GenerationStats::new(self.step, &[fitness], f64::NAN, 1, self.evaluations)
+    }
+}
+
+impl<G, F, R> Optimizer<G> for RandomRestartHillClimbing<G, F, R>
+    where G: Mutable + Clone, F: FitnessFunction<G>, R: Fn(&mut StdRng) -> GeneticResult<G> {
+    /// Summarises the current genome. Before the first step the factory generates the genome
+    /// of the first climb.
+    fn get_statistics(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        if self.current.is_none() {
+            self.start_climb(rng)?;
+            let stats = self.summarise();
+            self.update_best(&stats);
+            return Ok(stats);
+        }
+        Ok(self.summarise())
+    }
+
+    /// Tries one neighbour of the current genome, or restarts from a fresh genome if the
+    /// climb has run out of patience.
+    fn next_generation(&mut self, rng: &mut StdRng) -> GeneticResult<GenerationStats> {
+        self.get_statistics(rng)?;
+        if self.failures >= self.patience {
+            self.start_climb(rng)?;
+            self.restarts += 1;
+        } else {
+            let invalid_fitness = self.progress.get_invalid_fitness();
+            let (chosen, evaluations) = local_search::climb(self.current_individual(), Improvement::First, 1,
+                                                            &self.fitness_function, invalid_fitness, rng)?;
+            self.evaluations += evaluations;
+            match chosen {
+                Some(chosen) => {
+                    self.current = Some(chosen);
+                    self.failures = 0;
+                },
+                None => self.failures += 1
+            }
+        }
+        self.step += 1;
+        let stats = self.summarise();
+        self.update_best(&stats);
+        self.progress.notify_generation(&stats);
+        Ok(stats)
+    }
+
+    fn get_progress(&self) -> &Progress<G> {
+        &self.progress
+    }
+
+    fn get_progress_mutable(&mut self) -> &mut Progress<G> {
+        &mut self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use rand::SeedableRng;
+    use ::bit_string::BitString;
+    use ::genetic_byte::GeneticByte;
+    use ::genetic_string::GeneticString;
+    use ::interfaces::fitness_function::TargetFitness;
+    use ::interfaces::observer::Observer;
+    use ::termination::{any, FitnessThreshold, MaxGenerations};
+
+    fn one_max(bit_string: &BitString) -> GeneticResult<f64> {
+        Ok(bit_string.count_ones() as f64)
+    }
+
+    struct BestRecorder {
+        best: Arc<Mutex<Vec<f64>>>
+    }
+
+    impl Observer<BitString> for BestRecorder {
+        fn on_new_best(&mut self, _best: &BitString, fitness: f64, _stats: &GenerationStats) {
+            self.best.lock().unwrap().push(fitness);
+        }
+    }
+
+    #[test]
+    fn every_cooling_schedule_cools_and_solves_one_max() {
+        let schedules = [
+            CoolingSchedule::Geometric { initial_temperature: 2f64, factor: 0.995 },
+            CoolingSchedule::Linear { initial_temperature: 2f64, decrement: 0.002 },
+            CoolingSchedule::Adaptive { initial_temperature: 2f64, lambda: 0.7, window: 20 }
+        ];
+        for (seed, &schedule) in schedules.iter().enumerate() {
+            let mut rng = StdRng::from_seed(&[100 + seed][..]);
+            let mut annealing = SimulatedAnnealing::new(BitString::new(40), one_max, schedule);
+            let mut termination = any(vec![Box::new(MaxGenerations::new(5000)), Box::new(FitnessThreshold::new(40f64))]);
+            let stats = annealing.run(&mut termination, &mut rng).unwrap();
+            assert_eq!(40f64, annealing.get_best().unwrap().get_fitness(), "{:?}", schedule);
+            assert!(annealing.get_temperature() < 2f64);
+            assert_eq!(stats.get_generation() + 1, annealing.get_evaluations());
+        }
+    }
+
+    #[test]
+    fn annealing_reheats_once_it_stops_improving() {
+        let mut rng = StdRng::from_seed(&[103usize][..]);
+        let flat = |_: &BitString| Ok(1f64);
+        let schedule = CoolingSchedule::Geometric { initial_temperature: 1f64, factor: 0.5 };
+        let mut annealing = SimulatedAnnealing::new(BitString::new(10), flat, schedule);
+        annealing.set_reheating(10, 0.5);
+        let best = Arc::new(Mutex::new(vec![]));
+        annealing.add_observer(Box::new(BestRecorder { best: best.clone() }));
+        for _ in 0..9 {
+            annealing.next_generation(&mut rng).unwrap();
+        }
+        assert!(annealing.get_temperature() < 0.01);
+        annealing.next_generation(&mut rng).unwrap();
+        assert_eq!(1, annealing.get_reheats());
+        assert_eq!(0.5, annealing.get_temperature());
+        assert_eq!(vec![1f64], *best.lock().unwrap());
+    }
+
+    #[test]
+    fn hill_climbing_restarts_after_running_out_of_patience() {
+        let mut rng = StdRng::from_seed(&[104usize][..]);
+        let length = 16;
+        let random_bit_string = move |rng: &mut StdRng| Ok(BitString::new_random(length, rng));
+        let mut climbing = RandomRestartHillClimbing::new(random_bit_string, one_max, 50);
+        let stats = climbing.run(&mut MaxGenerations::new(2000), &mut rng).unwrap();
+        assert!(climbing.get_restarts() > 0);
+        assert_eq!(16f64, climbing.get_best().unwrap().get_fitness());
+        assert_eq!(2001, stats.get_evaluations());
+    }
+
+    #[test]
+    fn genetic_strings_are_annealed_with_their_own_mutation() {
+        let mut rng = StdRng::from_seed(&[105usize][..]);
+        let initial: GeneticString<GeneticByte> = GeneticString::new(7, &mut rng).unwrap();
+        let schedule = CoolingSchedule::Geometric { initial_temperature: 0.1, factor: 0.99 };
+        let mut annealing = SimulatedAnnealing::new(initial, TargetFitness::new(50f64), schedule);
+        let start = annealing.get_statistics(&mut rng).unwrap().get_best_fitness();
+        annealing.run(&mut MaxGenerations::new(300), &mut rng).unwrap();
+        assert!(annealing.get_best().unwrap().get_fitness() > start);
+    }
+}
@@ -0,0 +1,205 @@
+//! Summary statistics describing one generation of a run.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use ::diversity::DiversityStats;
+use ::individual::Individual;
+use ::interfaces::genetic_collection::GeneticCollection;
+
+/// Fitness, size and diversity figures for a single generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    generation: usize,
+    best_fitness: f64,
+    mean_fitness: f64,
+    median_fitness: f64,
+    worst_fitness: f64,
+    standard_deviation: f64,
+    mean_length: f64,
+    unique_genomes: usize,
+    population_size: usize,
+    evaluations: usize,
+    diversity: Option<DiversityStats>
+}
+
+impl GenerationStats {
+    /// Summarises the fitness values of a generation.
+    ///
+    /// # Parameters
+    /// <ul>
+    /// <li>generation - the number of the generation</li>
+    /// <li>fitness - the fitness of every member of the population</li>
+    /// <li>mean_length - the mean size of the genomes</li>
+    /// <li>unique_genomes - how many distinct genomes the population holds</li>
+    /// <li>evaluations - the number of fitness evaluations made so far in the run</li>
+    /// </ul>
+    ///
+    /// # Returns
+    /// The statistics. Fitness figures are NaN for an empty population.
+    pub fn new(generation: usize, fitness: &[f64], mean_length: f64, unique_genomes: usize,
+               evaluations: usize) -> GenerationStats {
+        let mut sorted = fitness.to_vec();
+        sorted.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        let population_size = sorted.len();
+        let (best_fitness, worst_fitness, median_fitness) = if population_size == 0 {
+            (f64::NAN, f64::NAN, f64::NAN)
+        } else if population_size % 2 == 1 {
+            (sorted[0], sorted[population_size - 1], sorted[population_size / 2])
+        } else {
+            let middle = population_size / 2;
+            (sorted[0], sorted[population_size - 1], (sorted[middle - 1] + sorted[middle]) / 2f64)
+        };
+        let mean_fitness = mean(&sorted);
+        let variance = sorted.iter()
+            .map(|fitness| (fitness - mean_fitness).powi(2))
+            .sum::<f64>() / population_size as f64;
+        GenerationStats {
+            generation,
+            best_fitness,
+            mean_fitness,
+            median_fitness,
+            worst_fitness,
+            standard_deviation: variance.sqrt(),
+            mean_length,
+            unique_genomes,
+            population_size,
+            evaluations,
+            diversity: None
+        }
+    }
+
+    /// Summarises an evaluated population of genetic collections.
+    pub fn from_population<G>(generation: usize, population: &[Individual<G>], evaluations: usize)
+                              -> GenerationStats where G: GeneticCollection + Hash + Eq {
+        let fitness: Vec<f64> = population.iter().map(|individual| individual.get_fitness()).collect();
+        let lengths: Vec<f64> = population.iter()
+            .map(|individual| individual.get_genome().get_size() as f64)
+            .collect();
+        let unique_genomes = population.iter()
+            .map(|individual| individual.get_genome())
+            .collect::<HashSet<_>>()
+            .len();
+        GenerationStats::new(generation, &fitness, mean(&lengths), unique_genomes, evaluations)
+    }
+
+    /// Summarises a population of real vectors, such as the candidates of a continuous
+    /// optimizer. The mean length is the mean dimension of the vectors.
+    pub fn from_real_vectors(generation: usize, vectors: &[Vec<f64>], fitness: &[f64], evaluations: usize)
+                             -> GenerationStats {
+        let lengths: Vec<f64> = vectors.iter().map(|vector| vector.len() as f64).collect();
+        let unique_genomes = vectors.iter()
+            .map(|vector| vector.iter().map(|value| value.to_bits()).collect::<Vec<u64>>())
+            .collect::<HashSet<_>>()
+            .len();
+        GenerationStats::new(generation, fitness, mean(&lengths), unique_genomes, evaluations)
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get_best_fitness(&self) -> f64 {
+        self.best_fitness
+    }
+
+    pub fn get_mean_fitness(&self) -> f64 {
+        self.mean_fitness
+    }
+
+    pub fn get_median_fitness(&self) -> f64 {
+        self.median_fitness
+    }
+
+    pub fn get_worst_fitness(&self) -> f64 {
+        self.worst_fitness
+    }
+
+    pub fn get_standard_deviation(&self) -> f64 {
+        self.standard_deviation
+    }
+
+    pub fn get_mean_length(&self) -> f64 {
+        self.mean_length
+    }
+
+    pub fn get_unique_genomes(&self) -> usize {
+        self.unique_genomes
+    }
+
+    pub fn get_population_size(&self) -> usize {
+        self.population_size
+    }
+
+    /// The number of fitness evaluations made from the start of the run up to and
+    /// including this generation.
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// The diversity measures of the generation, if they were taken.
+    pub fn get_diversity(&self) -> Option<&DiversityStats> {
+        self.diversity.as_ref()
+    }
+
+    pub fn set_diversity(&mut self, diversity: DiversityStats) {
+        self.diversity = Some(diversity);
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::bit_string::BitString;
+
+    #[test]
+    fn fitness_summary_matches_hand_computed_values() {
+        let stats = GenerationStats::new(3, &[2f64, 4f64, 4f64, 4f64, 5f64, 5f64, 7f64, 9f64], 1f64, 5, 40);
+        assert_eq!(9f64, stats.get_best_fitness());
+        assert_eq!(2f64, stats.get_worst_fitness());
+        assert_eq!(5f64, stats.get_mean_fitness());
+        assert_eq!(4.5f64, stats.get_median_fitness());
+        assert_eq!(2f64, stats.get_standard_deviation());
+        assert_eq!(8, stats.get_population_size());
+        assert_eq!(40, stats.get_evaluations());
+    }
+
+    #[test]
+    fn empty_population_has_nan_fitness_figures() {
+        let stats = GenerationStats::new(0, &[], 0f64, 0, 0);
+        assert!(stats.get_best_fitness().is_nan());
+        assert!(stats.get_worst_fitness().is_nan());
+        assert!(stats.get_mean_fitness().is_nan());
+        assert!(stats.get_median_fitness().is_nan());
+        assert!(stats.get_standard_deviation().is_nan());
+        assert_eq!(0, stats.get_population_size());
+    }
+
+    #[test]
+    fn odd_sized_population_takes_the_middle_fitness_as_its_median() {
+        let stats = GenerationStats::new(0, &[7f64, 1f64, 3f64, 10f64, 2f64], 1f64, 5, 5);
+        assert_eq!(3f64, stats.get_median_fitness());
+        assert_eq!(10f64, stats.get_best_fitness());
+        assert_eq!(1f64, stats.get_worst_fitness());
+    }
+
+    #[test]
+    fn from_population_counts_unique_genomes_and_their_mean_length() {
+        let population = vec![
+            Individual::new(BitString::from_bools(&[true, false]), 1f64),
+            Individual::new(BitString::from_bools(&[true, false]), 1f64),
+            Individual::new(BitString::from_bools(&[true, true, true, true]), 4f64)
+        ];
+        let stats = GenerationStats::from_population(2, &population, 9);
+        assert_eq!(2, stats.get_unique_genomes());
+        assert_eq!(8f64 / 3f64, stats.get_mean_length());
+        assert_eq!(2f64, stats.get_mean_fitness());
+        assert_eq!(2, stats.get_generation());
+        assert_eq!(9, stats.get_evaluations());
+    }
+}
@@ -0,0 +1,207 @@
+//! Built in termination criteria and the `any`/`all` combinators used to compose them.
+
+use std::time::{Duration, Instant};
+
+use ::interfaces::termination::Termination;
+use ::statistics::GenerationStats;
+
+/// Stops once the given generation has been reached.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxGenerations {
+    generations: usize
+}
+
+impl MaxGenerations {
+    pub fn new(generations: usize) -> MaxGenerations {
+        MaxGenerations { generations }
+    }
+}
+
+impl Termination for MaxGenerations {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        stats.get_generation() >= self.generations
+    }
+}
+
+/// Stops once the run has made at least the given number of fitness evaluations.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxEvaluations {
+    evaluations: usize
+}
+
+impl MaxEvaluations {
+    pub fn new(evaluations: usize) -> MaxEvaluations {
+        MaxEvaluations { evaluations }
+    }
+}
+
+impl Termination for MaxEvaluations {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        stats.get_evaluations() >= self.evaluations
+    }
+}
+
+/// Stops once the budget of wall clock time has been used up. The clock starts the first
+/// time the criterion is checked.
+#[derive(Clone, Copy, Debug)]
+pub struct WallClock {
+    budget: Duration,
+    start: Option<Instant>
+}
+
+impl WallClock {
+    pub fn new(budget: Duration) -> WallClock {
+        WallClock { budget, start: None }
+    }
+}
+
+impl Termination for WallClock {
+    fn should_terminate(&mut self, _stats: &GenerationStats) -> bool {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        start.elapsed() >= self.budget
+    }
+}
+
+/// Stops once the best fitness reaches the threshold.
+#[derive(Clone, Copy, Debug)]
+pub struct FitnessThreshold {
+    threshold: f64
+}
+
+impl FitnessThreshold {
+    pub fn new(threshold: f64) -> FitnessThreshold {
+        FitnessThreshold { threshold }
+    }
+
+    /// Stops once a genome hits the target of `GeneticCollection::get_fitness` exactly,
+    /// which that method scores as an infinite fitness.
+    pub fn exact_hit() -> FitnessThreshold {
+        FitnessThreshold::new(f64::INFINITY)
+    }
+}
+
+impl Termination for FitnessThreshold {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        stats.get_best_fitness() >= self.threshold
+    }
+}
+
+/// Stops once the best fitness hasn't improved for the given number of generations.
+#[derive(Clone, Copy, Debug)]
+pub struct Stagnation {
+    generations: usize,
+    best_fitness: Option<f64>,
+    generations_without_improvement: usize
+}
+
+impl Stagnation {
+    pub fn new(generations: usize) -> Stagnation {
+        Stagnation { generations, best_fitness: None, generations_without_improvement: 0 }
+    }
+}
+
+impl Termination for Stagnation {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        match self.best_fitness {
+            Some(best_fitness) if stats.get_best_fitness() <= best_fitness => {
+                self.generations_without_improvement += 1;
+            },
+            _ => {
+                self.best_fitness = Some(stats.get_best_fitness());
+                self.generations_without_improvement = 0;
+            }
+        }
+        self.generations_without_improvement >= self.generations
+    }
+}
+
+/// Stops once the fraction of unique genomes in the population falls to or below the
+/// given fraction.
+#[derive(Clone, Copy, Debug)]
+pub struct DiversityCollapse {
+    minimum_unique_fraction: f64
+}
+
+impl DiversityCollapse {
+    pub fn new(minimum_unique_fraction: f64) -> DiversityCollapse {
+        DiversityCollapse { minimum_unique_fraction }
+    }
+}
+
+impl Termination for DiversityCollapse {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        if stats.get_population_size() == 0 {
+            return true;
+        }
+        let unique_fraction = stats.get_unique_genomes() as f64 / stats.get_population_size() as f64;
+        unique_fraction <= self.minimum_unique_fraction
+    }
+}
+
+/// Stops once any of its criteria says to stop.
+pub struct Any {
+    criteria: Vec<Box<dyn Termination + Send>>
+}
+
+/// Stops once all of its criteria say to stop.
+pub struct All {
+    criteria: Vec<Box<dyn Termination + Send>>
+}
+
+/// Combines criteria so the run stops as soon as one of them is met.
+pub fn any(criteria: Vec<Box<dyn Termination + Send>>) -> Any {
+    Any { criteria }
+}
+
+/// Combines criteria so the run stops only once every one of them is met.
+pub fn all(criteria: Vec<Box<dyn Termination + Send>>) -> All {
+    All { criteria }
+}
+
+/// Checks every criterion, without short circuiting, so that stateful criteria such as
+/// `Stagnation` see every generation.
+fn check_all(criteria: &mut [Box<dyn Termination + Send>], stats: &GenerationStats) -> Vec<bool> {
+    criteria.iter_mut().map(|criterion| criterion.should_terminate(stats)).collect()
+}
+
+impl Termination for Any {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        check_all(&mut self.criteria, stats).into_iter().any(|stop| stop)
+    }
+}
+
+impl Termination for All {
+    fn should_terminate(&mut self, stats: &GenerationStats) -> bool {
+        let results = check_all(&mut self.criteria, stats);
+        !results.is_empty() && results.into_iter().all(|stop| stop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(generation: usize, best_fitness: f64) -> GenerationStats {
+        GenerationStats::new(generation, &[best_fitness, 0f64], 1f64, 2, generation * 2)
+    }
+
+    #[test]
+    fn stagnation_stops_after_generations_without_improvement() {
+        let mut stagnation = Stagnation::new(2);
+        assert!(!stagnation.should_terminate(&stats(0, 1f64)));
+        assert!(!stagnation.should_terminate(&stats(1, 1f64)));
+        assert!(!stagnation.should_terminate(&stats(2, 3f64)));
+        assert!(!stagnation.should_terminate(&stats(3, 3f64)));
+        assert!(stagnation.should_terminate(&stats(4, 2f64)));
+    }
+
+    #[test]
+    fn any_stops_when_one_criterion_is_met_and_all_waits_for_every_criterion() {
+        let mut either = any(vec![Box::new(MaxGenerations::new(5)), Box::new(FitnessThreshold::exact_hit())]);
+        let mut both = all(vec![Box::new(MaxGenerations::new(5)), Box::new(MaxEvaluations::new(20))]);
+        assert!(!either.should_terminate(&stats(1, 4f64)));
+        assert!(either.should_terminate(&stats(2, f64::INFINITY)));
+        assert!(!both.should_terminate(&stats(6, 0f64)));
+        assert!(both.should_terminate(&stats(10, 0f64)));
+    }
+}
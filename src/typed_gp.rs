@@ -0,0 +1,592 @@
+//! Strongly typed genetic programming.
+//!
+//! Where a `GeneticCollectionComponent` only tells operators from values, every primitive of a
+//! `TypedPrimitiveSet` declares the types of its arguments and of its result. Trees are only
+//! ever grown, crossed and mutated by putting a subtree where one of the same type was, so every
+//! `TypedGpTree` is a well typed program mixing booleans, integers and floats.
+
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use rand::{Rng, StdRng};
+
+use ::error::GeneticError;
+use ::gp_tree::GpMutation;
+use ::interfaces::genetic_collection::GeneticCollection;
+use ::mutable::Mutable;
+use ::prefix_tree::{self, DEFAULT_MAXIMUM_DEPTH, MUTATION_DEPTH};
+use ::GeneticResult;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GpType {
+    Boolean,
+    Integer,
+    Float
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GpValue {
+    Boolean(bool),
+    Integer(i32),
+    Float(f64)
+}
+
+impl GpValue {
+    pub fn get_type(&self) -> GpType {
+        match *self {
+            GpValue::Boolean(_) => GpType::Boolean,
+            GpValue::Integer(_) => GpType::Integer,
+            GpValue::Float(_) => GpType::Float
+        }
+    }
+
+    /// The value as a number, where true is 1 and false is 0.
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            GpValue::Boolean(value) => if value { 1f64 } else { 0f64 },
+            GpValue::Integer(value) => value as f64,
+            GpValue::Float(value) => value
+        }
+    }
+}
+
+impl Display for GpValue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            GpValue::Boolean(value) => write!(f, "{}", value),
+            GpValue::Integer(value) => write!(f, "{}", value),
+            GpValue::Float(value) => write!(f, "{:?}", value)
+        }
+    }
+}
+
+/// Computes the result of a function from its arguments, which are guaranteed to have the
+/// declared argument types.
+pub type PrimitiveFunction = fn(&[GpValue]) -> GeneticResult<GpValue>;
+
+#[derive(Clone, Debug)]
+enum PrimitiveKind {
+    Function(PrimitiveFunction),
+    Constant(GpValue),
+    Variable(usize)
+}
+
+/// A function or terminal together with its signature.
+#[derive(Clone, Debug)]
+pub struct TypedPrimitive {
+    name: String,
+    argument_types: Vec<GpType>,
+    return_type: GpType,
+    kind: PrimitiveKind
+}
+
+impl TypedPrimitive {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_argument_types(&self) -> &[GpType] {
+        &self.argument_types
+    }
+
+    pub fn get_return_type(&self) -> GpType {
+        self.return_type
+    }
+
+    pub fn get_arity(&self) -> usize {
+        self.argument_types.len()
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.argument_types.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TypedPrimitiveSet {
+    primitives: Vec<TypedPrimitive>,
+    variable_types: Vec<GpType>
+}
+
+impl TypedPrimitiveSet {
+    pub fn new() -> TypedPrimitiveSet {
+        TypedPrimitiveSet { primitives: vec!(), variable_types: vec!() }
+    }
+
+    /// Integer and float arithmetic, comparisons, boolean logic and a conditional for each
+    /// type, with a few constants of every type and a variable for each of the given types.
+    pub fn standard(variable_types: &[GpType]) -> TypedPrimitiveSet {
+        use self::GpType::{Boolean, Integer, Float};
+        let mut set = TypedPrimitiveSet::new();
+        set.add_function("add", vec![Integer, Integer], Integer, |a| checked(a[0], a[1], i32::checked_add));
+        set.add_function("sub", vec![Integer, Integer], Integer, |a| checked(a[0], a[1], i32::checked_sub));
+        set.add_function("mul", vec![Integer, Integer], Integer, |a| checked(a[0], a[1], i32::checked_mul));
+        set.add_function("div", vec![Integer, Integer], Integer, |a| checked(a[0], a[1], i32::checked_div));
+        set.add_function("fadd", vec![Float, Float], Float, |a| Ok(GpValue::Float(a[0].to_f64() + a[1].to_f64())));
+        set.add_function("fsub", vec![Float, Float], Float, |a| Ok(GpValue::Float(a[0].to_f64() - a[1].to_f64())));
+        set.add_function("fmul", vec![Float, Float], Float, |a| Ok(GpValue::Float(a[0].to_f64() * a[1].to_f64())));
+        // Protected division, so a zero denominator doesn't end the evaluation.
+        set.add_function("fdiv", vec![Float, Float], Float, |a| {
+            let denominator = a[1].to_f64();
+            Ok(GpValue::Float(if denominator == 0f64 { 1f64 } else { a[0].to_f64() / denominator }))
+        });
+        set.add_function("float", vec![Integer], Float, |a| Ok(GpValue::Float(a[0].to_f64())));
+        set.add_function("lt", vec![Integer, Integer], Boolean, |a| Ok(GpValue::Boolean(a[0].to_f64() < a[1].to_f64())));
+        set.add_function("gt", vec![Integer, Integer], Boolean, |a| Ok(GpValue::Boolean(a[0].to_f64() > a[1].to_f64())));
+        set.add_function("eq", vec![Integer, Integer], Boolean, |a| Ok(GpValue::Boolean(a[0] == a[1])));
+        set.add_function("flt", vec![Float, Float], Boolean, |a| Ok(GpValue::Boolean(a[0].to_f64() < a[1].to_f64())));
+        set.add_function("and", vec![Boolean, Boolean], Boolean, |a| Ok(GpValue::Boolean(a[0] == GpValue::Boolean(true) && a[1] == GpValue::Boolean(true))));
+        set.add_function("or", vec![Boolean, Boolean], Boolean, |a| Ok(GpValue::Boolean(a[0] == GpValue::Boolean(true) || a[1] == GpValue::Boolean(true))));
+        set.add_function("not", vec![Boolean], Boolean, |a| Ok(GpValue::Boolean(a[0] == GpValue::Boolean(false))));
+        set.add_function("if", vec![Boolean, Integer, Integer], Integer, |a| Ok(if a[0] == GpValue::Boolean(true) { a[1] } else { a[2] }));
+        set.add_function("fif", vec![Boolean, Float, Float], Float, |a| Ok(if a[0] == GpValue::Boolean(true) { a[1] } else { a[2] }));
+        for &value in &[GpValue::Boolean(true), GpValue::Boolean(false), GpValue::Integer(0), GpValue::Integer(1),
+                        GpValue::Integer(2), GpValue::Integer(3), GpValue::Float(0.5f64), GpValue::Float(1f64)] {
+            set.add_constant(&value.to_string(), value);
+        }
+        for (index, &variable_type) in variable_types.iter().enumerate() {
+            set.add_variable(&format!("x{}", index), variable_type);
+        }
+        set
+    }
+
+    /// Adds a function and returns its index in the set.
+    pub fn add_function(&mut self, name: &str, argument_types: Vec<GpType>, return_type: GpType,
+                        function: PrimitiveFunction) -> usize {
+        self.push(name, argument_types, return_type, PrimitiveKind::Function(function))
+    }
+
+    /// Adds a terminal that always has the given value and returns its index in the set.
+    pub fn add_constant(&mut self, name: &str, value: GpValue) -> usize {
+        self.push(name, vec!(), value.get_type(), PrimitiveKind::Constant(value))
+    }
+
+    /// Adds a terminal that reads the next input of `TypedGpTree::evaluate` and returns its
+    /// index in the set.
+    pub fn add_variable(&mut self, name: &str, variable_type: GpType) -> usize {
+        let input = self.variable_types.len();
+        self.variable_types.push(variable_type);
+        self.push(name, vec!(), variable_type, PrimitiveKind::Variable(input))
+    }
+
+    pub fn get_primitives(&self) -> &[TypedPrimitive] {
+        &self.primitives
+    }
+
+    /// The types of the inputs `TypedGpTree::evaluate` expects.
+    pub fn get_variable_types(&self) -> &[GpType] {
+        &self.variable_types
+    }
+
+    /// The index of the first primitive with the given name.
+    pub fn get_index(&self, name: &str) -> Option<usize> {
+        self.primitives.iter().position(|primitive| primitive.name == name)
+    }
+
+    fn push(&mut self, name: &str, argument_types: Vec<GpType>, return_type: GpType, kind: PrimitiveKind) -> usize {
+        self.primitives.push(TypedPrimitive { name: name.to_string(), argument_types, return_type, kind });
+        self.primitives.len() - 1
+    }
+
+    /// The indices of the primitives returning the given type that pass the filter.
+    fn candidates<P: Fn(&TypedPrimitive) -> bool>(&self, return_type: GpType, filter: P) -> Vec<usize> {
+        (0..self.primitives.len())
+            .filter(|&index| self.primitives[index].return_type == return_type && filter(&self.primitives[index]))
+            .collect()
+    }
+}
+
+fn checked<O: Fn(i32, i32) -> Option<i32>>(a: GpValue, b: GpValue, operation: O) -> GeneticResult<GpValue> {
+    match (a, b) {
+        (GpValue::Integer(a), GpValue::Integer(b)) =>
+            operation(a, b).map(GpValue::Integer).ok_or(GeneticError::ComputationError),
+        _ => Err(GeneticError::ComputationError)
+    }
+}
+
+/// A program tree whose nodes are indices into a primitive set, stored in prefix order.
+#[derive(Clone, Debug)]
+pub struct TypedGpTree {
+    nodes: Vec<usize>,
+    primitives: Arc<TypedPrimitiveSet>,
+    maximum_depth: usize,
+    mutation: GpMutation
+}
+
+impl GeneticCollection for TypedGpTree {
+    type Child = TypedGpTree;
+
+    /// Breeds a child by typed subtree crossover followed by mutation.
+    fn breed(&self, mate: &Self, rng: &mut StdRng) -> GeneticResult<Self::Child> {
+        let mut child = self.subtree_crossover(mate, rng);
+        child.mutate(rng)?;
+        Ok(child)
+    }
+
+    fn get_size(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl Mutable for TypedGpTree {
+    fn mutate(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        match self.mutation {
+            GpMutation::Subtree => self.subtree_mutation(rng),
+            GpMutation::Point => {
+                self.point_mutation(rng);
+                Ok(())
+            },
+            GpMutation::Hoist => {
+                self.hoist_mutation(rng);
+                Ok(())
+            },
+            GpMutation::Shrink => {
+                self.shrink_mutation(rng);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl PartialEq for TypedGpTree {
+    fn eq(&self, other: &TypedGpTree) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+impl Eq for TypedGpTree {}
+
+impl Hash for TypedGpTree {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nodes.hash(state);
+    }
+}
+
+impl Display for TypedGpTree {
+    /// Writes the program in prefix form, e.g. `if(gt(x0, 3), x1, 2)`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write_subtree(0, f).map(|_| ())
+    }
+}
+
+impl TypedGpTree {
+    /// Creates a tree from primitive indices in prefix order.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tree if successful.</li>
+    /// <li>InvalidParameterError if an index is out of range, the nodes don't form exactly one
+    /// complete tree or an argument has the wrong type.</li>
+    /// </ul>
+    pub fn from_nodes(nodes: Vec<usize>, primitives: Arc<TypedPrimitiveSet>) -> GeneticResult<TypedGpTree> {
+        let tree = TypedGpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree };
+        if tree.nodes.is_empty() || tree.nodes.iter().any(|&node| node >= tree.primitives.primitives.len()) {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        match tree.check_types(0) {
+            Some(end) if end == tree.nodes.len() => Ok(tree),
+            _ => Err(GeneticError::InvalidParameterError)
+        }
+    }
+
+    /// Creates a tree returning the given type in which every branch reaches the given depth,
+    /// unless no function returns the type a branch needs.
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The tree if successful.</li>
+    /// <li>InvalidParameterError if a branch needs a terminal of a type the set has none of.</li>
+    /// </ul>
+    pub fn new_full(return_type: GpType, depth: usize, primitives: Arc<TypedPrimitiveSet>,
+                    rng: &mut StdRng) -> GeneticResult<TypedGpTree> {
+        let mut nodes = vec!();
+        generate(&primitives, return_type, depth, true, &mut nodes, rng)?;
+        Ok(TypedGpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree })
+    }
+
+    /// Creates a tree returning the given type whose branches stop growing at random, up to
+    /// the given depth.
+    pub fn new_grow(return_type: GpType, depth: usize, primitives: Arc<TypedPrimitiveSet>,
+                    rng: &mut StdRng) -> GeneticResult<TypedGpTree> {
+        let mut nodes = vec!();
+        generate(&primitives, return_type, depth, false, &mut nodes, rng)?;
+        Ok(TypedGpTree { nodes, primitives, maximum_depth: DEFAULT_MAXIMUM_DEPTH, mutation: GpMutation::Subtree })
+    }
+
+    /// Ramped half-and-half initialisation, see `prefix_tree::ramped_half_and_half`.
+    pub fn ramped_half_and_half(population_size: usize, minimum_depth: usize, maximum_depth: usize,
+                                return_type: GpType, primitives: Arc<TypedPrimitiveSet>,
+                                rng: &mut StdRng) -> GeneticResult<Vec<TypedGpTree>> {
+        prefix_tree::ramped_half_and_half(population_size, minimum_depth, maximum_depth, |depth, full| if full {
+            TypedGpTree::new_full(return_type, depth, primitives.clone(), rng)
+        } else {
+            TypedGpTree::new_grow(return_type, depth, primitives.clone(), rng)
+        })
+    }
+
+    pub fn get_nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+
+    pub fn get_primitives(&self) -> &Arc<TypedPrimitiveSet> {
+        &self.primitives
+    }
+
+    /// The type of the value the program returns.
+    pub fn get_return_type(&self) -> GpType {
+        self.primitive(0).return_type
+    }
+
+    /// The depth of the tree, where a lone terminal has depth 0.
+    pub fn get_depth(&self) -> usize {
+        prefix_tree::subtree_depth(&self.nodes, 0, &self.arity())
+    }
+
+    pub fn set_maximum_depth(&mut self, maximum_depth: usize) {
+        self.maximum_depth = maximum_depth;
+    }
+
+    pub fn set_mutation(&mut self, mutation: GpMutation) {
+        self.mutation = mutation;
+    }
+
+    /// Runs the program. Every argument of a function is evaluated, including the branch a
+    /// conditional doesn't take.
+    ///
+    /// # Parameters
+    /// inputs - the values of the variables, in the order they were added to the set
+    ///
+    /// # Returns
+    /// <ul>
+    /// <li>The value of the program if successful.</li>
+    /// <li>InvalidParameterError if an input the program reads is missing or has the wrong
+    /// type.</li>
+    /// <li>Any error a function returns, e.g. ComputationError on integer overflow.</li>
+    /// </ul>
+    pub fn evaluate(&self, inputs: &[GpValue]) -> GeneticResult<GpValue> {
+        let mut stack: Vec<GpValue> = vec!();
+        for &node in self.nodes.iter().rev() {
+            let primitive = &self.primitives.primitives[node];
+            let value = match primitive.kind {
+                PrimitiveKind::Constant(value) => value,
+                PrimitiveKind::Variable(input) => match inputs.get(input) {
+                    Some(value) if value.get_type() == primitive.return_type => *value,
+                    _ => return Err(GeneticError::InvalidParameterError)
+                },
+                PrimitiveKind::Function(function) => {
+                    let mut arguments = Vec::with_capacity(primitive.get_arity());
+                    for _ in 0..primitive.get_arity() {
+                        arguments.push(stack.pop().ok_or(GeneticError::ComputationError)?);
+                    }
+                    function(&arguments)?
+                }
+            };
+            stack.push(value);
+        }
+        stack.pop().ok_or(GeneticError::ComputationError)
+    }
+
+    /// Replaces a random subtree of self with a random subtree of the mate returning the same
+    /// type. The crossover is retried a few times if the mate has no such subtree or the child
+    /// would exceed the maximum depth, after which self is returned unchanged.
+    pub fn subtree_crossover(&self, mate: &TypedGpTree, rng: &mut StdRng) -> TypedGpTree {
+        let child = prefix_tree::subtree_crossover(&self.nodes, &mate.nodes, self.maximum_depth, &self.arity(), || {
+            let target = self.crossover_point(None, rng).unwrap_or(0);
+            let donor = mate.crossover_point(Some(self.primitive(target).return_type), rng)?;
+            Some((target, donor))
+        });
+        TypedGpTree { nodes: child.unwrap_or_else(|| self.nodes.clone()), ..self.clone() }
+    }
+
+    /// Replaces a random subtree with a newly grown one of the same type.
+    pub fn subtree_mutation(&mut self, rng: &mut StdRng) -> GeneticResult<()> {
+        let target = rng.gen_range(0, self.nodes.len());
+        let depth_left = self.maximum_depth.saturating_sub(prefix_tree::depth_of(&self.nodes, target, &self.arity()));
+        let mut replacement = vec!();
+        generate(&self.primitives, self.primitive(target).return_type, MUTATION_DEPTH.min(depth_left),
+                 false, &mut replacement, rng)?;
+        *self = self.replace_subtree(target, &replacement);
+        Ok(())
+    }
+
+    /// Replaces a random node with a primitive of the same signature.
+    pub fn point_mutation(&mut self, rng: &mut StdRng) {
+        let target = rng.gen_range(0, self.nodes.len());
+        let current = self.primitive(target).clone();
+        let same_signature = self.primitives.candidates(current.return_type,
+            |candidate| candidate.argument_types == current.argument_types);
+        self.nodes[target] = same_signature[rng.gen_range(0, same_signature.len())];
+    }
+
+    /// Replaces the tree with one of its subtrees returning the same type.
+    pub fn hoist_mutation(&mut self, rng: &mut StdRng) {
+        let return_type = self.get_return_type();
+        let candidates: Vec<usize> = (0..self.nodes.len())
+            .filter(|&index| self.primitive(index).return_type == return_type)
+            .collect();
+        let root = candidates[rng.gen_range(0, candidates.len())];
+        let end = prefix_tree::subtree_end(&self.nodes, root, &self.arity()).unwrap_or(self.nodes.len());
+        self.nodes = self.nodes[root..end].to_vec();
+    }
+
+    /// Replaces a random function node with a terminal of the same type, if the set has one.
+    pub fn shrink_mutation(&mut self, rng: &mut StdRng) {
+        let candidates: Vec<(usize, Vec<usize>)> = (0..self.nodes.len())
+            .filter(|&index| !self.primitive(index).is_terminal())
+            .map(|index| (index, self.primitives.candidates(self.primitive(index).return_type, TypedPrimitive::is_terminal)))
+            .filter(|(_, terminals)| !terminals.is_empty())
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        let (target, ref terminals) = candidates[rng.gen_range(0, candidates.len())];
+        let terminal = terminals[rng.gen_range(0, terminals.len())];
+        *self = self.replace_subtree(target, &[terminal]);
+    }
+
+    fn primitive(&self, index: usize) -> &TypedPrimitive {
+        &self.primitives.primitives[self.nodes[index]]
+    }
+
+    /// The arity of a node, as the `prefix_tree` helpers expect it.
+    fn arity(&self) -> impl Fn(&usize) -> usize + '_ {
+        move |&node| self.primitives.primitives[node].get_arity()
+    }
+
+    /// A crossover point restricted to nodes returning the given type, see
+    /// `prefix_tree::crossover_point`.
+    fn crossover_point(&self, return_type: Option<GpType>, rng: &mut StdRng) -> Option<usize> {
+        let returns_type = |index: usize| return_type.is_none_or(|wanted| self.primitive(index).return_type == wanted);
+        prefix_tree::crossover_point(&self.nodes, returns_type, &self.arity(), rng)
+    }
+
+    fn replace_subtree(&self, root: usize, replacement: &[usize]) -> TypedGpTree {
+        TypedGpTree { nodes: prefix_tree::replace_subtree(&self.nodes, root, replacement, &self.arity()), ..self.clone() }
+    }
+
+    /// Checks the subtree rooted at the given index, returning where it ends if it's complete
+    /// and every argument has the type its function declares.
+    fn check_types(&self, root: usize) -> Option<usize> {
+        let primitive = self.primitives.primitives.get(*self.nodes.get(root)?)?;
+        let mut child = root + 1;
+        for &argument_type in &primitive.argument_types {
+            if self.primitives.primitives.get(*self.nodes.get(child)?)?.return_type != argument_type {
+                return None;
+            }
+            child = self.check_types(child)?;
+        }
+        Some(child)
+    }
+
+    fn write_subtree(&self, index: usize, f: &mut Formatter) -> Result<usize, fmt::Error> {
+        let primitive = self.primitive(index);
+        write!(f, "{}", primitive.name)?;
+        let mut child = index + 1;
+        if !primitive.is_terminal() {
+            write!(f, "(")?;
+            for argument in 0..primitive.get_arity() {
+                if argument > 0 {
+                    write!(f, ", ")?;
+                }
+                child = self.write_subtree(child, f)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(child)
+    }
+}
+
+/// Appends a random tree returning the given type of at most the given depth to the nodes, in
+/// prefix order.
+fn generate(primitives: &TypedPrimitiveSet, return_type: GpType, depth: usize, full: bool,
+            nodes: &mut Vec<usize>, rng: &mut StdRng) -> GeneticResult<()> {
+    let terminals = primitives.candidates(return_type, TypedPrimitive::is_terminal);
+    let functions = primitives.candidates(return_type, |primitive| !primitive.is_terminal());
+    let use_terminal = if depth == 0 || functions.is_empty() {
+        true
+    } else if full || terminals.is_empty() {
+        false
+    } else {
+        rng.gen::<f64>() < terminals.len() as f64 / (terminals.len() + functions.len()) as f64
+    };
+    if use_terminal {
+        if terminals.is_empty() {
+            return Err(GeneticError::InvalidParameterError);
+        }
+        nodes.push(terminals[rng.gen_range(0, terminals.len())]);
+        return Ok(());
+    }
+    let function = functions[rng.gen_range(0, functions.len())];
+    nodes.push(function);
+    for &argument_type in &primitives.primitives[function].argument_types {
+        generate(primitives, argument_type, depth - 1, full, nodes, rng)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn index(primitives: &TypedPrimitiveSet, name: &str) -> usize {
+        primitives.get_index(name).unwrap()
+    }
+
+    #[test]
+    fn hand_built_conditional_evaluates_by_its_typed_inputs() {
+        let primitives = TypedPrimitiveSet::standard(&[GpType::Integer, GpType::Float, GpType::Float]);
+        // if x0 > 3 then x1 else x2
+        let nodes = vec![index(&primitives, "fif"), index(&primitives, "gt"), index(&primitives, "x0"),
+                         index(&primitives, "3"), index(&primitives, "x1"), index(&primitives, "x2")];
+        let tree = TypedGpTree::from_nodes(nodes, Arc::new(primitives)).unwrap();
+        assert_eq!("fif(gt(x0, 3), x1, x2)", tree.to_string());
+        assert_eq!(GpType::Float, tree.get_return_type());
+        let inputs = |x| vec![GpValue::Integer(x), GpValue::Float(1.5f64), GpValue::Float(-2f64)];
+        assert_eq!(GpValue::Float(1.5f64), tree.evaluate(&inputs(4)).unwrap());
+        assert_eq!(GpValue::Float(-2f64), tree.evaluate(&inputs(3)).unwrap());
+        assert!(tree.evaluate(&[GpValue::Float(4f64)]).is_err());
+    }
+
+    #[test]
+    fn ill_typed_nodes_are_rejected() {
+        let primitives = TypedPrimitiveSet::standard(&[]);
+        let nodes = vec![index(&primitives, "add"), index(&primitives, "true"), index(&primitives, "1")];
+        assert!(TypedGpTree::from_nodes(nodes, Arc::new(primitives.clone())).is_err());
+        let incomplete = vec![index(&primitives, "add"), index(&primitives, "1")];
+        assert!(TypedGpTree::from_nodes(incomplete, Arc::new(primitives)).is_err());
+    }
+
+    #[test]
+    fn ramped_half_and_half_only_builds_well_typed_programs() {
+        let mut rng = StdRng::from_seed(&[18usize][..]);
+        let primitives = Arc::new(TypedPrimitiveSet::standard(&[GpType::Integer, GpType::Boolean]));
+        let inputs = [GpValue::Integer(7), GpValue::Boolean(true)];
+        let trees = TypedGpTree::ramped_half_and_half(20, 1, 4, GpType::Boolean, primitives, &mut rng).unwrap();
+        for tree in &trees {
+            assert!(TypedGpTree::from_nodes(tree.get_nodes().to_vec(), tree.get_primitives().clone()).is_ok());
+            if let Ok(value) = tree.evaluate(&inputs) {
+                assert_eq!(GpType::Boolean, value.get_type());
+            }
+        }
+    }
+
+    #[test]
+    fn crossover_and_mutation_keep_programs_well_typed_and_shallow() {
+        let mut rng = StdRng::from_seed(&[19usize][..]);
+        let primitives = Arc::new(TypedPrimitiveSet::standard(&[GpType::Float]));
+        let mut first = TypedGpTree::new_full(GpType::Integer, 4, primitives.clone(), &mut rng).unwrap();
+        let second = TypedGpTree::new_full(GpType::Integer, 4, primitives, &mut rng).unwrap();
+        first.set_maximum_depth(5);
+        for &mutation in &[GpMutation::Subtree, GpMutation::Point, GpMutation::Hoist, GpMutation::Shrink] {
+            first.set_mutation(mutation);
+            for _ in 0..20 {
+                let child = first.breed(&second, &mut rng).unwrap();
+                assert!(child.get_depth() <= 5);
+                assert_eq!(GpType::Integer, child.get_return_type());
+                assert!(TypedGpTree::from_nodes(child.get_nodes().to_vec(), child.get_primitives().clone()).is_ok());
+            }
+        }
+    }
+}